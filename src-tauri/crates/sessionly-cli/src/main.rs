@@ -0,0 +1,446 @@
+//! Headless command-line access to Claude Code sessions. Depends only on
+//! sessionly-core, so it builds without Tauri or any GUI libraries.
+
+use chrono::{Local, TimeZone};
+use serde::Serialize;
+use sessionly_core::session_types::{ProjectGroup, Session, SessionSummary};
+use sessionly_core::mcp::McpServer;
+use sessionly_core::{html_export, json_export, markdown_export, redaction, session_store, settings};
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Usage: sessionly <command> [options]
+
+Commands:
+  list [options]              List sessions, newest first
+      --projects              List projects instead of sessions
+      --project <text>        Only projects whose path contains <text>
+      --branch <name>         Only sessions on this git branch
+      --source <label>        Only sessions from this data root
+      --since <when>          Only sessions started after a date (YYYY-MM-DD)
+                              or a duration ago (e.g. 12h, 7d)
+      --limit <n>             Show at most <n> rows
+  show <id>                   Show a session's metadata and transcript
+  export <id> [options]       Print an export of a session
+      --format <fmt>          md (default), html, json or jsonl
+      --output <file>         Write to <file> instead of stdout
+      --no-redact             Skip the configured redaction
+  path <id>                   Print the path of a session's transcript
+  mcp [options]               Serve sessions to Claude Code over MCP (stdio)
+      --no-redact             Skip the configured redaction
+
+Options:
+  --json                      Print JSON instead of a table (list, show)
+  -h, --help                  Show this help
+  -V, --version               Show the version
+
+<id> may be any unique prefix of a session id.";
+
+const FIRST_MESSAGE_WIDTH: usize = 60;
+
+#[derive(Default)]
+struct Options {
+    positional: Vec<String>,
+    json: bool,
+    projects: bool,
+    project: Option<String>,
+    branch: Option<String>,
+    source: Option<String>,
+    since: Option<i64>,
+    limit: Option<usize>,
+    format: Option<String>,
+    output: Option<String>,
+    no_redact: bool,
+}
+
+/// Parse a date (YYYY-MM-DD, local midnight) or a duration ago (30m, 12h, 7d) into ms since epoch
+fn parse_since(value: &str) -> Result<i64, String> {
+    if let Ok(date) = chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        let midnight = date.and_hms_opt(0, 0, 0).expect("midnight is valid");
+        return Local
+            .from_local_datetime(&midnight)
+            .earliest()
+            .map(|dt| dt.timestamp_millis())
+            .ok_or_else(|| format!("Invalid date '{}'", value));
+    }
+    let split = value.char_indices().last().map_or(0, |(i, _)| i);
+    let (amount, unit) = value.split_at(split);
+    let amount: i64 = amount.parse().map_err(|_| format!("Invalid --since '{}'", value))?;
+    let unit_ms = match unit {
+        "m" => 60_000,
+        "h" => 3_600_000,
+        "d" => 86_400_000,
+        "w" => 7 * 86_400_000,
+        _ => return Err(format!("Invalid --since '{}', expected YYYY-MM-DD or e.g. 7d", value)),
+    };
+    Ok(chrono::Utc::now().timestamp_millis() - amount * unit_ms)
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options::default();
+    while let Some(arg) = args.next() {
+        let mut value =
+            |name: &str| args.next().ok_or_else(|| format!("{} requires a value", name));
+        match arg.as_str() {
+            "--json" => options.json = true,
+            "--projects" => options.projects = true,
+            "--no-redact" => options.no_redact = true,
+            "--project" => options.project = Some(value("--project")?),
+            "--branch" => options.branch = Some(value("--branch")?),
+            "--source" => options.source = Some(value("--source")?),
+            "--since" => options.since = Some(parse_since(&value("--since")?)?),
+            "--limit" => {
+                let limit = value("--limit")?;
+                let limit = limit.parse().map_err(|_| format!("Invalid --limit '{}'", limit))?;
+                options.limit = Some(limit);
+            }
+            "--format" => options.format = Some(value("--format")?),
+            "--output" | "-o" => options.output = Some(value("--output")?),
+            flag if flag.starts_with('-') => return Err(format!("Unknown option '{}'", flag)),
+            _ => options.positional.push(arg),
+        }
+    }
+    Ok(options)
+}
+
+fn format_time(ms: Option<i64>) -> String {
+    ms.and_then(|ms| Local.timestamp_millis_opt(ms).single())
+        .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_else(|| "-".to_string())
+}
+
+fn one_line(text: &str, width: usize) -> String {
+    let line = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if line.chars().count() > width {
+        let head: String = line.chars().take(width - 1).collect();
+        format!("{}…", head)
+    } else {
+        line
+    }
+}
+
+/// Render rows as left-aligned columns; the last column is not padded
+fn table(header: &[&str], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = header.iter().map(|h| h.chars().count()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let mut out = String::new();
+    let mut push_row = |cells: Vec<&str>| {
+        let last = cells.len() - 1;
+        let line: Vec<String> = cells
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| {
+                if i == last {
+                    cell.to_string()
+                } else {
+                    format!("{:width$}", cell, width = widths[i])
+                }
+            })
+            .collect();
+        let _ = writeln!(out, "{}", line.join("  ").trim_end());
+    };
+    push_row(header.to_vec());
+    for row in rows {
+        push_row(row.iter().map(String::as_str).collect());
+    }
+    out
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<String, String> {
+    serde_json::to_string_pretty(value)
+        .map(|json| json + "\n")
+        .map_err(|e| e.to_string())
+}
+
+fn filtered_groups(options: &Options) -> Vec<ProjectGroup> {
+    let mut groups = session_store::get_all_sessions();
+    if let Some(source) = &options.source {
+        groups.retain(|g| &g.source == source);
+    }
+    if let Some(project) = &options.project {
+        groups.retain(|g| g.project.contains(project.as_str()));
+    }
+    for group in &mut groups {
+        group.sessions.retain(|s| {
+            options.branch.as_ref().is_none_or(|b| s.git_branch.as_ref() == Some(b))
+                && options.since.is_none_or(|since| s.start_time.is_some_and(|t| t >= since))
+        });
+    }
+    groups.retain(|g| !g.sessions.is_empty());
+    groups
+}
+
+#[derive(Serialize)]
+struct ProjectInfo {
+    project: String,
+    #[serde(rename = "projectEncoded")]
+    project_encoded: String,
+    source: String,
+    #[serde(rename = "sessionCount")]
+    session_count: usize,
+    #[serde(rename = "lastActivity")]
+    last_activity: Option<i64>,
+}
+
+fn list(options: &Options) -> Result<String, String> {
+    let groups = filtered_groups(options);
+    let limit = options.limit.unwrap_or(usize::MAX);
+
+    if options.projects {
+        let projects: Vec<ProjectInfo> = groups
+            .iter()
+            .take(limit)
+            .map(|g| ProjectInfo {
+                project: g.project.clone(),
+                project_encoded: g.project_encoded.clone(),
+                source: g.source.clone(),
+                session_count: g.sessions.len(),
+                last_activity: g.sessions.iter().filter_map(|s| s.end_time.or(s.start_time)).max(),
+            })
+            .collect();
+        if options.json {
+            return to_json(&projects);
+        }
+        let rows: Vec<Vec<String>> = projects
+            .iter()
+            .map(|p| {
+                let project = match p.source.as_str() {
+                    session_store::DEFAULT_SOURCE => p.project.clone(),
+                    source => format!("{} ({})", p.project, source),
+                };
+                vec![p.session_count.to_string(), format_time(p.last_activity), project]
+            })
+            .collect();
+        return Ok(table(&["SESSIONS", "LAST ACTIVITY", "PROJECT"], &rows));
+    }
+
+    let mut sessions: Vec<SessionSummary> = groups.into_iter().flat_map(|g| g.sessions).collect();
+    sessions.sort_by_key(|s| std::cmp::Reverse(s.start_time));
+    sessions.truncate(limit);
+    if options.json {
+        return to_json(&sessions);
+    }
+    let rows: Vec<Vec<String>> = sessions
+        .iter()
+        .map(|s| {
+            vec![
+                s.id.clone(),
+                format_time(s.start_time),
+                s.message_count.to_string(),
+                s.git_branch.clone().unwrap_or_else(|| "-".to_string()),
+                s.project.clone(),
+                one_line(&s.first_message, FIRST_MESSAGE_WIDTH),
+            ]
+        })
+        .collect();
+    Ok(table(&["ID", "STARTED", "MSGS", "BRANCH", "PROJECT", "FIRST MESSAGE"], &rows))
+}
+
+/// Find the session whose id is, or uniquely starts with, `id`
+fn resolve(id: &str) -> Result<SessionSummary, String> {
+    let mut matches: Vec<SessionSummary> = session_store::get_all_sessions()
+        .into_iter()
+        .flat_map(|g| g.sessions)
+        .filter(|s| s.id.starts_with(id))
+        .collect();
+    if let Some(exact) = matches.iter().position(|s| s.id == id) {
+        return Ok(matches.swap_remove(exact));
+    }
+    match matches.len() {
+        0 => Err(format!("No session matches '{}'", id)),
+        1 => Ok(matches.remove(0)),
+        n => Err(format!("'{}' matches {} sessions; use a longer prefix", id, n)),
+    }
+}
+
+fn load(id: &str) -> Result<Session, String> {
+    let summary = resolve(id)?;
+    session_store::get_session(&summary.id, &summary.project_encoded, Some(&summary.source))
+        .ok_or_else(|| format!("Failed to read session {}", summary.id))
+}
+
+fn session_id_arg(options: &Options, command: &str) -> Result<String, String> {
+    match options.positional.as_slice() {
+        [id] => Ok(id.clone()),
+        [] => Err(format!("{} requires a session id", command)),
+        _ => Err(format!("{} takes a single session id", command)),
+    }
+}
+
+fn show(options: &Options) -> Result<String, String> {
+    let session = load(&session_id_arg(options, "show")?)?;
+    if options.json {
+        return Ok(json_export::session_to_json(&session) + "\n");
+    }
+
+    let mut out = String::new();
+    let _ = writeln!(out, "Session   {}", session.id);
+    let _ = writeln!(out, "Project   {}", session.project);
+    let _ = writeln!(out, "Branch    {}", session.git_branch.as_deref().unwrap_or("-"));
+    let _ = writeln!(out, "Started   {}", format_time(session.start_time));
+    if let Some(duration) = markdown_export::format_duration(session.start_time, session.end_time) {
+        let _ = writeln!(out, "Duration  {}", duration);
+    }
+    let _ = writeln!(out, "Messages  {}", session.messages.len());
+    if !session.subagents.is_empty() {
+        let _ = writeln!(out, "Subagents {}", session.subagents.len());
+    }
+    let _ = writeln!(out, "File      {}", session.file_path);
+    out.push('\n');
+
+    for message in &session.messages {
+        let time = markdown_export::format_timestamp(&message.timestamp);
+        let text = message.text_content.trim();
+        if !text.is_empty() {
+            let _ = writeln!(out, "[{}] {}: {}", time, message.role, text);
+        }
+        for tool_use in &message.tool_use_blocks {
+            let _ = writeln!(out, "[{}] {}: → {}", time, message.role, tool_use.name);
+        }
+    }
+    Ok(out)
+}
+
+fn export(options: &Options) -> Result<String, String> {
+    let mut session = load(&session_id_arg(options, "export")?)?;
+    let render: fn(&Session) -> String = match options.format.as_deref().unwrap_or("md") {
+        "md" | "markdown" => markdown_export::session_to_markdown,
+        "html" => html_export::session_to_html,
+        "json" => json_export::session_to_json,
+        "jsonl" => json_export::session_to_jsonl,
+        other => {
+            return Err(format!("Unknown format '{}', expected md, html, json or jsonl", other));
+        }
+    };
+    if !options.no_redact {
+        redaction::redact_session(&mut session, &settings::load().redaction)?;
+    }
+    let content = render(&session);
+    match &options.output {
+        Some(path) => {
+            std::fs::write(path, content).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+            Ok(String::new())
+        }
+        None => Ok(content),
+    }
+}
+
+fn path(options: &Options) -> Result<String, String> {
+    let summary = resolve(&session_id_arg(options, "path")?)?;
+    Ok(summary.file_path + "\n")
+}
+
+fn mcp(options: &Options) -> Result<String, String> {
+    let redaction = (!options.no_redact).then(|| settings::load().redaction);
+    McpServer::new(redaction).serve_stdio()?;
+    Ok(String::new())
+}
+
+/// Run a command, returning what to print
+fn run() -> Result<String, String> {
+    let mut args = std::env::args().skip(1);
+    let Some(command) = args.next() else {
+        return Ok(format!("{}\n", USAGE));
+    };
+    match command.as_str() {
+        "-h" | "--help" | "help" => return Ok(format!("{}\n", USAGE)),
+        "-V" | "--version" => return Ok(format!("sessionly {}\n", env!("CARGO_PKG_VERSION"))),
+        _ => {}
+    }
+
+    let options = parse_args(args)?;
+    session_store::set_data_roots(settings::load().data_roots);
+    match command.as_str() {
+        "list" | "ls" => list(&options),
+        "show" => show(&options),
+        "export" => export(&options),
+        "path" => path(&options),
+        "mcp" => mcp(&options),
+        other => Err(format!("Unknown command '{}'\n\n{}", other, USAGE)),
+    }
+}
+
+fn main() -> ExitCode {
+    match run() {
+        // A closed pipe, e.g. from `| head`, is not an error
+        Ok(output) => match std::io::stdout().lock().write_all(output.as_bytes()) {
+            Err(e) if e.kind() != std::io::ErrorKind::BrokenPipe => {
+                eprintln!("sessionly: {}", e);
+                ExitCode::FAILURE
+            }
+            _ => ExitCode::SUCCESS,
+        },
+        Err(e) => {
+            eprintln!("sessionly: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> impl Iterator<Item = String> {
+        list.iter().map(|s| s.to_string()).collect::<Vec<_>>().into_iter()
+    }
+
+    #[test]
+    fn since_accepts_durations() {
+        let now = chrono::Utc::now().timestamp_millis();
+        let cases = [
+            ("30m", 30 * 60_000),
+            ("12h", 12 * 3_600_000),
+            ("7d", 7 * 86_400_000),
+            ("2w", 14 * 86_400_000),
+        ];
+        for (value, ms) in cases {
+            let since = parse_since(value).unwrap();
+            assert!((now - ms - since).abs() < 5_000, "{} gave {}", value, since);
+        }
+    }
+
+    #[test]
+    fn since_accepts_dates_at_local_midnight() {
+        let since = parse_since("2026-03-01").unwrap();
+        let dt = Local.timestamp_millis_opt(since).single().unwrap();
+        assert_eq!(dt.format("%Y-%m-%d %H:%M").to_string(), "2026-03-01 00:00");
+    }
+
+    #[test]
+    fn since_rejects_other_values() {
+        for value in ["", "d", "7", "7y", "seven days", "2026-13-01", "7é"] {
+            assert!(parse_since(value).is_err(), "{:?} was accepted", value);
+        }
+    }
+
+    #[test]
+    fn parses_flags_values_and_positionals() {
+        let options = parse_args(args(&[
+            "abc123", "--json", "--project", "demo", "--branch", "main", "--source", "work",
+            "--limit", "5", "--format", "html", "-o", "out.html", "--no-redact", "--projects",
+        ]))
+        .unwrap();
+        assert_eq!(options.positional, ["abc123"]);
+        assert!(options.json && options.projects && options.no_redact);
+        assert_eq!(options.project.as_deref(), Some("demo"));
+        assert_eq!(options.branch.as_deref(), Some("main"));
+        assert_eq!(options.source.as_deref(), Some("work"));
+        assert_eq!(options.limit, Some(5));
+        assert_eq!(options.format.as_deref(), Some("html"));
+        assert_eq!(options.output.as_deref(), Some("out.html"));
+    }
+
+    #[test]
+    fn rejects_bad_arguments() {
+        for list in [&["--limit"][..], &["--limit", "many"], &["--since", "soon"], &["--verbose"]] {
+            assert!(parse_args(args(list)).is_err(), "{:?} was accepted", list);
+        }
+    }
+}
@@ -0,0 +1,431 @@
+//! Self-contained HTML export with inline styles and syntax highlighting.
+
+use crate::markdown_export::{format_duration, format_full_date, format_timestamp};
+use crate::session_types::{ProcessedMessage, Session, SubagentSession, ThinkingBlock, ToolResultBlock, ToolUseBlock};
+use chrono::Utc;
+use pulldown_cmark::{CodeBlockKind, CowStr, Event, Options, Parser, Tag, TagEnd};
+use std::collections::HashMap;
+
+const MAX_RESULT_CHARS: usize = 2000;
+
+const STYLE: &str = r#"
+:root {
+  --bg: #ffffff; --fg: #1f2328; --muted: #656d76; --border: #d0d7de;
+  --user-bg: #f0f6ff; --assistant-bg: #f6f8fa; --code-bg: #f6f8fa; --error: #cf222e;
+  --kw: #cf222e; --str: #0a3069; --num: #0550ae; --com: #6e7781;
+}
+@media (prefers-color-scheme: dark) {
+  :root {
+    --bg: #0d1117; --fg: #e6edf3; --muted: #8d96a0; --border: #30363d;
+    --user-bg: #122136; --assistant-bg: #161b22; --code-bg: #1c2128; --error: #f85149;
+    --kw: #ff7b72; --str: #a5d6ff; --num: #79c0ff; --com: #8b949e;
+  }
+}
+* { box-sizing: border-box; }
+body { margin: 0; background: var(--bg); color: var(--fg); font: 15px/1.6 -apple-system, BlinkMacSystemFont, "Segoe UI", Helvetica, Arial, sans-serif; }
+main { max-width: 960px; margin: 0 auto; padding: 32px 24px; }
+h1 { font-size: 1.6em; margin: 0 0 12px; word-break: break-all; }
+.meta { display: grid; grid-template-columns: max-content 1fr; gap: 2px 16px; margin: 0 0 24px; color: var(--muted); }
+.meta dt { font-weight: 600; }
+.meta dd { margin: 0; }
+.message { border: 1px solid var(--border); border-radius: 8px; padding: 12px 16px; margin: 12px 0; }
+.message.user { background: var(--user-bg); }
+.message.assistant { background: var(--assistant-bg); }
+.message-header { display: flex; gap: 8px; align-items: baseline; margin-bottom: 6px; }
+.role { font-weight: 600; }
+time { color: var(--muted); font-size: 0.85em; }
+details { border: 1px solid var(--border); border-radius: 6px; margin: 8px 0; background: var(--bg); }
+details > summary { cursor: pointer; padding: 6px 10px; color: var(--muted); }
+details > .body { padding: 4px 12px 8px; }
+details.thinking > summary { font-style: italic; }
+.tool-name { font-weight: 600; color: var(--fg); font-family: ui-monospace, SFMono-Regular, Menlo, monospace; }
+.tool-error { color: var(--error); font-weight: 600; }
+h4 { margin: 8px 0 4px; font-size: 0.85em; text-transform: uppercase; color: var(--muted); }
+pre { background: var(--code-bg); border-radius: 6px; padding: 10px 12px; overflow-x: auto; font-size: 13px; line-height: 1.45; }
+code { font-family: ui-monospace, SFMono-Regular, Menlo, monospace; }
+:not(pre) > code { background: var(--code-bg); padding: 1px 4px; border-radius: 4px; font-size: 0.9em; }
+.markdown > :first-child { margin-top: 0; }
+.markdown > :last-child { margin-bottom: 0; }
+.tok-kw { color: var(--kw); }
+.tok-str { color: var(--str); }
+.tok-num { color: var(--num); }
+.tok-com { color: var(--com); font-style: italic; }
+footer { margin-top: 32px; color: var(--muted); font-size: 0.85em; text-align: center; }
+"#;
+
+const KEYWORDS: &[&str] = &[
+    "abstract", "and", "as", "async", "await", "break", "case", "catch", "class", "const", "continue", "crate",
+    "def", "default", "defer", "del", "do", "done", "dyn", "elif", "else", "enum", "esac", "except", "export",
+    "extends", "extern", "false", "fi", "finally", "fn", "for", "from", "func", "function", "go", "if", "impl",
+    "implements", "import", "in", "interface", "is", "lambda", "let", "loop", "match", "mod", "module", "move",
+    "mut", "new", "nil", "not", "null", "or", "package", "pass", "private", "protected", "pub", "public", "raise",
+    "ref", "return", "self", "static", "struct", "super", "switch", "then", "this", "throw", "trait", "true",
+    "try", "type", "typeof", "undefined", "unsafe", "use", "var", "void", "where", "while", "with", "yield",
+    "None", "True", "False", "Self", "Some", "Ok", "Err",
+];
+
+/// Escape text for use in HTML content and attribute values
+pub(crate) fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn is_ident(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn push_token(out: &mut String, class: &str, chars: &[char]) {
+    let text: String = chars.iter().collect();
+    out.push_str(&format!("<span class=\"{}\">{}</span>", class, escape_html(&text)));
+}
+
+/// Lightweight lexer-based highlighting for comments, strings, numbers and
+/// common keywords; good enough for transcripts without shipping a JS highlighter
+fn highlight_code(code: &str, lang: &str) -> String {
+    let lang = lang.to_lowercase();
+    if matches!(lang.as_str(), "" | "text" | "plaintext" | "txt" | "output" | "console" | "diff") {
+        return escape_html(code);
+    }
+    let hash_comments = matches!(
+        lang.as_str(),
+        "python" | "py" | "sh" | "bash" | "shell" | "zsh" | "ruby" | "rb" | "yaml" | "yml" | "toml" | "perl" | "r" | "dockerfile" | "makefile"
+    );
+    let dash_comments = matches!(lang.as_str(), "sql" | "lua" | "haskell" | "hs");
+    let rust = matches!(lang.as_str(), "rust" | "rs");
+
+    let chars: Vec<char> = code.chars().collect();
+    let mut out = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+
+        // Line comments
+        let line_comment = if hash_comments {
+            c == '#'
+        } else if dash_comments {
+            c == '-' && next == Some('-')
+        } else {
+            c == '/' && next == Some('/')
+        };
+        if line_comment {
+            let end = chars[i..].iter().position(|&ch| ch == '\n').map_or(chars.len(), |p| i + p);
+            push_token(&mut out, "tok-com", &chars[i..end]);
+            i = end;
+            continue;
+        }
+
+        // Block comments
+        if !hash_comments && c == '/' && next == Some('*') {
+            let end = (i + 2..chars.len().saturating_sub(1))
+                .find(|&j| chars[j] == '*' && chars[j + 1] == '/')
+                .map_or(chars.len(), |j| j + 2);
+            push_token(&mut out, "tok-com", &chars[i..end]);
+            i = end;
+            continue;
+        }
+
+        // Strings; Rust lifetimes like 'a are not char literals
+        if c == '"' || c == '`' || c == '\'' {
+            let lifetime = rust && c == '\'' && next.is_some_and(is_ident) && chars.get(i + 2) != Some(&'\'');
+            if !lifetime {
+                let mut j = i + 1;
+                while j < chars.len() && chars[j] != c && (c == '`' || chars[j] != '\n') {
+                    j += if chars[j] == '\\' { 2 } else { 1 };
+                }
+                let end = (j + 1).min(chars.len());
+                push_token(&mut out, "tok-str", &chars[i..end]);
+                i = end;
+                continue;
+            }
+        }
+
+        // Numbers
+        if c.is_ascii_digit() && (i == 0 || !is_ident(chars[i - 1])) {
+            let end = chars[i..]
+                .iter()
+                .position(|&ch| !(is_ident(ch) || ch == '.'))
+                .map_or(chars.len(), |p| i + p);
+            push_token(&mut out, "tok-num", &chars[i..end]);
+            i = end;
+            continue;
+        }
+
+        // Identifiers and keywords
+        if is_ident(c) {
+            let end = chars[i..].iter().position(|&ch| !is_ident(ch)).map_or(chars.len(), |p| i + p);
+            let word: String = chars[i..end].iter().collect();
+            if KEYWORDS.contains(&word.as_str()) {
+                push_token(&mut out, "tok-kw", &chars[i..end]);
+            } else {
+                out.push_str(&escape_html(&word));
+            }
+            i = end;
+            continue;
+        }
+
+        out.push_str(&escape_html(&c.to_string()));
+        i += 1;
+    }
+    out
+}
+
+fn code_block(code: &str, lang: &str) -> String {
+    if lang.is_empty() {
+        return format!("<pre><code>{}</code></pre>\n", escape_html(code));
+    }
+    format!(
+        "<pre><code class=\"language-{}\">{}</code></pre>\n",
+        escape_html(lang),
+        highlight_code(code, lang)
+    )
+}
+
+/// Whether a link target is safe to keep: http, https, mailto, or a relative
+/// link or fragment. Browsers ignore control characters and whitespace inside
+/// a scheme (`java\tscript:`), so those are dropped before checking it.
+fn is_safe_link(url: &str) -> bool {
+    let url: String = url.chars().filter(|c| !c.is_ascii_control() && !c.is_whitespace()).collect();
+    match url.find([':', '/', '?', '#']) {
+        Some(i) if url[i..].starts_with(':') => {
+            matches!(url[..i].to_ascii_lowercase().as_str(), "http" | "https" | "mailto")
+        }
+        _ => true,
+    }
+}
+
+/// Render message markdown to HTML with highlighted code blocks; raw HTML in
+/// the transcript is escaped rather than passed through, and links to other
+/// schemes are rendered as their text
+fn render_markdown(text: &str) -> String {
+    let mut events: Vec<Event> = Vec::new();
+    let mut code: Option<(String, String)> = None;
+    // Whether each open link was dropped, so its end tag is dropped too
+    let mut links: Vec<bool> = Vec::new();
+
+    for event in Parser::new_ext(text, Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS) {
+        match event {
+            Event::Start(Tag::CodeBlock(kind)) => {
+                let lang = match kind {
+                    CodeBlockKind::Fenced(info) => info.split_whitespace().next().unwrap_or_default().to_string(),
+                    CodeBlockKind::Indented => String::new(),
+                };
+                code = Some((lang, String::new()));
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                if let Some((lang, body)) = code.take() {
+                    events.push(Event::Html(CowStr::from(code_block(&body, &lang))));
+                }
+            }
+            Event::Text(t) if code.is_some() => {
+                if let Some((_, body)) = code.as_mut() {
+                    body.push_str(&t);
+                }
+            }
+            Event::Html(raw) | Event::InlineHtml(raw) => events.push(Event::Text(raw)),
+            Event::Start(Tag::Link { ref dest_url, .. }) => {
+                let safe = is_safe_link(dest_url);
+                links.push(safe);
+                if safe {
+                    events.push(event);
+                }
+            }
+            Event::End(TagEnd::Link) => {
+                if links.pop().unwrap_or(true) {
+                    events.push(event);
+                }
+            }
+            other => events.push(other),
+        }
+    }
+
+    let mut html = String::new();
+    pulldown_cmark::html::push_html(&mut html, events.into_iter());
+    html
+}
+
+fn result_text(result: &ToolResultBlock) -> String {
+    let content = if let Some(s) = result.content.as_str() {
+        s.to_string()
+    } else if let Some(arr) = result.content.as_array() {
+        arr.iter()
+            .map(|block| match block.get("text").and_then(|t| t.as_str()) {
+                Some(text) => text.to_string(),
+                None => serde_json::to_string(block).unwrap_or_default(),
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    } else {
+        serde_json::to_string(&result.content).unwrap_or_default()
+    };
+    if content.chars().count() > MAX_RESULT_CHARS {
+        let s: String = content.chars().take(MAX_RESULT_CHARS).collect();
+        format!("{}\n... (truncated)", s)
+    } else {
+        content
+    }
+}
+
+fn format_tool_use(tool: &ToolUseBlock, result: Option<&ToolResultBlock>) -> String {
+    let is_error = result.is_some_and(|r| r.is_error == Some(true));
+    let mut html = String::new();
+    html.push_str("<details class=\"tool\">\n<summary><span class=\"tool-name\">");
+    html.push_str(&escape_html(&tool.name));
+    html.push_str("</span>");
+    if is_error {
+        html.push_str(" <span class=\"tool-error\">error</span>");
+    }
+    html.push_str("</summary>\n<div class=\"body\">\n");
+
+    if tool.input != serde_json::Value::Object(Default::default()) {
+        html.push_str("<h4>Input</h4>\n");
+        html.push_str(&code_block(&serde_json::to_string_pretty(&tool.input).unwrap_or_default(), "json"));
+    }
+
+    if let Some(result) = result {
+        html.push_str(if is_error { "<h4>Error</h4>\n" } else { "<h4>Result</h4>\n" });
+        html.push_str(&code_block(&result_text(result), ""));
+    }
+
+    html.push_str("</div>\n</details>\n");
+    html
+}
+
+fn format_thinking(thinking: &ThinkingBlock) -> String {
+    format!(
+        "<details class=\"thinking\">\n<summary>Thinking…</summary>\n<div class=\"body markdown\">{}</div>\n</details>\n",
+        render_markdown(&thinking.thinking)
+    )
+}
+
+fn format_subagent(subagent: &SubagentSession) -> String {
+    let mut html = format!(
+        "<details class=\"subagent\">\n<summary><strong>Subagent</strong> ({} messages)</summary>\n<div class=\"body\">\n",
+        subagent.message_count
+    );
+    for message in &subagent.messages {
+        html.push_str(&format_message(message, &HashMap::new()));
+    }
+    html.push_str("</div>\n</details>\n");
+    html
+}
+
+fn format_message(message: &ProcessedMessage, subagents: &HashMap<String, SubagentSession>) -> String {
+    let (class, role) = if message.role == "user" { ("user", "User") } else { ("assistant", "Assistant") };
+    let mut html = format!(
+        "<article class=\"message {}\">\n<div class=\"message-header\"><span class=\"role\">{}</span><time datetime=\"{}\">{}</time></div>\n",
+        class,
+        role,
+        escape_html(&message.timestamp),
+        escape_html(&format_timestamp(&message.timestamp))
+    );
+
+    for thinking in &message.thinking_blocks {
+        html.push_str(&format_thinking(thinking));
+    }
+
+    if !message.text_content.is_empty() {
+        html.push_str("<div class=\"markdown\">");
+        html.push_str(&render_markdown(&message.text_content));
+        html.push_str("</div>\n");
+    }
+
+    for tool in &message.tool_use_blocks {
+        if tool.name == "Task" {
+            if let Some(subagent) = tool.agent_id.as_ref().and_then(|id| subagents.get(id)) {
+                html.push_str(&format_subagent(subagent));
+                continue;
+            }
+        }
+        html.push_str(&format_tool_use(tool, message.tool_results.get(&tool.id)));
+    }
+
+    html.push_str("</article>\n");
+    html
+}
+
+/// Render a session as a single self-contained HTML page
+pub fn session_to_html(session: &Session) -> String {
+    let mut meta: Vec<(&str, String)> = vec![("Date", format_full_date(session.start_time))];
+    if let Some(dur) = format_duration(session.start_time, session.end_time) {
+        meta.push(("Duration", dur));
+    }
+    meta.push(("Messages", session.messages.len().to_string()));
+    if let Some(branch) = &session.git_branch {
+        meta.push(("Branch", branch.clone()));
+    }
+    if !session.version.is_empty() {
+        meta.push(("Claude Code Version", session.version.clone()));
+    }
+    meta.push(("Session ID", session.id.clone()));
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str("<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n");
+    html.push_str(&format!("<title>Session: {}</title>\n", escape_html(&session.project)));
+    html.push_str(&format!("<style>{}</style>\n</head>\n<body>\n<main>\n", STYLE));
+
+    html.push_str(&format!("<header>\n<h1>Session: {}</h1>\n<dl class=\"meta\">\n", escape_html(&session.project)));
+    for (label, value) in meta {
+        html.push_str(&format!("<dt>{}</dt><dd>{}</dd>\n", label, escape_html(&value)));
+    }
+    html.push_str("</dl>\n</header>\n<section class=\"conversation\">\n");
+
+    for message in &session.messages {
+        html.push_str(&format_message(message, &session.subagents));
+    }
+
+    let now = Utc::now().format("%B %e, %Y at %l:%M %p");
+    html.push_str(&format!(
+        "</section>\n<footer>Exported from Sessionly on {}</footer>\n</main>\n</body>\n</html>\n",
+        now
+    ));
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_allowed_links() {
+        for url in ["https://example.com", "http://a.b/c", "mailto:me@example.com", "docs/x.md", "#top", "/abs?q=1:2"] {
+            let html = render_markdown(&format!("[x]({})", url));
+            assert!(html.contains("<a href="), "{} was dropped: {}", url, html);
+        }
+    }
+
+    #[test]
+    fn renders_other_schemes_as_text() {
+        for url in [
+            "javascript:alert(1)",
+            "JavaScript:alert(1)",
+            "java\tscript:alert(1)",
+            "data:text/html,<script>alert(1)</script>",
+            "vbscript:msgbox(1)",
+            "&#x6A;avascript:alert(1)",
+            "file:///etc/passwd",
+        ] {
+            let html = render_markdown(&format!("[click]({})", url));
+            assert!(!html.contains("<a "), "{} was linked: {}", url, html);
+            assert!(html.contains("click"), "{} lost its text: {}", url, html);
+        }
+    }
+
+    #[test]
+    fn is_safe_link_ignores_control_characters_in_the_scheme() {
+        assert!(!is_safe_link("java\tscript:alert(1)"));
+        assert!(!is_safe_link(" java\nscript:alert(1)"));
+        assert!(!is_safe_link("\u{0}javascript:alert(1)"));
+    }
+}
@@ -0,0 +1,495 @@
+//! Normalized, versioned JSON and JSONL export formats.
+//!
+//! The schema is documented in `docs/export-schema.md`. Field names are
+//! snake_case and independent of the frontend types so they can stay stable;
+//! bump `SCHEMA_VERSION` on any breaking change.
+
+use crate::session_store::parse_timestamp;
+use crate::session_types::{ProcessedMessage, Session, SubagentSession, TokenUsage, ToolResultBlock};
+use chrono::{SecondsFormat, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Value of the `schema` field
+pub const SCHEMA: &str = "sessionly.session";
+/// Value of the `schema_version` field
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// The JSON format: one document holding the whole session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportDocument {
+    /// Always [`SCHEMA`]
+    pub schema: String,
+    /// [`SCHEMA_VERSION`] of the writer
+    pub schema_version: u32,
+    /// RFC 3339 time of the export
+    pub exported_at: String,
+    /// The exported session
+    pub session: ExportSession,
+}
+
+/// Session metadata shared by the JSON and JSONL formats
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportSessionInfo {
+    /// Session id
+    pub id: String,
+    /// Decoded project path
+    pub project: String,
+    /// Project directory name as stored on disk
+    pub project_encoded: String,
+    /// Working directory of the session
+    pub cwd: String,
+    /// Claude Code version that wrote the transcript
+    pub claude_code_version: String,
+    /// Git branch at the start of the session
+    pub git_branch: Option<String>,
+    /// RFC 3339 time of the first message
+    pub start_time: Option<String>,
+    /// RFC 3339 time of the last message
+    pub end_time: Option<String>,
+}
+
+/// A session with its messages and subagents
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportSession {
+    /// Session metadata, flattened into this object
+    #[serde(flatten)]
+    pub info: ExportSessionInfo,
+    /// Messages in order
+    pub messages: Vec<ExportMessage>,
+    /// Subagent transcripts
+    pub subagents: Vec<ExportSubagent>,
+}
+
+/// The transcript of a subagent started by a Task call
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportSubagent {
+    /// Subagent id, referenced by [`ExportToolCall::subagent_id`]
+    pub agent_id: String,
+    /// Id of the Task call that started it
+    pub parent_tool_use_id: String,
+    /// Messages in order
+    pub messages: Vec<ExportMessage>,
+}
+
+/// Token counts of an assistant message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportUsage {
+    /// Uncached input tokens
+    pub input_tokens: u64,
+    /// Output tokens, including thinking
+    pub output_tokens: u64,
+    /// Input tokens written to the prompt cache
+    pub cache_creation_input_tokens: u64,
+    /// Input tokens read from the prompt cache
+    pub cache_read_input_tokens: u64,
+}
+
+/// A user or assistant message; a streamed assistant reply is one message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportMessage {
+    /// Entry uuid; the first entry's for merged replies
+    pub uuid: String,
+    /// Uuid of the entry this one follows
+    pub parent_uuid: Option<String>,
+    /// RFC 3339 time, or the raw value if it couldn't be parsed
+    pub timestamp: String,
+    /// `user` or `assistant`
+    pub role: String,
+    /// Model that wrote an assistant message
+    pub model: Option<String>,
+    /// Text content
+    pub text: String,
+    /// Thinking blocks in order
+    pub thinking: Vec<String>,
+    /// Tool calls with their results
+    pub tool_calls: Vec<ExportToolCall>,
+    /// Token usage of an assistant message
+    pub usage: Option<ExportUsage>,
+}
+
+/// A tool call and its result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportToolCall {
+    /// Tool use id
+    pub id: String,
+    /// Tool name
+    pub name: String,
+    /// Arguments the tool was called with
+    pub input: serde_json::Value,
+    /// Set for Task calls whose subagent transcript is in `subagents`
+    pub subagent_id: Option<String>,
+    /// The result, if one was recorded
+    pub result: Option<ExportToolResult>,
+}
+
+/// The result of a tool call
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportToolResult {
+    /// Whether the tool reported an error
+    pub is_error: bool,
+    /// Text content, whether the raw result was a string or text blocks
+    pub text: String,
+    /// Non-text result blocks (e.g. images) as they appeared in the transcript
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<serde_json::Value>,
+}
+
+/// One line of the JSONL format: a session header, then each message;
+/// subagent messages follow their `subagent` record and carry its `agent_id`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "record", rename_all = "snake_case")]
+pub enum ExportRecord {
+    /// The first line
+    Session {
+        /// Always [`SCHEMA`]
+        schema: String,
+        /// [`SCHEMA_VERSION`] of the writer
+        schema_version: u32,
+        /// RFC 3339 time of the export
+        exported_at: String,
+        /// Session metadata, flattened into the record
+        #[serde(flatten)]
+        info: ExportSessionInfo,
+    },
+    /// Starts a subagent's messages
+    Subagent {
+        /// Subagent id
+        agent_id: String,
+        /// Id of the Task call that started it
+        parent_tool_use_id: String,
+    },
+    /// A message of the session or of the preceding subagent
+    Message {
+        /// Subagent the message belongs to, if any
+        agent_id: Option<String>,
+        /// The message, flattened into the record
+        #[serde(flatten)]
+        message: ExportMessage,
+    },
+}
+
+fn format_time(ms: Option<i64>) -> Option<String> {
+    ms.and_then(|ms| Utc.timestamp_millis_opt(ms).single())
+        .map(|dt| dt.to_rfc3339_opts(SecondsFormat::Millis, true))
+}
+
+/// Token usage in the export's field naming
+pub fn export_usage(usage: &TokenUsage) -> ExportUsage {
+    ExportUsage {
+        input_tokens: usage.input_tokens,
+        output_tokens: usage.output_tokens,
+        cache_creation_input_tokens: usage.cache_creation_input_tokens,
+        cache_read_input_tokens: usage.cache_read_input_tokens,
+    }
+}
+
+fn export_tool_result(result: &ToolResultBlock) -> ExportToolResult {
+    let mut texts: Vec<String> = Vec::new();
+    let mut attachments: Vec<serde_json::Value> = Vec::new();
+    match &result.content {
+        serde_json::Value::String(s) => texts.push(s.clone()),
+        serde_json::Value::Array(blocks) => {
+            for block in blocks {
+                match block.get("text").and_then(|t| t.as_str()) {
+                    Some(text) if block.get("type").and_then(|t| t.as_str()) == Some("text") => {
+                        texts.push(text.to_string())
+                    }
+                    _ => attachments.push(block.clone()),
+                }
+            }
+        }
+        serde_json::Value::Null => {}
+        other => attachments.push(other.clone()),
+    }
+    ExportToolResult {
+        is_error: result.is_error.unwrap_or(false),
+        text: texts.join("\n"),
+        attachments,
+    }
+}
+
+fn export_message(message: &ProcessedMessage) -> ExportMessage {
+    ExportMessage {
+        uuid: message.uuid.clone(),
+        parent_uuid: message.parent_uuid.clone(),
+        timestamp: format_time(parse_timestamp(&message.timestamp))
+            .unwrap_or_else(|| message.timestamp.clone()),
+        role: message.role.clone(),
+        model: message.model.clone(),
+        text: message.text_content.clone(),
+        thinking: message.thinking_blocks.iter().map(|t| t.thinking.clone()).collect(),
+        tool_calls: message
+            .tool_use_blocks
+            .iter()
+            .map(|tool| ExportToolCall {
+                id: tool.id.clone(),
+                name: tool.name.clone(),
+                input: tool.input.clone(),
+                subagent_id: tool.agent_id.clone(),
+                result: message.tool_results.get(&tool.id).map(export_tool_result),
+            })
+            .collect(),
+        usage: message.usage.as_ref().map(export_usage),
+    }
+}
+
+/// Append a later chunk of a streamed assistant reply to the message so far
+fn merge_chunk(message: &mut ExportMessage, chunk: ExportMessage) {
+    if !chunk.text.is_empty() {
+        if !message.text.is_empty() {
+            message.text.push('\n');
+        }
+        message.text.push_str(&chunk.text);
+    }
+    message.thinking.extend(chunk.thinking);
+    message.tool_calls.extend(chunk.tool_calls);
+    message.model = message.model.take().or(chunk.model);
+    message.usage = message.usage.take().or(chunk.usage);
+}
+
+/// Export messages, combining the entries a streamed assistant reply was split
+/// into: consecutive assistant messages with the same API message id
+fn export_messages(messages: &[ProcessedMessage]) -> Vec<ExportMessage> {
+    let mut exported: Vec<ExportMessage> = Vec::new();
+    let mut previous_id: Option<&str> = None;
+    for message in messages {
+        let id = message.message_id.as_deref().filter(|_| message.role == "assistant");
+        let chunk = export_message(message);
+        match exported.last_mut() {
+            Some(last) if id.is_some() && id == previous_id => merge_chunk(last, chunk),
+            _ => exported.push(chunk),
+        }
+        previous_id = id;
+    }
+    exported
+}
+
+fn export_subagent(subagent: &SubagentSession) -> ExportSubagent {
+    ExportSubagent {
+        agent_id: subagent.agent_id.clone(),
+        parent_tool_use_id: subagent.parent_tool_use_id.clone(),
+        messages: export_messages(&subagent.messages),
+    }
+}
+
+fn export_info(session: &Session) -> ExportSessionInfo {
+    ExportSessionInfo {
+        id: session.id.clone(),
+        project: session.project.clone(),
+        project_encoded: session.project_encoded.clone(),
+        cwd: session.cwd.clone(),
+        claude_code_version: session.version.clone(),
+        git_branch: session.git_branch.clone(),
+        start_time: format_time(session.start_time),
+        end_time: format_time(session.end_time),
+    }
+}
+
+/// Subagents in a stable order: by first message timestamp, then id
+fn sorted_subagents(session: &Session) -> Vec<&SubagentSession> {
+    let mut subagents: Vec<&SubagentSession> = session.subagents.values().collect();
+    subagents.sort_by(|a, b| {
+        let a_ts = a.messages.first().map(|m| m.timestamp.as_str());
+        let b_ts = b.messages.first().map(|m| m.timestamp.as_str());
+        a_ts.cmp(&b_ts).then_with(|| a.agent_id.cmp(&b.agent_id))
+    });
+    subagents
+}
+
+fn exported_at() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// Build the JSON export document for a session
+pub fn session_to_document(session: &Session) -> ExportDocument {
+    ExportDocument {
+        schema: SCHEMA.to_string(),
+        schema_version: SCHEMA_VERSION,
+        exported_at: exported_at(),
+        session: ExportSession {
+            info: export_info(session),
+            messages: export_messages(&session.messages),
+            subagents: sorted_subagents(session).into_iter().map(export_subagent).collect(),
+        },
+    }
+}
+
+/// Build the JSONL export records for a session: a header, its messages, then
+/// each subagent followed by its messages
+pub fn session_to_records(session: &Session) -> Vec<ExportRecord> {
+    let mut records = vec![ExportRecord::Session {
+        schema: SCHEMA.to_string(),
+        schema_version: SCHEMA_VERSION,
+        exported_at: exported_at(),
+        info: export_info(session),
+    }];
+    records.extend(export_messages(&session.messages).into_iter().map(|message| {
+        ExportRecord::Message {
+            agent_id: None,
+            message,
+        }
+    }));
+    for subagent in sorted_subagents(session) {
+        records.push(ExportRecord::Subagent {
+            agent_id: subagent.agent_id.clone(),
+            parent_tool_use_id: subagent.parent_tool_use_id.clone(),
+        });
+        records.extend(export_messages(&subagent.messages).into_iter().map(|message| {
+            ExportRecord::Message {
+                agent_id: Some(subagent.agent_id.clone()),
+                message,
+            }
+        }));
+    }
+    records
+}
+
+/// Export a session as a pretty-printed JSON document
+pub fn session_to_json(session: &Session) -> String {
+    serde_json::to_string_pretty(&session_to_document(session)).unwrap_or_default()
+}
+
+/// Export a session as JSONL, one record per line
+pub fn session_to_jsonl(session: &Session) -> String {
+    session_to_records(session)
+        .iter()
+        .filter_map(|record| serde_json::to_string(record).ok())
+        .map(|line| line + "\n")
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session_store::{get_session, set_data_roots, DataRoot};
+    use std::path::Path;
+
+    const SESSION_ID: &str = "5f0c8a10-3b1e-4c8a-9d2f-7a6e1b2c3d4e";
+
+    /// The session in `tests/fixtures/root`, read the way the app reads it
+    fn fixture_session() -> Session {
+        set_data_roots(vec![DataRoot {
+            label: "fixtures".to_string(),
+            path: Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/root"),
+            enabled: true,
+        }]);
+        get_session(SESSION_ID, "-tmp-demo", Some("fixtures")).expect("fixture session")
+    }
+
+    #[test]
+    fn json_round_trips() {
+        let json = session_to_json(&fixture_session());
+        let document: ExportDocument = serde_json::from_str(&json).unwrap();
+        assert_eq!(serde_json::to_string_pretty(&document).unwrap(), json);
+
+        assert_eq!(document.schema, SCHEMA);
+        assert_eq!(document.schema_version, SCHEMA_VERSION);
+        let session = &document.session;
+        assert_eq!(session.info.id, SESSION_ID);
+        assert_eq!(session.info.cwd, "/tmp/demo");
+        assert_eq!(session.info.claude_code_version, "2.0.1");
+        assert_eq!(session.info.git_branch.as_deref(), Some("main"));
+        assert_eq!(session.info.start_time.as_deref(), Some("2026-10-01T10:00:00.000Z"));
+        assert_eq!(session.info.end_time.as_deref(), Some("2026-10-01T10:00:30.250Z"));
+
+        let uuids: Vec<&str> = session.messages.iter().map(|m| m.uuid.as_str()).collect();
+        assert_eq!(uuids, ["u1", "a1", "a4", "a5"]);
+
+        let subagent = &session.subagents[0];
+        assert_eq!(subagent.agent_id, "a1b2c3");
+        assert_eq!(subagent.parent_tool_use_id, "toolu_task");
+        assert_eq!(subagent.messages.len(), 2);
+        assert_eq!(subagent.messages[1].model.as_deref(), Some("claude-haiku-4-5"));
+    }
+
+    #[test]
+    fn split_assistant_reply_is_one_message() {
+        let document = session_to_document(&fixture_session());
+        let reply = &document.session.messages[1];
+        assert_eq!(reply.parent_uuid.as_deref(), Some("u1"));
+        assert_eq!(reply.text, "I'll explore it first.");
+        assert_eq!(reply.thinking, ["Start with the layout."]);
+        assert_eq!(reply.tool_calls.len(), 1);
+        let usage = reply.usage.as_ref().unwrap();
+        assert_eq!((usage.input_tokens, usage.output_tokens), (10, 20));
+        assert_eq!(usage.cache_read_input_tokens, 100);
+
+        let task = &reply.tool_calls[0];
+        assert_eq!(task.name, "Task");
+        assert_eq!(task.subagent_id.as_deref(), Some("a1b2c3"));
+        let result = task.result.as_ref().unwrap();
+        assert_eq!(result.text, "src/ and docs/");
+        assert!(!result.is_error);
+        assert_eq!(result.attachments.len(), 1);
+
+        let read = document.session.messages[2].tool_calls[0].result.as_ref().unwrap();
+        assert!(read.is_error);
+        assert_eq!(read.text, "File does not exist.");
+    }
+
+    #[test]
+    fn timestamps_are_utc() {
+        let document = session_to_document(&fixture_session());
+        let timestamps: Vec<&str> =
+            document.session.messages.iter().map(|m| m.timestamp.as_str()).collect();
+        assert_eq!(
+            timestamps,
+            [
+                "2026-10-01T10:00:00.000Z",
+                "2026-10-01T10:00:02.000Z",
+                "2026-10-01T10:00:21.000Z",
+                "2026-10-01T10:00:30.250Z",
+            ]
+        );
+    }
+
+    #[test]
+    fn jsonl_round_trips() {
+        let session = fixture_session();
+        let jsonl = session_to_jsonl(&session);
+        let records: Vec<ExportRecord> =
+            jsonl.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        let reserialized: String = records
+            .iter()
+            .map(|record| serde_json::to_string(record).unwrap() + "\n")
+            .collect();
+        assert_eq!(reserialized, jsonl);
+
+        let document = session_to_document(&session);
+        let ExportRecord::Session { info, schema_version, .. } = &records[0] else {
+            panic!("first record is {:?}", records[0]);
+        };
+        assert_eq!(*schema_version, SCHEMA_VERSION);
+        assert_eq!(
+            serde_json::to_value(info).unwrap(),
+            serde_json::to_value(&document.session.info).unwrap()
+        );
+
+        // The main messages, then the subagent and its messages, as in the JSON document
+        let mut expected: Vec<(Option<String>, &ExportMessage)> =
+            document.session.messages.iter().map(|m| (None, m)).collect();
+        let subagent = &document.session.subagents[0];
+        expected.extend(subagent.messages.iter().map(|m| (Some(subagent.agent_id.clone()), m)));
+        let subagent_at = document.session.messages.len() + 1;
+        assert!(matches!(
+            &records[subagent_at],
+            ExportRecord::Subagent { agent_id, parent_tool_use_id }
+                if agent_id == "a1b2c3" && parent_tool_use_id == "toolu_task"
+        ));
+        let messages: Vec<(Option<String>, serde_json::Value)> = records
+            .iter()
+            .filter_map(|record| match record {
+                ExportRecord::Message { agent_id, message } => {
+                    Some((agent_id.clone(), serde_json::to_value(message).unwrap()))
+                }
+                _ => None,
+            })
+            .collect();
+        let expected: Vec<(Option<String>, serde_json::Value)> = expected
+            .into_iter()
+            .map(|(agent_id, m)| (agent_id, serde_json::to_value(m).unwrap()))
+            .collect();
+        assert_eq!(messages, expected);
+        assert_eq!(records.len(), 1 + messages.len() + 1);
+    }
+}
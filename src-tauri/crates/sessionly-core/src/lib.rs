@@ -0,0 +1,54 @@
+//! Reading and exporting Claude Code CLI session history.
+//!
+//! This is the parser the Sessionly app and `sessionly` CLI are built on. It
+//! has no dependency on Tauri.
+//!
+//! Sessions are read from the `projects/` directory of each data root, one
+//! directory per project. The Claude config directory (`$CLAUDE_CONFIG_DIR`
+//! or `~/.claude`) is always scanned; [`set_data_roots`] adds more.
+//!
+//! - [`list_projects`] lists the project directories across roots
+//! - [`get_all_sessions`] summarizes every session, grouped by root and project
+//! - [`get_session`] parses one session in full, including subagents
+//!
+//! A parsed [`Session`] can be rendered with [`session_to_markdown`],
+//! [`session_to_html`], [`session_to_json`] or [`session_to_jsonl`], after
+//! optionally removing secrets with [`redact_session`].
+//!
+//! [`session_search`] searches across sessions, [`mcp`] serves them to
+//! Claude Code, and [`settings`] reads the data roots and redaction rules
+//! configured in the app.
+//!
+//! ```no_run
+//! for group in sessionly_core::get_all_sessions() {
+//!     for summary in &group.sessions {
+//!         let (id, project) = (&summary.id, &summary.project_encoded);
+//!         let session = sessionly_core::get_session(id, project, Some(&summary.source));
+//!         if let Some(session) = session {
+//!             println!("{}", sessionly_core::session_to_markdown(&session));
+//!         }
+//!     }
+//! }
+//! ```
+
+#![warn(missing_docs)]
+
+pub mod html_export;
+pub mod json_export;
+pub mod markdown_export;
+pub mod mcp;
+pub mod redaction;
+mod session_index;
+pub mod session_search;
+pub mod session_store;
+pub mod session_types;
+pub mod settings;
+
+pub use html_export::session_to_html;
+pub use json_export::{session_to_json, session_to_jsonl};
+pub use markdown_export::session_to_markdown;
+pub use redaction::{redact_session, RedactionConfig, RedactionReport};
+pub use session_store::{
+    get_all_sessions, get_session, list_projects, rebuild_index, set_data_roots, DataRoot,
+};
+pub use session_types::{ProjectGroup, Session, SessionSummary};
@@ -1,8 +1,12 @@
+//! Markdown export, plus the date and duration formatting shared with the
+//! other exporters.
+
 use crate::session_types::{Session, ProcessedMessage, SubagentSession, ToolUseBlock, ToolResultBlock, ThinkingBlock};
 use chrono::{DateTime, Utc, TimeZone};
 use std::collections::HashMap;
 
-fn format_timestamp(timestamp: &str) -> String {
+/// Format an RFC 3339 timestamp as a time of day, e.g. "3:04 PM"
+pub fn format_timestamp(timestamp: &str) -> String {
     if let Ok(dt) = DateTime::parse_from_rfc3339(timestamp) {
         dt.format("%l:%M %p").to_string().trim().to_string()
     } else {
@@ -10,7 +14,8 @@ fn format_timestamp(timestamp: &str) -> String {
     }
 }
 
-fn format_full_date(timestamp: Option<i64>) -> String {
+/// Format milliseconds since epoch as a date, e.g. "March 15, 2026"
+pub(crate) fn format_full_date(timestamp: Option<i64>) -> String {
     match timestamp {
         Some(ms) => {
             if let Some(dt) = Utc.timestamp_millis_opt(ms).single() {
@@ -23,7 +28,8 @@ fn format_full_date(timestamp: Option<i64>) -> String {
     }
 }
 
-fn format_duration(start: Option<i64>, end: Option<i64>) -> Option<String> {
+/// Human-readable time between two timestamps in ms, if both are known
+pub fn format_duration(start: Option<i64>, end: Option<i64>) -> Option<String> {
     match (start, end) {
         (Some(s), Some(e)) => {
             let minutes = ((e - s) / 60000) as u64;
@@ -140,6 +146,7 @@ fn format_message(message: &ProcessedMessage, subagents: &HashMap<String, Subage
     lines.join("\n")
 }
 
+/// Render a session as a Markdown document
 pub fn session_to_markdown(session: &Session) -> String {
     let mut lines = Vec::new();
 
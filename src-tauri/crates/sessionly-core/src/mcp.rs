@@ -0,0 +1,578 @@
+//! Model Context Protocol server over stdio, so Claude Code can look up what
+//! happened in earlier sessions.
+//!
+//! Messages are newline-delimited JSON-RPC 2.0. Sessions are available through
+//! tools and as `sessionly://session/<source>/<projectEncoded>/<id>` resources
+//! rendered as Markdown. Content is redacted with the app's settings unless
+//! disabled.
+
+use crate::json_export::{self, ExportUsage};
+use crate::markdown_export;
+use crate::redaction::{self, RedactionConfig, Redactor};
+use crate::session_search::{self, SearchQuery};
+use crate::session_store;
+use crate::session_types::{Session, SessionSummary, TokenUsage};
+use chrono::{SecondsFormat, TimeZone, Utc};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::io::{BufRead, Write};
+
+/// Supported protocol versions, newest first
+const PROTOCOL_VERSIONS: &[&str] = &["2025-06-18", "2025-03-26", "2024-11-05"];
+const RESOURCE_SCHEME: &str = "sessionly://session/";
+const RESOURCE_PAGE_SIZE: usize = 100;
+const DEFAULT_RECENT_LIMIT: usize = 20;
+const DEFAULT_SEARCH_LIMIT: usize = 20;
+/// Characters of Markdown `get_session` returns unless asked for more
+const DEFAULT_MAX_CHARS: usize = 100_000;
+const MAX_PROMPTS: usize = 20;
+const PROMPT_CHARS: usize = 300;
+const TITLE_CHARS: usize = 80;
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const INTERNAL_ERROR: i64 = -32603;
+const RESOURCE_NOT_FOUND: i64 = -32002;
+
+const INSTRUCTIONS: &str = "Look up earlier Claude Code sessions. To find past work on the \
+current project, call list_recent_sessions with the working directory as `project`, then \
+get_session_summary or get_session for details. search_sessions finds sessions by content.";
+
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+/// A session as listed by `list_recent_sessions`
+#[derive(Debug, Serialize)]
+struct SessionListing {
+    session_id: String,
+    project: String,
+    project_encoded: String,
+    /// Label of the data root the session was read from
+    source: String,
+    started_at: Option<String>,
+    git_branch: Option<String>,
+    message_count: usize,
+    first_message: String,
+}
+
+/// A match as returned by `search_sessions`
+#[derive(Debug, Serialize)]
+struct SearchResult {
+    session_id: String,
+    project: String,
+    project_encoded: String,
+    source: String,
+    agent_id: Option<String>,
+    role: String,
+    timestamp: String,
+    tool_name: Option<String>,
+    snippet: String,
+}
+
+#[derive(Debug, Serialize)]
+struct SessionOverview {
+    session_id: String,
+    project: String,
+    project_encoded: String,
+    git_branch: Option<String>,
+    model: Option<String>,
+    started_at: Option<String>,
+    ended_at: Option<String>,
+    duration: Option<String>,
+    message_count: usize,
+    subagent_count: usize,
+    usage: ExportUsage,
+    /// What the user asked for, in order
+    user_prompts: Vec<String>,
+    /// Tool name -> number of calls
+    tool_calls: BTreeMap<String, usize>,
+    /// Files written or edited
+    files_modified: Vec<String>,
+}
+
+fn format_time(ms: Option<i64>) -> Option<String> {
+    ms.and_then(|ms| Utc.timestamp_millis_opt(ms).single())
+        .map(|dt| dt.to_rfc3339_opts(SecondsFormat::Secs, true))
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+fn str_arg<'a>(args: &'a Value, name: &str) -> Option<&'a str> {
+    args.get(name).and_then(Value::as_str).filter(|s| !s.is_empty())
+}
+
+fn required_str<'a>(args: &'a Value, name: &str) -> Result<&'a str, String> {
+    str_arg(args, name).ok_or_else(|| format!("'{}' is required", name))
+}
+
+fn usize_arg(args: &Value, name: &str, default: usize) -> Result<usize, String> {
+    match args.get(name) {
+        None | Some(Value::Null) => Ok(default),
+        Some(value) => value
+            .as_u64()
+            .map(|n| n as usize)
+            .ok_or_else(|| format!("'{}' must be a non-negative integer", name)),
+    }
+}
+
+/// Whether a project matches an encoded name or a decoded path, e.g. the
+/// working directory Claude Code runs in
+fn project_matches(summary: &SessionSummary, project: &str) -> bool {
+    let project = project.trim_end_matches('/');
+    summary.project_encoded == project || summary.project == project
+}
+
+/// All sessions, newest first
+fn all_sessions() -> Vec<SessionSummary> {
+    let mut sessions: Vec<SessionSummary> = session_store::get_all_sessions()
+        .into_iter()
+        .flat_map(|group| group.sessions)
+        .collect();
+    sessions.sort_by_key(|s| Reverse(s.start_time));
+    sessions
+}
+
+/// Redact a string with the text redactor, if redaction is on
+fn redact(redactor: &mut Option<Redactor>, text: &str) -> String {
+    match redactor {
+        Some(redactor) => redactor.redact_str(text),
+        None => text.to_string(),
+    }
+}
+
+fn session_listing(s: SessionSummary, redactor: &mut Option<Redactor>) -> SessionListing {
+    SessionListing {
+        started_at: format_time(s.start_time),
+        project: redact(redactor, &s.project),
+        first_message: truncate(&redact(redactor, &s.first_message), PROMPT_CHARS),
+        session_id: s.id,
+        project_encoded: s.project_encoded,
+        source: s.source,
+        git_branch: s.git_branch,
+        message_count: s.message_count,
+    }
+}
+
+fn session_resource(s: &SessionSummary, redactor: &mut Option<Redactor>) -> Value {
+    json!({
+        "uri": format!("{}{}/{}/{}", RESOURCE_SCHEME, s.source, s.project_encoded, s.id),
+        "name": s.id,
+        "title": truncate(&redact(redactor, &s.first_message), TITLE_CHARS),
+        "description": format!(
+            "{} · {} · {} messages",
+            redact(redactor, &s.project),
+            format_time(s.start_time).unwrap_or_else(|| "unknown date".to_string()),
+            s.message_count
+        ),
+        "mimeType": "text/markdown"
+    })
+}
+
+fn tool(name: &str, description: &str, properties: Value, required: &[&str]) -> Value {
+    json!({
+        "name": name,
+        "description": description,
+        "inputSchema": { "type": "object", "properties": properties, "required": required }
+    })
+}
+
+fn tool_definitions() -> Value {
+    let string = |description: &str| json!({ "type": "string", "description": description });
+    let integer = |description: String| json!({ "type": "integer", "description": description });
+    let session_id = json!({ "type": "string", "description": "Session ID" });
+    let session_project = string("Project path or encoded name, if the ID is ambiguous");
+    json!([
+        tool(
+            "list_recent_sessions",
+            "List Claude Code sessions, newest first, optionally for one project",
+            json!({
+                "project": string("Project path (e.g. the working directory) or encoded name"),
+                "limit": integer(format!("Maximum sessions (default {})", DEFAULT_RECENT_LIMIT))
+            }),
+            &[],
+        ),
+        tool(
+            "search_sessions",
+            "Search messages, thinking, tool inputs and tool results across sessions",
+            json!({
+                "query": { "type": "string", "description": "Words or phrase to find" },
+                "project": string("Project path or encoded name to search in"),
+                "limit": integer(format!("Maximum matches (default {})", DEFAULT_SEARCH_LIMIT))
+            }),
+            &["query"],
+        ),
+        tool(
+            "get_session_summary",
+            "Summarize a session: prompts, tools used, files modified and token usage",
+            json!({ "session_id": session_id, "project": session_project }),
+            &["session_id"],
+        ),
+        tool(
+            "get_session",
+            "Get a session's full transcript as Markdown",
+            json!({
+                "session_id": session_id,
+                "project": session_project,
+                "max_chars": integer(format!(
+                    "Truncate the transcript after this many characters (default {})",
+                    DEFAULT_MAX_CHARS
+                ))
+            }),
+            &["session_id"],
+        ),
+    ])
+}
+
+/// Serves sessions over MCP, redacting content when configured to
+pub struct McpServer {
+    redaction: Option<RedactionConfig>,
+}
+
+impl McpServer {
+    /// `redaction` is applied to everything returned, if set and enabled
+    pub fn new(redaction: Option<RedactionConfig>) -> Self {
+        Self {
+            redaction: redaction.filter(|config| config.enabled),
+        }
+    }
+
+    /// Serve requests from stdin until it closes
+    pub fn serve_stdio(&self) -> Result<(), String> {
+        let stdin = std::io::stdin();
+        let mut stdout = std::io::stdout();
+        for line in stdin.lock().lines() {
+            let line = line.map_err(|e| e.to_string())?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            if let Some(response) = self.handle_message(&line) {
+                writeln!(stdout, "{}", response)
+                    .and_then(|_| stdout.flush())
+                    .map_err(|e| e.to_string())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Handle one JSON-RPC message, returning the response if it needs one
+    pub fn handle_message(&self, line: &str) -> Option<Value> {
+        let message: Value = match serde_json::from_str(line) {
+            Ok(message) => message,
+            Err(e) => return Some(error_response(Value::Null, PARSE_ERROR, &e.to_string())),
+        };
+        let id = message.get("id").cloned();
+        let Some(method) = message.get("method").and_then(Value::as_str) else {
+            return id.map(|id| error_response(id, INVALID_REQUEST, "Missing method"));
+        };
+        // Notifications get no response
+        let id = id?;
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+        Some(match self.dispatch(method, &params) {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(e) => error_response(id, e.code, &e.message),
+        })
+    }
+
+    fn dispatch(&self, method: &str, params: &Value) -> Result<Value, RpcError> {
+        match method {
+            "initialize" => Ok(initialize(params)),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({ "tools": tool_definitions() })),
+            "tools/call" => self.call_tool(params),
+            "resources/list" => self.list_resources(params),
+            "resources/templates/list" => Ok(json!({
+                "resourceTemplates": [{
+                    "uriTemplate": format!(
+                        "{}{{source}}/{{projectEncoded}}/{{sessionId}}",
+                        RESOURCE_SCHEME
+                    ),
+                    "name": "session",
+                    "title": "Claude Code session",
+                    "description": "A session transcript as Markdown",
+                    "mimeType": "text/markdown"
+                }]
+            })),
+            "resources/read" => self.read_resource(params),
+            _ => Err(RpcError::new(METHOD_NOT_FOUND, format!("Unknown method '{}'", method))),
+        }
+    }
+
+    fn call_tool(&self, params: &Value) -> Result<Value, RpcError> {
+        let name = params.get("name").and_then(Value::as_str).unwrap_or_default();
+        let args = params.get("arguments").cloned().unwrap_or_else(|| json!({}));
+        let result = match name {
+            "list_recent_sessions" => self.list_recent_sessions(&args),
+            "search_sessions" => self.search_sessions(&args),
+            "get_session_summary" => self.get_session_summary(&args),
+            "get_session" => self.get_session(&args),
+            _ => return Err(RpcError::new(INVALID_PARAMS, format!("Unknown tool '{}'", name))),
+        };
+        // Tool failures are reported to the model rather than as protocol errors
+        Ok(match result {
+            Ok(text) => json!({ "content": [{ "type": "text", "text": text }], "isError": false }),
+            Err(e) => json!({ "content": [{ "type": "text", "text": e }], "isError": true }),
+        })
+    }
+
+    /// A redactor for strings outside a loaded session, like listings
+    fn text_redactor(&self) -> Result<Option<Redactor>, String> {
+        self.redaction.as_ref().map(Redactor::for_text).transpose()
+    }
+
+    /// Load a session with the configured redaction applied
+    fn load_session(
+        &self,
+        session_id: &str,
+        project: Option<&str>,
+        source: Option<&str>,
+    ) -> Result<Session, String> {
+        let summary = all_sessions()
+            .into_iter()
+            .filter(|s| source.is_none_or(|source| s.source == source))
+            .find(|s| s.id == session_id && project.is_none_or(|p| project_matches(s, p)))
+            .ok_or_else(|| format!("Session '{}' not found", session_id))?;
+        let mut session =
+            session_store::get_session(&summary.id, &summary.project_encoded, Some(&summary.source))
+            .ok_or_else(|| format!("Session '{}' could not be read", session_id))?;
+        if let Some(config) = &self.redaction {
+            redaction::redact_session(&mut session, config)?;
+        }
+        Ok(session)
+    }
+
+    fn search_sessions(&self, args: &Value) -> Result<String, String> {
+        let query = SearchQuery {
+            query: required_str(args, "query")?.to_string(),
+            project: str_arg(args, "project").map(|p| p.trim_end_matches('/').to_string()),
+            limit: Some(usize_arg(args, "limit", DEFAULT_SEARCH_LIMIT)?),
+            ..Default::default()
+        };
+        let mut redactor = self.text_redactor()?;
+        let results: Vec<SearchResult> = session_search::search(&query)
+            .into_iter()
+            .map(|hit| SearchResult {
+                snippet: redact(&mut redactor, &hit.snippet),
+                project: redact(&mut redactor, &hit.project),
+                session_id: hit.session_id,
+                project_encoded: hit.project_encoded,
+                source: hit.source,
+                agent_id: hit.agent_id,
+                role: hit.role,
+                timestamp: hit.timestamp,
+                tool_name: hit.tool_name,
+            })
+            .collect();
+        serde_json::to_string_pretty(&results).map_err(|e| e.to_string())
+    }
+
+    fn get_session_summary(&self, args: &Value) -> Result<String, String> {
+        let session_id = required_str(args, "session_id")?;
+        let session = self.load_session(session_id, str_arg(args, "project"), None)?;
+        let messages = session
+            .messages
+            .iter()
+            .chain(session.subagents.values().flat_map(|s| &s.messages));
+
+        let mut usage = TokenUsage::default();
+        let mut tool_calls: BTreeMap<String, usize> = BTreeMap::new();
+        let mut files_modified: Vec<String> = Vec::new();
+        for message in messages {
+            if let Some(message_usage) = message.usage {
+                usage += message_usage;
+            }
+            for tool in &message.tool_use_blocks {
+                *tool_calls.entry(tool.name.clone()).or_default() += 1;
+                if matches!(tool.name.as_str(), "Write" | "Edit" | "MultiEdit" | "NotebookEdit") {
+                    let path = tool.input.get("file_path").or(tool.input.get("notebook_path"));
+                    if let Some(path) = path.and_then(Value::as_str) {
+                        if !files_modified.iter().any(|f| f == path) {
+                            files_modified.push(path.to_string());
+                        }
+                    }
+                }
+            }
+        }
+
+        let overview = SessionOverview {
+            session_id: session.id.clone(),
+            project: session.project.clone(),
+            project_encoded: session.project_encoded.clone(),
+            git_branch: session.git_branch.clone(),
+            model: session.messages.iter().rev().find_map(|m| m.model.clone()),
+            started_at: format_time(session.start_time),
+            ended_at: format_time(session.end_time),
+            duration: markdown_export::format_duration(session.start_time, session.end_time),
+            message_count: session.messages.len(),
+            subagent_count: session.subagents.len(),
+            usage: json_export::export_usage(&usage),
+            user_prompts: session
+                .messages
+                .iter()
+                .filter(|m| m.role == "user" && !m.text_content.trim().is_empty())
+                .take(MAX_PROMPTS)
+                .map(|m| truncate(m.text_content.trim(), PROMPT_CHARS))
+                .collect(),
+            tool_calls,
+            files_modified,
+        };
+        serde_json::to_string_pretty(&overview).map_err(|e| e.to_string())
+    }
+
+    fn list_recent_sessions(&self, args: &Value) -> Result<String, String> {
+        let project = str_arg(args, "project");
+        let limit = usize_arg(args, "limit", DEFAULT_RECENT_LIMIT)?;
+        let mut redactor = self.text_redactor()?;
+        let sessions: Vec<SessionListing> = all_sessions()
+            .into_iter()
+            .filter(|s| project.is_none_or(|p| project_matches(s, p)))
+            .take(limit)
+            .map(|s| session_listing(s, &mut redactor))
+            .collect();
+        serde_json::to_string_pretty(&sessions).map_err(|e| e.to_string())
+    }
+
+    /// One page of sessions as resources; the cursor is the offset of the next page
+    fn list_resources(&self, params: &Value) -> Result<Value, RpcError> {
+        let offset = match params.get("cursor").and_then(Value::as_str) {
+            Some(cursor) => cursor
+                .parse()
+                .map_err(|_| RpcError::new(INVALID_PARAMS, "Invalid cursor"))?,
+            None => 0,
+        };
+        let mut redactor = self
+            .text_redactor()
+            .map_err(|e| RpcError::new(INTERNAL_ERROR, e))?;
+        let sessions = all_sessions();
+        let resources: Vec<Value> = sessions
+            .iter()
+            .skip(offset)
+            .take(RESOURCE_PAGE_SIZE)
+            .map(|s| session_resource(s, &mut redactor))
+            .collect();
+        let mut result = json!({ "resources": resources });
+        let next = offset + RESOURCE_PAGE_SIZE;
+        if next < sessions.len() {
+            result["nextCursor"] = json!(next.to_string());
+        }
+        Ok(result)
+    }
+
+    fn get_session(&self, args: &Value) -> Result<String, String> {
+        let max_chars = usize_arg(args, "max_chars", DEFAULT_MAX_CHARS)?;
+        let session_id = required_str(args, "session_id")?;
+        let session = self.load_session(session_id, str_arg(args, "project"), None)?;
+        let markdown = markdown_export::session_to_markdown(&session);
+        Ok(match markdown.char_indices().nth(max_chars) {
+            Some((end, _)) => format!(
+                "{}\n\n[Truncated after {} characters; pass a larger max_chars for more]",
+                &markdown[..end],
+                max_chars
+            ),
+            None => markdown,
+        })
+    }
+
+    fn read_resource(&self, params: &Value) -> Result<Value, RpcError> {
+        let uri = params.get("uri").and_then(Value::as_str).unwrap_or_default();
+        let parts: Vec<&str> = uri
+            .strip_prefix(RESOURCE_SCHEME)
+            .unwrap_or_default()
+            .split('/')
+            .collect();
+        let [source, project_encoded, session_id] = parts[..] else {
+            return Err(RpcError::new(INVALID_PARAMS, format!("Invalid resource URI '{}'", uri)));
+        };
+        let session = self
+            .load_session(session_id, Some(project_encoded), Some(source))
+            .map_err(|e| RpcError::new(RESOURCE_NOT_FOUND, e))?;
+        Ok(json!({
+            "contents": [{
+                "uri": uri,
+                "mimeType": "text/markdown",
+                "text": markdown_export::session_to_markdown(&session)
+            }]
+        }))
+    }
+}
+
+fn initialize(params: &Value) -> Value {
+    let requested = params.get("protocolVersion").and_then(Value::as_str);
+    let version = requested
+        .filter(|v| PROTOCOL_VERSIONS.contains(v))
+        .unwrap_or(PROTOCOL_VERSIONS[0]);
+    json!({
+        "protocolVersion": version,
+        "capabilities": { "tools": {}, "resources": {} },
+        "serverInfo": { "name": "sessionly", "version": env!("CARGO_PKG_VERSION") },
+        "instructions": INSTRUCTIONS
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &str = "sk-ant-REDACTED";
+
+    fn summary() -> SessionSummary {
+        SessionSummary {
+            id: "s1".to_string(),
+            project: "/tmp/demo".to_string(),
+            project_encoded: "-tmp-demo".to_string(),
+            source: "default".to_string(),
+            first_message: format!("use {} for the API", KEY),
+            message_count: 2,
+            start_time: Some(0),
+            end_time: None,
+            git_branch: None,
+            model: None,
+            file_path: String::new(),
+            usage: TokenUsage::default(),
+            model_usage: Default::default(),
+            usage_slices: Vec::new(),
+            cost_usd: None,
+        }
+    }
+
+    #[test]
+    fn listings_and_resources_are_redacted() {
+        let server = McpServer::new(Some(RedactionConfig::default()));
+        let mut redactor = server.text_redactor().unwrap();
+        let listing = session_listing(summary(), &mut redactor);
+        assert!(!listing.first_message.contains(KEY), "{}", listing.first_message);
+        let resource = session_resource(&summary(), &mut redactor);
+        assert!(!resource.to_string().contains(KEY), "{}", resource);
+    }
+
+    #[test]
+    fn listings_are_untouched_without_redaction() {
+        let server = McpServer::new(None);
+        let mut redactor = server.text_redactor().unwrap();
+        let listing = session_listing(summary(), &mut redactor);
+        assert!(listing.first_message.contains(KEY));
+    }
+}
@@ -0,0 +1,373 @@
+//! Redaction of secrets and local paths from sessions before export.
+//!
+//! Patterns may contain a named `secret` group, in which case only that group
+//! is replaced (e.g. keep `password=` but hide the value).
+
+use crate::session_types::{ProcessedMessage, Session};
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+const PROJECT_PLACEHOLDER: &str = "<project>";
+
+/// Built-in detectors as (name, pattern)
+const BUILTIN_DETECTORS: &[(&str, &str)] = &[
+    ("anthropic-key", r"sk-ant-[A-Za-z0-9_-]{20,}"),
+    ("openai-key", r"sk-(?:proj-)?[A-Za-z0-9_-]{32,}"),
+    ("aws-access-key", r"\b(?:AKIA|ASIA)[0-9A-Z]{16}\b"),
+    ("github-token", r"\b(?:gh[pousr]_[A-Za-z0-9]{36,}|github_pat_[A-Za-z0-9_]{60,})"),
+    ("slack-token", r"\bxox[abprs]-[A-Za-z0-9-]{10,}"),
+    ("google-api-key", r"\bAIza[0-9A-Za-z_-]{35}"),
+    ("jwt", r"\beyJ[A-Za-z0-9_-]{8,}\.eyJ[A-Za-z0-9_-]{8,}\.[A-Za-z0-9_-]{8,}"),
+    (
+        "private-key",
+        r"-----BEGIN [A-Z ]*PRIVATE KEY-----[\s\S]*?-----END [A-Z ]*PRIVATE KEY-----",
+    ),
+    ("bearer-token", r"(?i)\bbearer\s+(?P<secret>[A-Za-z0-9._~+/-]{20,}=*)"),
+    (
+        "credential-assignment",
+        r#"(?i)\b[A-Z0-9_]*(?:api_?key|secret|password|passwd|token)[A-Z0-9_]*\s*[:=]\s*["']?(?P<secret>[^\s"'\[][^\s"']{7,})"#,
+    ),
+    ("email", r"\b[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}\b"),
+];
+
+/// Whether an assigned value looks like a credential rather than code or a
+/// count, e.g. not `tokenCount = 12345678` or `token = getToken(user)`
+fn looks_like_secret(value: &str) -> bool {
+    value.len() >= 8
+        && !value.contains(['(', ')', '[', ']', '{', '}', '<', '>', ',', ';'])
+        && value.chars().any(|c| c.is_ascii_digit())
+        && value.chars().any(|c| c.is_ascii_alphabetic())
+}
+
+/// Extra check a built-in detector's match (its `secret` group, if any) must pass
+fn builtin_check(name: &str) -> Option<fn(&str) -> bool> {
+    match name {
+        "credential-assignment" => Some(looks_like_secret),
+        _ => None,
+    }
+}
+
+/// Characters that continue a file or directory name
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '.' | '_' | '-')
+}
+
+/// Replace occurrences of `path` that begin and end on a path component
+/// boundary, so `/a/proj` doesn't rewrite `/a/project2`. A trailing `.` still
+/// counts as a boundary when it ends a sentence rather than a file name.
+fn replace_path(text: &str, path: &str, replacement: &str) -> (String, usize) {
+    let mut result = String::with_capacity(text.len());
+    let mut count = 0;
+    let mut copied = 0;
+    for (start, _) in text.match_indices(path) {
+        let end = start + path.len();
+        let joined_before = text[..start].chars().next_back().is_some_and(is_name_char);
+        let joined_after = text[end..]
+            .trim_start_matches('.')
+            .chars()
+            .next()
+            .is_some_and(is_name_char);
+        if joined_before || joined_after {
+            continue;
+        }
+        result.push_str(&text[copied..start]);
+        result.push_str(replacement);
+        copied = end;
+        count += 1;
+    }
+    result.push_str(&text[copied..]);
+    (result, count)
+}
+
+/// A user-defined detector
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomPattern {
+    /// Name reported in [`RedactionReport::by_detector`]
+    pub name: String,
+    /// Regex to match; may use a `secret` group
+    pub pattern: String,
+}
+
+/// Which secrets and paths to redact
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedactionConfig {
+    /// Whether to redact at all
+    pub enabled: bool,
+    /// Names of built-in detectors to skip
+    #[serde(default, rename = "disabledDetectors")]
+    pub disabled_detectors: Vec<String>,
+    /// Detectors added to the built-in ones
+    #[serde(default, rename = "customPatterns")]
+    pub custom_patterns: Vec<CustomPattern>,
+    /// Replace the project path with `<project>` and the home directory with `~`
+    #[serde(default, rename = "anonymizePaths")]
+    pub anonymize_paths: bool,
+}
+
+impl Default for RedactionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            disabled_detectors: Vec::new(),
+            custom_patterns: Vec::new(),
+            anonymize_paths: false,
+        }
+    }
+}
+
+/// What a redaction pass replaced
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RedactionReport {
+    /// Number of secrets replaced
+    pub total: usize,
+    /// Detector name -> number of redactions
+    #[serde(rename = "byDetector")]
+    pub by_detector: BTreeMap<String, usize>,
+    /// Number of paths replaced
+    #[serde(rename = "pathsAnonymized")]
+    pub paths_anonymized: usize,
+}
+
+struct Rule {
+    name: String,
+    regex: Regex,
+    check: Option<fn(&str) -> bool>,
+}
+
+/// Compiled rules for one redaction pass, counting what they replace
+pub struct Redactor {
+    rules: Vec<Rule>,
+    /// (path, replacement), longest first
+    paths: Vec<(String, String)>,
+    report: RedactionReport,
+}
+
+/// Names of the built-in detectors
+pub fn builtin_detectors() -> Vec<&'static str> {
+    BUILTIN_DETECTORS.iter().map(|(name, _)| *name).collect()
+}
+
+fn replace_match(caps: &Captures, placeholder: &str) -> String {
+    let whole = caps.get(0).expect("group 0 always matches");
+    match caps.name("secret") {
+        Some(secret) => {
+            let text = whole.as_str();
+            let start = secret.start() - whole.start();
+            let end = secret.end() - whole.start();
+            format!("{}{}{}", &text[..start], placeholder, &text[end..])
+        }
+        None => placeholder.to_string(),
+    }
+}
+
+impl Redactor {
+    /// A redactor for a session, anonymizing its project paths if configured
+    pub fn new(config: &RedactionConfig, session: &Session) -> Result<Self, String> {
+        let project_paths = [&session.cwd, &session.project, &session.project_encoded];
+        Self::with_project_paths(config, &project_paths.map(String::as_str))
+    }
+
+    /// A redactor for text not tied to one session, e.g. search snippets.
+    /// Only the home directory is anonymized.
+    pub fn for_text(config: &RedactionConfig) -> Result<Self, String> {
+        Self::with_project_paths(config, &[])
+    }
+
+    fn with_project_paths(config: &RedactionConfig, project_paths: &[&str]) -> Result<Self, String> {
+        let mut rules = Vec::new();
+        for (name, pattern) in BUILTIN_DETECTORS {
+            if config.disabled_detectors.iter().any(|d| d == name) {
+                continue;
+            }
+            rules.push(Rule {
+                name: name.to_string(),
+                regex: Regex::new(pattern).map_err(|e| e.to_string())?,
+                check: builtin_check(name),
+            });
+        }
+        for custom in &config.custom_patterns {
+            let regex = Regex::new(&custom.pattern)
+                .map_err(|e| format!("Invalid pattern '{}': {}", custom.name, e))?;
+            rules.push(Rule {
+                name: custom.name.clone(),
+                regex,
+                check: None,
+            });
+        }
+
+        let mut paths = Vec::new();
+        if config.anonymize_paths {
+            for &project_path in project_paths {
+                if !project_path.is_empty() && !paths.iter().any(|(p, _)| p == project_path) {
+                    paths.push((project_path.to_string(), PROJECT_PLACEHOLDER.to_string()));
+                }
+            }
+            if let Some(home) = dirs::home_dir().and_then(|h| h.to_str().map(String::from)) {
+                if home.len() > 1 {
+                    paths.push((home, "~".to_string()));
+                }
+            }
+            paths.sort_by_key(|(path, _)| std::cmp::Reverse(path.len()));
+        }
+
+        Ok(Self {
+            rules,
+            paths,
+            report: RedactionReport::default(),
+        })
+    }
+
+    /// Redact one string, adding what was replaced to the report
+    pub fn redact_str(&mut self, text: &str) -> String {
+        let mut result = text.to_string();
+        for rule in &self.rules {
+            let placeholder = format!("[REDACTED:{}]", rule.name);
+            let mut count = 0;
+            let replaced = rule.regex.replace_all(&result, |caps: &Captures| {
+                let matched = caps.name("secret").or_else(|| caps.get(0));
+                if let (Some(check), Some(matched)) = (rule.check, matched) {
+                    if !check(matched.as_str()) {
+                        return caps[0].to_string();
+                    }
+                }
+                count += 1;
+                replace_match(caps, &placeholder)
+            });
+            if count > 0 {
+                result = replaced.into_owned();
+                self.report.total += count;
+                *self.report.by_detector.entry(rule.name.clone()).or_default() += count;
+            }
+        }
+        for (path, replacement) in &self.paths {
+            let (replaced, count) = replace_path(&result, path, replacement);
+            if count > 0 {
+                result = replaced;
+                self.report.paths_anonymized += count;
+            }
+        }
+        result
+    }
+
+    fn redact_string(&mut self, text: &mut String) {
+        *text = self.redact_str(text);
+    }
+
+    fn redact_value(&mut self, value: &mut serde_json::Value) {
+        match value {
+            serde_json::Value::String(s) => self.redact_string(s),
+            serde_json::Value::Array(items) => items.iter_mut().for_each(|v| self.redact_value(v)),
+            serde_json::Value::Object(map) => map.values_mut().for_each(|v| self.redact_value(v)),
+            _ => {}
+        }
+    }
+
+    fn redact_message(&mut self, message: &mut ProcessedMessage) {
+        self.redact_string(&mut message.text_content);
+        for thinking in &mut message.thinking_blocks {
+            self.redact_string(&mut thinking.thinking);
+        }
+        for tool_use in &mut message.tool_use_blocks {
+            self.redact_value(&mut tool_use.input);
+        }
+        for result in message.tool_results.values_mut() {
+            self.redact_value(&mut result.content);
+        }
+    }
+
+    /// Redact every message, subagent and path-bearing field of a session in place
+    pub fn redact_session(&mut self, session: &mut Session) {
+        for message in &mut session.messages {
+            self.redact_message(message);
+        }
+        for subagent in session.subagents.values_mut() {
+            for message in &mut subagent.messages {
+                self.redact_message(message);
+            }
+        }
+        for field in [
+            &mut session.cwd,
+            &mut session.project,
+            &mut session.project_encoded,
+            &mut session.file_path,
+        ] {
+            self.redact_string(field);
+        }
+    }
+
+    /// The counts of everything redacted so far
+    pub fn into_report(self) -> RedactionReport {
+        self.report
+    }
+}
+
+/// Apply the configured redaction to a session, returning what was redacted
+pub fn redact_session(session: &mut Session, config: &RedactionConfig) -> Result<RedactionReport, String> {
+    if !config.enabled {
+        return Ok(RedactionReport::default());
+    }
+    let mut redactor = Redactor::new(config, session)?;
+    redactor.redact_session(session);
+    Ok(redactor.into_report())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project_redactor(anonymize_paths: bool) -> Redactor {
+        let config = RedactionConfig {
+            anonymize_paths,
+            ..RedactionConfig::default()
+        };
+        Redactor::with_project_paths(&config, &["/a/proj", "-a-proj"]).unwrap()
+    }
+
+    #[test]
+    fn anonymizes_whole_path_components_only() {
+        let mut redactor = project_redactor(true);
+        assert_eq!(
+            redactor.redact_str("cd /a/proj && ls /a/proj/src /a/project2 /a/proj.bak /b/a/proj"),
+            "cd <project> && ls <project>/src /a/project2 /a/proj.bak /b/a/proj"
+        );
+        assert_eq!(redactor.redact_str("Done in /a/proj."), "Done in <project>.");
+        assert_eq!(
+            redactor.redact_str("~/.claude/projects/-a-proj/x.jsonl and -a-proj2"),
+            "~/.claude/projects/<project>/x.jsonl and -a-proj2"
+        );
+        assert_eq!(redactor.into_report().paths_anonymized, 4);
+    }
+
+    #[test]
+    fn leaves_paths_alone_unless_anonymizing() {
+        let mut redactor = project_redactor(false);
+        assert_eq!(redactor.redact_str("ls /a/proj"), "ls /a/proj");
+    }
+
+    #[test]
+    fn credential_assignment_needs_a_secret_looking_value() {
+        let mut redactor = project_redactor(false);
+        for code in [
+            "tokenCount = 12",
+            "tokenCount = 12345678",
+            "let token = getToken(user);",
+            "password: process.env.PASSWORD",
+            "max_tokens=[1024, 2048]",
+        ] {
+            assert_eq!(redactor.redact_str(code), code);
+        }
+        assert_eq!(redactor.into_report().total, 0);
+
+        let mut redactor = project_redactor(false);
+        assert_eq!(
+            redactor.redact_str("DB_PASSWORD=hunter2hunter2"),
+            "DB_PASSWORD=[REDACTED:credential-assignment]"
+        );
+        assert_eq!(
+            redactor.redact_str(r#"api_key: "f81d4fae7dec11d0a76500a0c91e6bf6""#),
+            r#"api_key: "[REDACTED:credential-assignment]""#
+        );
+        assert_eq!(redactor.into_report().by_detector["credential-assignment"], 2);
+    }
+}
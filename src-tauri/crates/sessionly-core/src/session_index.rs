@@ -0,0 +1,196 @@
+use crate::session_store;
+use crate::session_types::SessionSummary;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use std::time::UNIX_EPOCH;
+
+/// Bump whenever `SessionSummary` or the way it is computed changes, so stale
+/// indexes are discarded instead of serving outdated rows.
+const INDEX_VERSION: u32 = 4;
+
+/// Serializes access to the index file between concurrent `get_projects` calls
+static INDEX_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndexEntry {
+    size: u64,
+    mtime: u64,
+    // None for files that contain no messages, so they are not re-scanned either
+    summary: Option<SessionSummary>,
+}
+
+/// On-disk cache of session summaries keyed by file path, size and mtime,
+/// including those of the session's subagent files
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SessionIndex {
+    version: u32,
+    entries: HashMap<String, IndexEntry>,
+    #[serde(skip)]
+    dirty: bool,
+    /// Stamps of each project's legacy subagent files, read once per load
+    #[serde(skip)]
+    legacy_stamps: HashMap<PathBuf, (u64, u64)>,
+}
+
+/// Get the index file path
+pub fn get_index_path() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_default()
+        .join("sessionly")
+        .join("session-index.json")
+}
+
+/// Acquire the index lock for a load/update/save cycle
+pub fn lock() -> MutexGuard<'static, ()> {
+    INDEX_LOCK.lock().unwrap()
+}
+
+/// Size and modification time (ms since epoch) of a file
+fn file_stamp(path: &Path) -> Option<(u64, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+    let mtime = metadata
+        .modified()
+        .ok()?
+        .duration_since(UNIX_EPOCH)
+        .ok()?
+        .as_millis() as u64;
+    Some((metadata.len(), mtime))
+}
+
+/// Combined size and latest mtime of the files in a directory whose names
+/// pass `include`
+fn dir_stamp(dir: &Path, include: impl Fn(&str) -> bool) -> (u64, u64) {
+    let (mut size, mut mtime) = (0, 0);
+    for entry in fs::read_dir(dir).into_iter().flatten().flatten() {
+        if !include(&entry.file_name().to_string_lossy()) {
+            continue;
+        }
+        if let Some((file_size, file_mtime)) = file_stamp(&entry.path()) {
+            size += file_size;
+            mtime = mtime.max(file_mtime);
+        }
+    }
+    (size, mtime)
+}
+
+/// Whether a file in a project directory is a legacy `agent-*.jsonl`
+/// subagent file, which may belong to any of the project's sessions
+fn is_legacy_agent_file(name: &str) -> bool {
+    name.starts_with("agent-") && name.ends_with(".jsonl")
+}
+
+/// Combined size and latest mtime of a session file and its subagent files,
+/// whose usage is part of the summary. `legacy` is the stamp of the project's
+/// legacy subagent files.
+fn session_stamp(path: &Path, legacy: (u64, u64)) -> Option<(u64, u64)> {
+    let (size, mtime) = file_stamp(path)?;
+    let subagents = dir_stamp(&path.with_extension("").join("subagents"), |_| true);
+    Some((size + subagents.0 + legacy.0, mtime.max(subagents.1).max(legacy.1)))
+}
+
+impl SessionIndex {
+    /// Load the index from disk, starting empty if missing, corrupt or outdated
+    pub fn load() -> Self {
+        let index = fs::read_to_string(get_index_path())
+            .ok()
+            .and_then(|content| serde_json::from_str::<SessionIndex>(&content).ok());
+        match index {
+            Some(index) if index.version == INDEX_VERSION => index,
+            _ => Self {
+                version: INDEX_VERSION,
+                dirty: true,
+                ..Self::default()
+            },
+        }
+    }
+
+    /// Write the index to disk atomically
+    pub fn save(&self) -> std::io::Result<()> {
+        let path = get_index_path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let tmp_path = path.with_extension("json.tmp");
+        fs::write(&tmp_path, serde_json::to_vec(self)?)?;
+        fs::rename(&tmp_path, &path)
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Get the summary for a session file, re-scanning it only if its size or
+    /// mtime changed since it was last indexed
+    pub fn summary(&mut self, file_path: &Path) -> Option<SessionSummary> {
+        let key = file_path.to_string_lossy().to_string();
+        let project_dir = file_path.parent()?;
+        let legacy = *self
+            .legacy_stamps
+            .entry(project_dir.to_path_buf())
+            .or_insert_with(|| dir_stamp(project_dir, is_legacy_agent_file));
+        let (size, mtime) = session_stamp(file_path, legacy)?;
+
+        if let Some(entry) = self.entries.get(&key) {
+            if entry.size == size && entry.mtime == mtime {
+                return entry.summary.clone();
+            }
+        }
+
+        let summary = session_store::get_session_summary(file_path);
+        self.entries.insert(
+            key,
+            IndexEntry {
+                size,
+                mtime,
+                summary: summary.clone(),
+            },
+        );
+        self.dirty = true;
+        summary
+    }
+
+    /// Drop entries for files that no longer exist
+    pub fn retain_paths(&mut self, live_paths: &HashSet<String>) {
+        let before = self.entries.len();
+        self.entries.retain(|path, _| live_paths.contains(path));
+        if self.entries.len() != before {
+            self.dirty = true;
+        }
+    }
+}
+
+/// Discard the persisted index so the next listing re-scans every session file
+pub fn clear() -> std::io::Result<()> {
+    let _guard = lock();
+    match fs::remove_file(get_index_path()) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn session_stamps_cover_legacy_subagent_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let session = dir.path().join("s1.jsonl");
+        fs::write(&session, "{}\n").unwrap();
+        fs::write(dir.path().join("s2.jsonl"), "{}\n").unwrap();
+        let stamp = || session_stamp(&session, dir_stamp(dir.path(), is_legacy_agent_file)).unwrap();
+        let before = stamp();
+        assert_eq!(before.0, 3);
+
+        fs::write(dir.path().join("agent-a1.jsonl"), "{\"sessionId\":\"s1\"}\n").unwrap();
+        assert_eq!(stamp().0, before.0 + 19);
+
+        let subagents = dir.path().join("s1").join("subagents");
+        fs::create_dir_all(&subagents).unwrap();
+        fs::write(subagents.join("agent-a2.jsonl"), "{}\n").unwrap();
+        assert_eq!(stamp().0, before.0 + 22);
+    }
+}
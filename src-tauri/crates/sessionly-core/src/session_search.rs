@@ -0,0 +1,382 @@
+//! Full-text search over messages, thinking and tool calls across sessions.
+
+use crate::session_store;
+use crate::session_types::{ProcessedMessage, SessionSummary};
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_LIMIT: usize = 100;
+const SNIPPET_BEFORE: usize = 60;
+const SNIPPET_AFTER: usize = 140;
+
+/// What to search for and where
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SearchQuery {
+    /// Words to find; all must appear in a field, and the whole phrase ranks higher
+    pub query: String,
+    /// Encoded or decoded project path
+    pub project: Option<String>,
+    /// Only messages at or after this time (ms since epoch)
+    pub since: Option<i64>,
+    /// Only messages at or before this time (ms since epoch)
+    pub until: Option<i64>,
+    /// Only messages with this role, `user` or `assistant`
+    pub role: Option<String>,
+    /// Restrict matches to inputs and results of this tool
+    #[serde(rename = "toolName")]
+    pub tool_name: Option<String>,
+    /// Maximum hits to return
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+/// The part of a message a hit was found in
+pub enum MatchField {
+    /// Message text
+    Text,
+    /// A thinking block
+    Thinking,
+    /// Arguments of a tool call
+    ToolInput,
+    /// Result of a tool call
+    ToolResult,
+}
+
+impl MatchField {
+    fn weight(self) -> f64 {
+        match self {
+            MatchField::Text => 3.0,
+            MatchField::ToolInput => 2.0,
+            MatchField::Thinking | MatchField::ToolResult => 1.0,
+        }
+    }
+}
+
+/// A field of a message that matched the query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchHit {
+    /// Session the message belongs to
+    #[serde(rename = "sessionId")]
+    pub session_id: String,
+    /// Decoded project path
+    pub project: String,
+    /// Project directory name as stored on disk
+    #[serde(rename = "projectEncoded")]
+    pub project_encoded: String,
+    /// Label of the data root the session was read from
+    pub source: String,
+    /// Uuid of the matching message
+    #[serde(rename = "messageUuid")]
+    pub message_uuid: String,
+    /// Set when the hit is inside a subagent transcript
+    #[serde(rename = "agentId")]
+    pub agent_id: Option<String>,
+    /// Role of the matching message
+    pub role: String,
+    /// Timestamp of the matching message
+    pub timestamp: String,
+    /// Which part of the message matched
+    pub field: MatchField,
+    /// Tool whose input or result matched
+    #[serde(rename = "toolName")]
+    pub tool_name: Option<String>,
+    /// Text around the match, on one line
+    pub snippet: String,
+    /// Matched ranges in `snippet` as [start, end) UTF-16 offsets, as
+    /// JavaScript indexes strings
+    pub highlights: Vec<(usize, usize)>,
+    /// Relevance; higher is better
+    pub score: f64,
+}
+
+/// Lowercase char-by-char so offsets in the result line up with the original text
+fn fold(text: &str) -> Vec<char> {
+    text.chars()
+        .map(|c| c.to_lowercase().next().unwrap_or(c))
+        .collect()
+}
+
+fn find_all(haystack: &[char], needle: &[char]) -> Vec<usize> {
+    if needle.is_empty() || needle.len() > haystack.len() {
+        return vec![];
+    }
+    (0..=haystack.len() - needle.len())
+        .filter(|&i| haystack[i..i + needle.len()] == *needle)
+        .collect()
+}
+
+/// Collect the human-readable strings in a tool input or result, skipping
+/// block type tags, signatures and embedded image data
+fn value_text(value: &serde_json::Value, out: &mut Vec<String>) {
+    match value {
+        serde_json::Value::String(s) => out.push(s.clone()),
+        serde_json::Value::Array(arr) => arr.iter().for_each(|v| value_text(v, out)),
+        serde_json::Value::Object(obj) => {
+            for (key, v) in obj {
+                if key != "type" && key != "signature" && key != "source" {
+                    value_text(v, out);
+                }
+            }
+        }
+        serde_json::Value::Number(n) => out.push(n.to_string()),
+        _ => {}
+    }
+}
+
+struct FieldMatch {
+    score: f64,
+    snippet: String,
+    highlights: Vec<(usize, usize)>,
+}
+
+/// Score a field against the query terms and build its snippet, or None if
+/// any term is missing
+fn match_field(text: &str, terms: &[Vec<char>], phrase: &[char], field: MatchField) -> Option<FieldMatch> {
+    let chars: Vec<char> = text.chars().collect();
+    let folded = fold(text);
+
+    let mut occurrences: Vec<(usize, usize)> = Vec::new();
+    for term in terms {
+        let found = find_all(&folded, term);
+        if found.is_empty() {
+            return None;
+        }
+        occurrences.extend(found.into_iter().map(|i| (i, i + term.len())));
+    }
+    occurrences.sort();
+
+    let mut score = occurrences.len().min(20) as f64 * field.weight();
+    let phrase_hits = if terms.len() > 1 { find_all(&folded, phrase) } else { vec![] };
+    if !phrase_hits.is_empty() {
+        score *= 2.0;
+    }
+
+    // Center the snippet on the phrase if present, else on the first match
+    let anchor = phrase_hits.first().copied().unwrap_or(occurrences[0].0);
+    let start = anchor.saturating_sub(SNIPPET_BEFORE);
+    let end = (anchor + SNIPPET_AFTER).min(chars.len());
+
+    let mut snippet = String::new();
+    let mut offset = 0usize;
+    if start > 0 {
+        snippet.push('…');
+        offset = 1;
+    }
+    snippet.extend(chars[start..end].iter().map(|&c| if c == '\n' { ' ' } else { c }));
+    if end < chars.len() {
+        snippet.push('…');
+    }
+
+    // UTF-16 offset of each char in the snippet, and of its end
+    let mut utf16 = vec![0];
+    for c in snippet.chars() {
+        utf16.push(utf16[utf16.len() - 1] + c.len_utf16());
+    }
+    let highlights = occurrences
+        .into_iter()
+        .filter(|&(s, e)| s >= start && e <= end)
+        .map(|(s, e)| (utf16[s - start + offset], utf16[e - start + offset]))
+        .collect();
+
+    Some(FieldMatch {
+        score,
+        snippet,
+        highlights,
+    })
+}
+
+struct Matcher<'a> {
+    query: &'a SearchQuery,
+    terms: Vec<Vec<char>>,
+    phrase: Vec<char>,
+}
+
+impl Matcher<'_> {
+    fn message_in_range(&self, message: &ProcessedMessage) -> bool {
+        if self.query.since.is_none() && self.query.until.is_none() {
+            return true;
+        }
+        match session_store::parse_timestamp(&message.timestamp) {
+            Some(ms) => {
+                self.query.since.is_none_or(|since| ms >= since)
+                    && self.query.until.is_none_or(|until| ms <= until)
+            }
+            None => false,
+        }
+    }
+
+    fn search_message(&self, summary: &SessionSummary, agent_id: Option<&str>, message: &ProcessedMessage, hits: &mut Vec<SearchHit>) {
+        if let Some(role) = &self.query.role {
+            if &message.role != role {
+                return;
+            }
+        }
+        if !self.message_in_range(message) {
+            return;
+        }
+
+        let mut candidates: Vec<(MatchField, Option<String>, String)> = Vec::new();
+        if self.query.tool_name.is_none() {
+            candidates.push((MatchField::Text, None, message.text_content.clone()));
+            for thinking in &message.thinking_blocks {
+                candidates.push((MatchField::Thinking, None, thinking.thinking.clone()));
+            }
+        }
+        for tool_use in &message.tool_use_blocks {
+            if self.query.tool_name.as_ref().is_some_and(|t| t != &tool_use.name) {
+                continue;
+            }
+            let mut parts = Vec::new();
+            value_text(&tool_use.input, &mut parts);
+            candidates.push((MatchField::ToolInput, Some(tool_use.name.clone()), parts.join("\n")));
+
+            if let Some(result) = message.tool_results.get(&tool_use.id) {
+                let mut parts = Vec::new();
+                value_text(&result.content, &mut parts);
+                candidates.push((MatchField::ToolResult, Some(tool_use.name.clone()), parts.join("\n")));
+            }
+        }
+
+        for (field, tool_name, text) in candidates {
+            if let Some(m) = match_field(&text, &self.terms, &self.phrase, field) {
+                hits.push(SearchHit {
+                    session_id: summary.id.clone(),
+                    project: summary.project.clone(),
+                    project_encoded: summary.project_encoded.clone(),
+                    source: summary.source.clone(),
+                    message_uuid: message.uuid.clone(),
+                    agent_id: agent_id.map(String::from),
+                    role: message.role.clone(),
+                    timestamp: message.timestamp.clone(),
+                    field,
+                    tool_name,
+                    snippet: m.snippet,
+                    highlights: m.highlights,
+                    score: m.score,
+                });
+            }
+        }
+    }
+}
+
+/// Whether a session can contain hits for the query's project and date filters
+fn session_matches(query: &SearchQuery, summary: &SessionSummary) -> bool {
+    if let Some(project) = &query.project {
+        if &summary.project_encoded != project && &summary.project != project {
+            return false;
+        }
+    }
+    if let (Some(since), Some(end)) = (query.since, summary.end_time) {
+        if end < since {
+            return false;
+        }
+    }
+    if let (Some(until), Some(start)) = (query.until, summary.start_time) {
+        if start > until {
+            return false;
+        }
+    }
+    true
+}
+
+/// Search message text, thinking, tool inputs and tool results across all
+/// sessions (including subagents), ranked by score then recency
+pub fn search(query: &SearchQuery) -> Vec<SearchHit> {
+    let terms: Vec<Vec<char>> = query.query.split_whitespace().map(fold).collect();
+    if terms.is_empty() {
+        return vec![];
+    }
+    let matcher = Matcher {
+        query,
+        terms,
+        phrase: fold(query.query.trim()),
+    };
+
+    let mut hits: Vec<SearchHit> = Vec::new();
+    for group in session_store::get_all_sessions() {
+        for summary in group.sessions.iter().filter(|s| session_matches(query, s)) {
+            let session = session_store::get_session(&summary.id, &summary.project_encoded, Some(&summary.source));
+            let session = match session {
+                Some(s) => s,
+                None => continue,
+            };
+            for message in &session.messages {
+                matcher.search_message(summary, None, message, &mut hits);
+            }
+            for (agent_id, subagent) in &session.subagents {
+                for message in &subagent.messages {
+                    matcher.search_message(summary, Some(agent_id), message, &mut hits);
+                }
+            }
+        }
+    }
+
+    hits.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| b.timestamp.cmp(&a.timestamp))
+    });
+    hits.truncate(query.limit.unwrap_or(DEFAULT_LIMIT));
+    hits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(text: &str, query: &str) -> Option<FieldMatch> {
+        let terms: Vec<Vec<char>> = query.split_whitespace().map(fold).collect();
+        match_field(text, &terms, &fold(query), MatchField::Text)
+    }
+
+    /// The highlighted parts of a snippet, sliced by UTF-16 offsets as the frontend does
+    fn highlighted(m: &FieldMatch) -> Vec<String> {
+        let units: Vec<u16> = m.snippet.encode_utf16().collect();
+        m.highlights
+            .iter()
+            .map(|&(s, e)| String::from_utf16(&units[s..e]).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn matches_case_insensitively() {
+        let m = field("Fix the Parser bug", "parser BUG").unwrap();
+        assert_eq!(m.snippet, "Fix the Parser bug");
+        assert_eq!(highlighted(&m), ["Parser", "bug"]);
+        assert!(field("Fix the parser", "parser bug").is_none());
+    }
+
+    #[test]
+    fn the_whole_phrase_ranks_higher() {
+        let phrase = field("the parser bug", "parser bug").unwrap();
+        let apart = field("the bug in the parser", "parser bug").unwrap();
+        assert!(phrase.score > apart.score);
+    }
+
+    #[test]
+    fn snippets_are_windowed_around_the_match() {
+        let text = format!("{}needle\nnext{}", "a".repeat(100), "b".repeat(200));
+        let m = field(&text, "needle").unwrap();
+        let expected = format!("…{}needle next{}…", "a".repeat(SNIPPET_BEFORE), "b".repeat(SNIPPET_AFTER - 11));
+        assert_eq!(m.snippet, expected);
+        assert_eq!(m.highlights, [(SNIPPET_BEFORE + 1, SNIPPET_BEFORE + 7)]);
+
+        // Matches outside the window aren't highlighted
+        let text = format!("needle {} needle", "c".repeat(300));
+        let m = field(&text, "needle").unwrap();
+        assert!(m.snippet.starts_with("needle") && m.snippet.ends_with('…'));
+        assert_eq!(m.highlights, [(0, 6)]);
+    }
+
+    #[test]
+    fn highlights_are_utf16_offsets() {
+        let m = field("🦀 Größe straße 🦀 STRASSE", "straße").unwrap();
+        assert_eq!(highlighted(&m), ["straße"]);
+        assert_eq!(m.highlights, [(9, 15)]);
+
+        let text = format!("{}🦀 日本語のテキスト", "é".repeat(100));
+        let m = field(&text, "テキスト").unwrap();
+        assert!(m.snippet.starts_with('…'));
+        assert_eq!(highlighted(&m), ["テキスト"]);
+    }
+}
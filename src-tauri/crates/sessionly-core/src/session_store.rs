@@ -0,0 +1,1092 @@
+//! Discovery and parsing of Claude Code session files.
+//!
+//! Sessions are read from the `projects/` directory of each data root: the
+//! Claude config directory, plus any configured with [`set_data_roots`].
+
+use crate::session_index::{self, SessionIndex};
+use crate::session_types::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+/// Label of the data root at the Claude config directory
+pub const DEFAULT_SOURCE: &str = "default";
+
+/// Key identifying a project across data roots: the encoded name for the
+/// default root, `source:projectEncoded` for others, as the app's
+/// `projectKey` builds it
+pub fn project_key(source: &str, project_encoded: &str) -> String {
+    if source.is_empty() || source == DEFAULT_SOURCE {
+        project_encoded.to_string()
+    } else {
+        format!("{}:{}", source, project_encoded)
+    }
+}
+
+/// Roots scanned in addition to the Claude config directory
+static EXTRA_ROOTS: RwLock<Vec<DataRoot>> = RwLock::new(Vec::new());
+
+/// A directory Claude Code keeps sessions in, e.g. a second profile or a
+/// backup copied from another machine
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DataRoot {
+    /// Identifies the root; stored as `source` on its projects and sessions
+    pub label: String,
+    /// Directory containing `projects/`, like `~/.claude`
+    pub path: PathBuf,
+    /// Disabled roots are kept in settings but not scanned
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl DataRoot {
+    /// The `projects/` directory inside the root
+    pub fn projects_dir(&self) -> PathBuf {
+        self.path.join("projects")
+    }
+}
+
+/// Get the Claude directory path: `$CLAUDE_CONFIG_DIR` if set, else `~/.claude`
+pub fn get_claude_dir() -> PathBuf {
+    if let Some(dir) = std::env::var_os("CLAUDE_CONFIG_DIR").filter(|d| !d.is_empty()) {
+        return PathBuf::from(dir);
+    }
+    dirs::home_dir()
+        .unwrap_or_default()
+        .join(".claude")
+}
+
+/// Check that roots have usable, unique labels and don't repeat the default root
+pub fn validate_data_roots(roots: &[DataRoot]) -> Result<(), String> {
+    let mut labels = HashSet::new();
+    for root in roots {
+        if root.label.is_empty()
+            || !root
+                .label
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        {
+            return Err(format!(
+                "Invalid label '{}': use letters, digits, '-', '_' and '.'",
+                root.label
+            ));
+        }
+        if root.label == DEFAULT_SOURCE || !labels.insert(root.label.as_str()) {
+            return Err(format!("Label '{}' is already in use", root.label));
+        }
+        if root.path.as_os_str().is_empty() {
+            return Err(format!("No path given for '{}'", root.label));
+        }
+    }
+    Ok(())
+}
+
+/// Scan these roots as well as the Claude config directory
+pub fn set_data_roots(roots: Vec<DataRoot>) {
+    *EXTRA_ROOTS.write().unwrap() = roots;
+}
+
+/// A path with symlinks and `..` resolved, for telling whether two roots
+/// are the same directory; unchanged if it doesn't exist
+fn resolved(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Every enabled data root, starting with the Claude config directory.
+/// Roots pointing at a directory already listed are skipped.
+pub fn data_roots() -> Vec<DataRoot> {
+    let default = DataRoot {
+        label: DEFAULT_SOURCE.to_string(),
+        path: get_claude_dir(),
+        enabled: true,
+    };
+    let mut seen = vec![resolved(&default.path)];
+    let mut roots = vec![default];
+    for root in EXTRA_ROOTS.read().unwrap().iter().filter(|r| r.enabled) {
+        let path = resolved(&root.path);
+        if !seen.contains(&path) {
+            seen.push(path);
+            roots.push(root.clone());
+        }
+    }
+    roots
+}
+
+/// Label of the data root a session file belongs to
+pub fn source_for_path(file_path: &Path) -> String {
+    data_roots()
+        .into_iter()
+        .filter(|root| file_path.starts_with(root.projects_dir()))
+        .max_by_key(|root| root.path.components().count())
+        .map_or_else(|| DEFAULT_SOURCE.to_string(), |root| root.label)
+}
+
+/// Path of a session file. Without a source, the first root that has the
+/// session is used.
+pub fn session_file_path(
+    session_id: &str,
+    project_encoded: &str,
+    source: Option<&str>,
+) -> Option<PathBuf> {
+    let file_name = format!("{}.jsonl", session_id);
+    let mut candidates = data_roots()
+        .into_iter()
+        .filter(|root| source.is_none_or(|s| root.label == s))
+        .map(|root| root.projects_dir().join(project_encoded).join(&file_name));
+    match source {
+        Some(_) => candidates.next(),
+        None => candidates.find(|path| path.exists()),
+    }
+}
+
+/// Decode an encoded project path
+/// e.g., "-Users-name-project" -> "/Users/name/project"
+pub(crate) fn decode_project_path(encoded: &str) -> String {
+    if !encoded.starts_with('-') {
+        return encoded.to_string();
+    }
+
+    let simple_decode = encoded.replace('-', "/");
+    if Path::new(&simple_decode).exists() {
+        return simple_decode;
+    }
+
+    let segments: Vec<&str> = encoded[1..].split('-').collect();
+    find_valid_path("/", &segments).unwrap_or(simple_decode)
+}
+
+fn find_valid_path(base: &str, segments: &[&str]) -> Option<String> {
+    if segments.is_empty() {
+        return if Path::new(base).exists() {
+            Some(base.to_string())
+        } else {
+            None
+        };
+    }
+
+    // Try adding next segment as a new directory
+    let with_slash = Path::new(base).join(segments[0]);
+    if with_slash.exists() {
+        if let Some(result) = find_valid_path(with_slash.to_str()?, &segments[1..]) {
+            return Some(result);
+        }
+    }
+
+    // Try joining with hyphen
+    if segments.len() >= 2 {
+        let combined = format!("{}-{}", segments[0], segments[1]);
+        let mut new_segments = vec![combined.as_str()];
+        new_segments.extend_from_slice(&segments[2..]);
+        if let Some(result) = find_valid_path(base, &new_segments) {
+            return Some(result);
+        }
+    }
+
+    // Last segment
+    if segments.len() == 1 {
+        let with_slash = Path::new(base).join(segments[0]);
+        if with_slash.exists() {
+            return Some(with_slash.to_str()?.to_string());
+        }
+    }
+
+    None
+}
+
+/// Parse an entry timestamp (RFC 3339, or naive UTC) into milliseconds since epoch
+pub fn parse_timestamp(ts: &str) -> Option<i64> {
+    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(ts) {
+        Some(dt.timestamp_millis())
+    } else if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(ts, "%Y-%m-%dT%H:%M:%S%.f") {
+        Some(dt.and_utc().timestamp_millis())
+    } else {
+        None
+    }
+}
+
+/// Extract text content from message content (can be string or array of blocks)
+fn extract_text_content(content: &serde_json::Value) -> String {
+    if let Some(s) = content.as_str() {
+        return s.to_string();
+    }
+    if let Some(arr) = content.as_array() {
+        let texts: Vec<String> = arr
+            .iter()
+            .filter_map(|block| {
+                if block.get("type")?.as_str()? == "text" {
+                    block.get("text")?.as_str().map(String::from)
+                } else {
+                    None
+                }
+            })
+            .collect();
+        return texts.join("\n");
+    }
+    String::new()
+}
+
+/// Extract thinking blocks from content
+fn extract_thinking_blocks(content: &serde_json::Value) -> Vec<ThinkingBlock> {
+    let arr = match content.as_array() {
+        Some(a) => a,
+        None => return vec![],
+    };
+    arr.iter()
+        .filter_map(|block| {
+            if block.get("type")?.as_str()? == "thinking" {
+                Some(ThinkingBlock {
+                    block_type: "thinking".to_string(),
+                    thinking: block.get("thinking")?.as_str()?.to_string(),
+                    signature: block.get("signature").and_then(|s| s.as_str()).map(String::from),
+                })
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Extract tool use blocks from content
+fn extract_tool_use_blocks(content: &serde_json::Value) -> Vec<ToolUseBlock> {
+    let arr = match content.as_array() {
+        Some(a) => a,
+        None => return vec![],
+    };
+    arr.iter()
+        .filter_map(|block| {
+            if block.get("type")?.as_str()? == "tool_use" {
+                Some(ToolUseBlock {
+                    block_type: "tool_use".to_string(),
+                    id: block.get("id")?.as_str()?.to_string(),
+                    name: block.get("name")?.as_str()?.to_string(),
+                    input: block.get("input").cloned().unwrap_or(serde_json::Value::Object(Default::default())),
+                    agent_id: None,
+                })
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Extract tool results from content
+fn extract_tool_results(content: &serde_json::Value) -> HashMap<String, ToolResultBlock> {
+    let mut results = HashMap::new();
+    if let Some(arr) = content.as_array() {
+        for block in arr {
+            if block.get("type").and_then(|t| t.as_str()) == Some("tool_result") {
+                if let Some(tool_use_id) = block.get("tool_use_id").and_then(|t| t.as_str()) {
+                    results.insert(
+                        tool_use_id.to_string(),
+                        ToolResultBlock {
+                            block_type: "tool_result".to_string(),
+                            tool_use_id: tool_use_id.to_string(),
+                            content: block.get("content").cloned().unwrap_or(serde_json::Value::String(String::new())),
+                            is_error: block.get("is_error").and_then(|e| e.as_bool()),
+                        },
+                    );
+                }
+            }
+        }
+    }
+    results
+}
+
+/// Process a raw entry into a ProcessedMessage
+fn process_message(entry: &RawEntry) -> Option<ProcessedMessage> {
+    let msg = entry.message.as_ref()?;
+    let content = &msg.content;
+
+    let text_content = extract_text_content(content);
+    let thinking_blocks = extract_thinking_blocks(content);
+    let tool_use_blocks = extract_tool_use_blocks(content);
+    let tool_results = extract_tool_results(content);
+
+    Some(ProcessedMessage {
+        uuid: entry.uuid.clone().unwrap_or_default(),
+        parent_uuid: entry.parent_uuid.as_ref().and_then(|v| v.as_str()).map(String::from),
+        timestamp: entry.timestamp.clone().unwrap_or_default(),
+        role: msg.role.clone(),
+        text_content,
+        thinking_blocks,
+        tool_use_blocks,
+        tool_results,
+        model: msg.model.clone(),
+        message_id: msg.id.clone(),
+        usage: msg.usage,
+    })
+}
+
+/// A project's directory within a data root
+#[derive(Debug, Clone)]
+pub struct ProjectDir {
+    /// Label of the data root
+    pub source: String,
+    /// Directory name, the encoded project path
+    pub project_encoded: String,
+    /// Full path of the directory
+    pub path: PathBuf,
+}
+
+/// List the projects in every data root
+pub fn list_projects() -> Vec<ProjectDir> {
+    let mut projects = Vec::new();
+    for root in data_roots() {
+        let Ok(entries) = fs::read_dir(root.projects_dir()) else {
+            continue;
+        };
+        projects.extend(
+            entries
+                .filter_map(|e| e.ok())
+                .filter(|e| e.file_type().map(|t| t.is_dir()).unwrap_or(false))
+                .map(|e| ProjectDir {
+                    source: root.label.clone(),
+                    project_encoded: e.file_name().to_string_lossy().to_string(),
+                    path: e.path(),
+                }),
+        );
+    }
+    projects
+}
+
+/// List session files in a project directory (excluding agent files)
+pub(crate) fn list_session_files(project_dir: &Path) -> Vec<PathBuf> {
+    if !project_dir.exists() {
+        return vec![];
+    }
+    match fs::read_dir(project_dir) {
+        Ok(entries) => entries
+            .filter_map(|e| e.ok())
+            .filter(|e| {
+                let name = e.file_name().to_string_lossy().to_string();
+                name.ends_with(".jsonl") && !name.starts_with("agent-")
+            })
+            .map(|e| e.path())
+            .collect(),
+        Err(_) => vec![],
+    }
+}
+
+/// Get a session summary by parsing a JSONL file
+pub fn get_session_summary(file_path: &Path) -> Option<SessionSummary> {
+    let file = fs::File::open(file_path).ok()?;
+    let reader = BufReader::new(file);
+
+    let session_id = file_path.file_stem()?.to_str()?.to_string();
+    let mut project = String::new();
+    let mut project_encoded = String::new();
+    let mut first_message = String::new();
+    let mut message_count = 0usize;
+    let mut start_time: Option<i64> = None;
+    let mut end_time: Option<i64> = None;
+    let mut git_branch: Option<String> = None;
+    let mut model: Option<String> = None;
+    let mut tally = UsageTally::default();
+    let mut metadata_extracted = false;
+
+    // Extract project from path
+    if let Some(parent) = file_path.parent() {
+        if let Some(name) = parent.file_name() {
+            project_encoded = name.to_string_lossy().to_string();
+            project = decode_project_path(&project_encoded);
+        }
+    }
+
+    for line in reader.lines() {
+        let line = match line {
+            Ok(l) => l,
+            Err(_) => continue,
+        };
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+
+        let entry: RawEntry = match serde_json::from_str(trimmed) {
+            Ok(e) => e,
+            Err(_) => continue,
+        };
+
+        let entry_type = match &entry.entry_type {
+            Some(t) => t.as_str(),
+            None => continue,
+        };
+
+        if entry_type == "file-history-snapshot" || entry_type == "progress" {
+            continue;
+        }
+
+        if entry_type == "user" || entry_type == "assistant" {
+            message_count += 1;
+
+            if !metadata_extracted {
+                metadata_extracted = true;
+                git_branch = entry.git_branch.clone();
+            }
+
+            // First user message as summary
+            if first_message.is_empty() && entry_type == "user" {
+                if let Some(msg) = &entry.message {
+                    let text = extract_text_content(&msg.content);
+                    first_message = text.chars().take(200).collect();
+                }
+            }
+
+            // Track model
+            if model.is_none() && entry_type == "assistant" {
+                if let Some(msg) = &entry.message {
+                    model = msg.model.clone();
+                }
+            }
+
+            let timestamp = entry.timestamp.as_deref().and_then(parse_timestamp);
+            tally.add(&entry, timestamp);
+
+            // Track timestamps
+            if let Some(ms) = timestamp {
+                start_time = Some(start_time.map_or(ms, |s: i64| s.min(ms)));
+                end_time = Some(end_time.map_or(ms, |e: i64| e.max(ms)));
+            }
+        }
+    }
+
+    if message_count == 0 {
+        return None;
+    }
+
+    // Subagents are billed to the session that started them
+    if let Some(project_dir) = file_path.parent() {
+        for sub_path in find_subagent_files(project_dir, &session_id) {
+            // Legacy files sit next to the sessions and may belong to any of them
+            let shared = sub_path.parent() == Some(project_dir);
+            tally.add_subagent_file(&sub_path, &session_id, shared);
+        }
+    }
+    let UsageTally {
+        usage,
+        model_usage,
+        slices,
+        ..
+    } = tally;
+
+    Some(SessionSummary {
+        id: session_id,
+        project,
+        project_encoded,
+        source: source_for_path(file_path),
+        first_message,
+        message_count,
+        start_time,
+        end_time,
+        git_branch,
+        model,
+        file_path: file_path.to_string_lossy().to_string(),
+        usage,
+        model_usage,
+        usage_slices: slices
+            .into_iter()
+            .map(|((start, model), usage)| UsageSlice {
+                start,
+                model,
+                usage,
+            })
+            .collect(),
+        cost_usd: None,
+    })
+}
+
+/// Token usage summed once per API message, in total, per model and per
+/// model and time slice
+#[derive(Default)]
+struct UsageTally {
+    usage: TokenUsage,
+    model_usage: HashMap<String, TokenUsage>,
+    slices: BTreeMap<(i64, String), TokenUsage>,
+    /// API message ids already counted; split assistant entries share one
+    ids: HashSet<String>,
+}
+
+impl UsageTally {
+    fn add(&mut self, entry: &RawEntry, timestamp: Option<i64>) {
+        let Some(msg) = &entry.message else {
+            return;
+        };
+        let Some(msg_usage) = msg.usage else {
+            return;
+        };
+        if msg.id.as_ref().is_some_and(|id| !self.ids.insert(id.clone())) {
+            return;
+        }
+        let model = msg.model.clone().unwrap_or_else(|| "unknown".to_string());
+        self.usage += msg_usage;
+        *self.model_usage.entry(model.clone()).or_default() += msg_usage;
+        if let Some(ms) = timestamp {
+            let start = ms - ms.rem_euclid(USAGE_SLICE_MS);
+            *self.slices.entry((start, model)).or_default() += msg_usage;
+        }
+    }
+
+    /// Add the assistant usage in a subagent transcript. In a `shared` file
+    /// only entries recorded for `session_id` count.
+    fn add_subagent_file(&mut self, file_path: &Path, session_id: &str, shared: bool) {
+        let Ok(file) = fs::File::open(file_path) else {
+            return;
+        };
+        for line in BufReader::new(file).lines().map_while(Result::ok) {
+            let Ok(entry) = serde_json::from_str::<RawEntry>(line.trim()) else {
+                continue;
+            };
+            if entry.entry_type.as_deref() != Some("assistant")
+                || (shared && entry.session_id.as_deref() != Some(session_id))
+            {
+                continue;
+            }
+            let timestamp = entry.timestamp.as_deref().and_then(parse_timestamp);
+            self.add(&entry, timestamp);
+        }
+    }
+}
+
+/// Line-by-line parser state shared by full and incremental session parsing
+#[derive(Debug, Default)]
+pub(crate) struct SessionParser {
+    pub cwd: String,
+    pub version: String,
+    pub git_branch: Option<String>,
+    pub start_time: Option<i64>,
+    pub end_time: Option<i64>,
+    /// agentId -> parent Task tool_use id, from progress entries
+    pub agent_links: HashMap<String, String>,
+    /// Tool results seen in user messages, keyed by tool_use id
+    pub pending_tool_results: HashMap<String, ToolResultBlock>,
+    // API message ids whose usage was already attributed to a message
+    usage_ids: HashSet<String>,
+    metadata_extracted: bool,
+}
+
+impl SessionParser {
+    /// Feed one JSONL line, returning the message to display if it produced one
+    pub fn ingest_line(&mut self, line: &str) -> Option<ProcessedMessage> {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            return None;
+        }
+
+        let entry: RawEntry = serde_json::from_str(trimmed).ok()?;
+        let entry_type = entry.entry_type.clone()?;
+
+        // Extract agent links from progress messages
+        if entry_type == "progress" {
+            if let (Some(data), Some(parent_id)) = (&entry.data, &entry.parent_tool_use_id) {
+                if let Some(agent_id) = &data.agent_id {
+                    self.agent_links.insert(agent_id.clone(), parent_id.clone());
+                }
+            }
+            return None;
+        }
+
+        if entry_type != "user" && entry_type != "assistant" {
+            return None;
+        }
+
+        if !self.metadata_extracted {
+            self.metadata_extracted = true;
+            self.cwd = entry.cwd.clone().unwrap_or_default();
+            self.version = entry.version.clone().unwrap_or_default();
+            self.git_branch = entry.git_branch.clone();
+        }
+
+        // Track timestamps
+        if let Some(ms) = entry.timestamp.as_deref().and_then(parse_timestamp) {
+            self.start_time = Some(self.start_time.map_or(ms, |s: i64| s.min(ms)));
+            self.end_time = Some(self.end_time.map_or(ms, |e: i64| e.max(ms)));
+        }
+
+        let mut processed = process_message(&entry)?;
+
+        // Split assistant messages repeat the same usage on every entry
+        let message_id = entry.message.as_ref().and_then(|m| m.id.clone());
+        if let (Some(_), Some(id)) = (processed.usage, message_id) {
+            if !self.usage_ids.insert(id) {
+                processed.usage = None;
+            }
+        }
+
+        // Store tool results from user messages
+        if entry_type == "user" {
+            for (tool_id, result) in &processed.tool_results {
+                self.pending_tool_results.insert(tool_id.clone(), result.clone());
+            }
+        }
+
+        // Only keep non-empty messages
+        if !processed.text_content.trim().is_empty()
+            || !processed.tool_use_blocks.is_empty()
+            || !processed.thinking_blocks.is_empty()
+        {
+            Some(processed)
+        } else {
+            None
+        }
+    }
+
+    /// Merge tool results into the tool use messages they belong to
+    pub fn merge_tool_results(&self, messages: &mut [ProcessedMessage]) {
+        for msg in messages {
+            for tool_use in &msg.tool_use_blocks {
+                if let Some(result) = self.pending_tool_results.get(&tool_use.id) {
+                    msg.tool_results.insert(tool_use.id.clone(), result.clone());
+                }
+            }
+        }
+    }
+}
+
+/// Parse every line of a file, returning its displayable messages and the parser state
+fn parse_lines(file_path: &Path) -> Option<(Vec<ProcessedMessage>, SessionParser)> {
+    let file = fs::File::open(file_path).ok()?;
+    let reader = BufReader::new(file);
+    let mut parser = SessionParser::default();
+
+    let mut messages: Vec<ProcessedMessage> = Vec::new();
+
+    for line in reader.lines() {
+        let line = match line {
+            Ok(l) => l,
+            Err(_) => continue,
+        };
+        if let Some(message) = parser.ingest_line(&line) {
+            messages.push(message);
+        }
+    }
+    parser.merge_tool_results(&mut messages);
+
+    Some((messages, parser))
+}
+
+/// Parse a full session file
+pub(crate) fn parse_session_file(file_path: &Path) -> Option<(Session, HashMap<String, String>)> {
+    let session_id = file_path.file_stem()?.to_str()?.to_string();
+    let mut project = String::new();
+    let mut project_encoded = String::new();
+
+    // Extract project from path
+    if let Some(parent) = file_path.parent() {
+        if let Some(name) = parent.file_name() {
+            project_encoded = name.to_string_lossy().to_string();
+            project = decode_project_path(&project_encoded);
+        }
+    }
+
+    let (messages, parser) = parse_lines(file_path)?;
+    if messages.is_empty() {
+        return None;
+    }
+
+    Some((
+        Session {
+            id: session_id,
+            project,
+            project_encoded,
+            source: source_for_path(file_path),
+            git_branch: parser.git_branch,
+            cwd: parser.cwd,
+            version: parser.version,
+            start_time: parser.start_time,
+            end_time: parser.end_time,
+            messages,
+            file_path: file_path.to_string_lossy().to_string(),
+            subagents: HashMap::new(),
+        },
+        parser.agent_links,
+    ))
+}
+
+/// Parse a subagent file
+pub fn parse_subagent_file(file_path: &Path) -> Vec<ProcessedMessage> {
+    parse_lines(file_path)
+        .map(|(messages, _)| messages)
+        .unwrap_or_default()
+}
+
+/// Messages appended to a session file since the previous read
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TailUpdate {
+    /// True when the file shrank or was replaced and was re-read from the start;
+    /// `messages` then holds the whole session
+    pub reset: bool,
+    /// New messages, with tool results already merged in
+    pub messages: Vec<ProcessedMessage>,
+    /// Results that arrived for tool uses returned by earlier updates
+    #[serde(rename = "toolResults")]
+    pub tool_results: HashMap<String, ToolResultBlock>,
+    /// Agent links discovered in this update
+    #[serde(rename = "agentLinks")]
+    pub agent_links: HashMap<String, String>,
+}
+
+/// Bytes before the read offset kept to notice a file rewritten in place
+const TAIL_FINGERPRINT_LEN: usize = 256;
+
+/// Incremental reader that parses only the bytes appended to a session file
+#[derive(Debug)]
+pub struct SessionTail {
+    file_path: PathBuf,
+    offset: u64,
+    /// The last bytes read, ending at `offset`
+    fingerprint: Vec<u8>,
+    parser: SessionParser,
+    /// Tool uses already returned to the caller that are still waiting for a result
+    unresolved_tool_uses: HashSet<String>,
+}
+
+impl SessionTail {
+    /// A reader starting at the beginning of the file; nothing is read until [`Self::read_new`]
+    pub fn new(file_path: &Path) -> Self {
+        Self {
+            file_path: file_path.to_path_buf(),
+            offset: 0,
+            fingerprint: Vec::new(),
+            parser: SessionParser::default(),
+            unresolved_tool_uses: HashSet::new(),
+        }
+    }
+
+    /// The file being read
+    pub fn file_path(&self) -> &Path {
+        &self.file_path
+    }
+
+    /// Subagent id -> parent tool use id, for everything read so far
+    pub fn agent_links(&self) -> &HashMap<String, String> {
+        &self.parser.agent_links
+    }
+
+    /// Whether the content already read is no longer at the start of the
+    /// file, i.e. it was truncated or rewritten rather than appended to
+    fn replaced(&self, file: &mut fs::File) -> std::io::Result<bool> {
+        if file.metadata()?.len() < self.offset {
+            return Ok(true);
+        }
+        let mut current = vec![0; self.fingerprint.len()];
+        file.seek(SeekFrom::Start(self.offset - current.len() as u64))?;
+        file.read_exact(&mut current)?;
+        Ok(current != self.fingerprint)
+    }
+
+    /// Parse complete lines appended since the last read; a trailing partial
+    /// line is left for the next call
+    pub fn read_new(&mut self) -> std::io::Result<TailUpdate> {
+        let mut update = TailUpdate::default();
+
+        let mut file = fs::File::open(&self.file_path)?;
+        if self.replaced(&mut file)? {
+            self.offset = 0;
+            self.fingerprint.clear();
+            self.parser = SessionParser::default();
+            self.unresolved_tool_uses.clear();
+            update.reset = true;
+        }
+
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut buf = Vec::new();
+        file.read_to_end(&mut buf)?;
+        let complete = match buf.iter().rposition(|&b| b == b'\n') {
+            Some(pos) => pos + 1,
+            None => return Ok(update),
+        };
+        self.offset += complete as u64;
+        self.fingerprint
+            .extend_from_slice(&buf[complete.saturating_sub(TAIL_FINGERPRINT_LEN)..complete]);
+        let excess = self.fingerprint.len().saturating_sub(TAIL_FINGERPRINT_LEN);
+        self.fingerprint.drain(..excess);
+
+        let links_before: HashSet<String> = self.parser.agent_links.keys().cloned().collect();
+        for line in String::from_utf8_lossy(&buf[..complete]).lines() {
+            if let Some(message) = self.parser.ingest_line(line) {
+                update.messages.push(message);
+            }
+        }
+        self.parser.merge_tool_results(&mut update.messages);
+
+        // Results for tool uses handed out in earlier updates
+        self.unresolved_tool_uses.retain(|id| match self.parser.pending_tool_results.get(id) {
+            Some(result) => {
+                update.tool_results.insert(id.clone(), result.clone());
+                false
+            }
+            None => true,
+        });
+        for msg in &update.messages {
+            for tool_use in &msg.tool_use_blocks {
+                if !msg.tool_results.contains_key(&tool_use.id) {
+                    self.unresolved_tool_uses.insert(tool_use.id.clone());
+                }
+            }
+        }
+        // Resolved results are never needed again
+        let unresolved = &self.unresolved_tool_uses;
+        self.parser.pending_tool_results.retain(|id, _| unresolved.contains(id));
+
+        update.agent_links = self
+            .parser
+            .agent_links
+            .iter()
+            .filter(|(id, _)| !links_before.contains(*id))
+            .map(|(id, parent)| (id.clone(), parent.clone()))
+            .collect();
+
+        Ok(update)
+    }
+}
+
+/// Find subagent files for a session
+fn find_subagent_files(project_dir: &Path, session_id: &str) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+
+    // New location: {project}/{sessionId}/subagents/
+    let subagents_dir = project_dir.join(session_id).join("subagents");
+    if subagents_dir.exists() {
+        if let Ok(entries) = fs::read_dir(&subagents_dir) {
+            for entry in entries.flatten() {
+                let name = entry.file_name().to_string_lossy().to_string();
+                if name.starts_with("agent-") && name.ends_with(".jsonl") {
+                    files.push(entry.path());
+                }
+            }
+        }
+    }
+
+    // Old location: {project}/agent-*.jsonl
+    if let Ok(entries) = fs::read_dir(project_dir) {
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.starts_with("agent-") && name.ends_with(".jsonl") {
+                files.push(entry.path());
+            }
+        }
+    }
+
+    files
+}
+
+/// Get all sessions grouped by project, served from the persistent index
+/// where session files are unchanged
+pub fn get_all_sessions() -> Vec<ProjectGroup> {
+    let _guard = session_index::lock();
+    let mut index = SessionIndex::load();
+    let mut live_paths: HashSet<String> = HashSet::new();
+
+    let projects = list_projects();
+    let mut groups: Vec<ProjectGroup> = Vec::new();
+
+    for ProjectDir {
+        source,
+        project_encoded,
+        path,
+    } in projects
+    {
+        let session_files = list_session_files(&path);
+        let mut sessions: Vec<SessionSummary> = Vec::new();
+
+        for file_path in session_files {
+            live_paths.insert(file_path.to_string_lossy().to_string());
+            if let Some(mut summary) = index.summary(&file_path) {
+                // Labels can be renamed without the files changing
+                summary.source = source.clone();
+                sessions.push(summary);
+            }
+        }
+
+        if sessions.is_empty() {
+            continue;
+        }
+
+        // Sort by start time (newest first)
+        sessions.sort_by_key(|s| std::cmp::Reverse(s.start_time));
+
+        let mut usage = TokenUsage::default();
+        for session in &sessions {
+            usage += session.usage;
+        }
+
+        groups.push(ProjectGroup {
+            project: decode_project_path(&project_encoded),
+            project_encoded,
+            source,
+            sessions,
+            usage,
+            cost_usd: None,
+        });
+    }
+
+    // Sort groups by most recent session
+    groups.sort_by(|a, b| {
+        let a_time = a.sessions.first().and_then(|s| s.start_time).unwrap_or(0);
+        let b_time = b.sessions.first().and_then(|s| s.start_time).unwrap_or(0);
+        b_time.cmp(&a_time)
+    });
+
+    index.retain_paths(&live_paths);
+    if index.is_dirty() {
+        if let Err(e) = index.save() {
+            eprintln!("Failed to save session index: {}", e);
+        }
+    }
+
+    groups
+}
+
+/// Discard the session index and rebuild it by re-scanning every session file
+pub fn rebuild_index() -> Vec<ProjectGroup> {
+    if let Err(e) = session_index::clear() {
+        eprintln!("Failed to clear session index: {}", e);
+    }
+    get_all_sessions()
+}
+
+/// Get a single session by ID and project, from the given data root or the
+/// first one that has it
+pub fn get_session(
+    session_id: &str,
+    project_encoded: &str,
+    source: Option<&str>,
+) -> Option<Session> {
+    let file_path = session_file_path(session_id, project_encoded, source)?;
+    let project_dir = file_path.parent()?;
+
+    let (mut session, agent_links) = parse_session_file(&file_path)?;
+
+    // Find and load subagent files
+    let subagent_files = find_subagent_files(project_dir, session_id);
+
+    for sub_path in subagent_files {
+        let agent_id = sub_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .map(|s| s.strip_prefix("agent-").unwrap_or(s).to_string())
+            .unwrap_or_default();
+
+        if let Some(parent_tool_use_id) = agent_links.get(&agent_id) {
+            let messages = parse_subagent_file(&sub_path);
+            if !messages.is_empty() {
+                let count = messages.len();
+                session.subagents.insert(
+                    agent_id.clone(),
+                    SubagentSession {
+                        agent_id: agent_id.clone(),
+                        parent_tool_use_id: parent_tool_use_id.clone(),
+                        messages,
+                        message_count: count,
+                    },
+                );
+            }
+        }
+    }
+
+    // Annotate Task tool uses with agentId
+    for msg in &mut session.messages {
+        for tool_use in &mut msg.tool_use_blocks {
+            if tool_use.name == "Task" {
+                for (aid, sub) in &session.subagents {
+                    if sub.parent_tool_use_id == tool_use.id {
+                        tool_use.agent_id = Some(aid.clone());
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    Some(session)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn user_line(uuid: &str, text: &str) -> String {
+        format!(
+            r#"{{"type":"user","uuid":"{}","timestamp":"2026-01-01T00:00:00.000Z","message":{{"role":"user","content":"{}"}}}}"#,
+            uuid, text
+        ) + "\n"
+    }
+
+    #[test]
+    fn resolved_paths_match_through_symlinks_and_trailing_slashes() {
+        let dir = tempfile::tempdir().unwrap();
+        let real = dir.path().join("claude");
+        fs::create_dir(&real).unwrap();
+        fs::create_dir(dir.path().join("x")).unwrap();
+        assert_eq!(resolved(&dir.path().join("claude/")), resolved(&real));
+        assert_eq!(resolved(&dir.path().join("x/../claude")), resolved(&real));
+        #[cfg(unix)]
+        {
+            let link = dir.path().join("link");
+            std::os::unix::fs::symlink(&real, &link).unwrap();
+            assert_eq!(resolved(&link), resolved(&real));
+        }
+    }
+
+    #[test]
+    fn tail_reads_only_appended_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("s.jsonl");
+        fs::write(&path, user_line("u1", "first")).unwrap();
+
+        let mut tail = SessionTail::new(&path);
+        assert_eq!(tail.read_new().unwrap().messages.len(), 1);
+
+        let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(user_line("u2", "second").as_bytes()).unwrap();
+        // A partial line waits for the next read
+        file.write_all(b"{\"type\":").unwrap();
+        let update = tail.read_new().unwrap();
+        assert!(!update.reset);
+        assert_eq!(update.messages.len(), 1);
+        assert_eq!(update.messages[0].uuid, "u2");
+    }
+
+    #[test]
+    fn tail_rereads_a_file_rewritten_in_place() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("s.jsonl");
+        fs::write(&path, user_line("u1", "first")).unwrap();
+        let mut tail = SessionTail::new(&path);
+        tail.read_new().unwrap();
+
+        // Same length, different content
+        fs::write(&path, user_line("u9", "other")).unwrap();
+        let update = tail.read_new().unwrap();
+        assert!(update.reset);
+        assert_eq!(update.messages[0].uuid, "u9");
+
+        // Longer content that doesn't start with what was read
+        fs::write(&path, user_line("u7", "rewritten") + &user_line("u8", "longer")).unwrap();
+        let update = tail.read_new().unwrap();
+        assert!(update.reset);
+        assert_eq!(update.messages.len(), 2);
+    }
+
+    #[test]
+    fn tail_rereads_a_truncated_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("s.jsonl");
+        fs::write(&path, user_line("u1", "first") + &user_line("u2", "second")).unwrap();
+        let mut tail = SessionTail::new(&path);
+        tail.read_new().unwrap();
+
+        fs::write(&path, user_line("u3", "x")).unwrap();
+        let update = tail.read_new().unwrap();
+        assert!(update.reset);
+        assert_eq!(update.messages[0].uuid, "u3");
+    }
+}
@@ -0,0 +1,295 @@
+//! Raw transcript entries and the processed session types built from them.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// ============================================================================
+// Raw JSONL types (as stored in .jsonl files)
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct RawMessageContent {
+    // API message id; shared by the entries an assistant turn is split into
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub role: String,
+    pub content: serde_json::Value, // Can be string or array of ContentBlock
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub usage: Option<TokenUsage>,
+}
+
+/// Token counts from an assistant message's `usage` object
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenUsage {
+    /// Uncached input tokens
+    #[serde(default, rename = "inputTokens", alias = "input_tokens")]
+    pub input_tokens: u64,
+    /// Output tokens, including thinking
+    #[serde(default, rename = "outputTokens", alias = "output_tokens")]
+    pub output_tokens: u64,
+    /// Input tokens written to the prompt cache
+    #[serde(default, rename = "cacheCreationInputTokens", alias = "cache_creation_input_tokens")]
+    pub cache_creation_input_tokens: u64,
+    /// Input tokens read from the prompt cache
+    #[serde(default, rename = "cacheReadInputTokens", alias = "cache_read_input_tokens")]
+    pub cache_read_input_tokens: u64,
+}
+
+impl std::ops::AddAssign for TokenUsage {
+    fn add_assign(&mut self, other: Self) {
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.cache_creation_input_tokens += other.cache_creation_input_tokens;
+        self.cache_read_input_tokens += other.cache_read_input_tokens;
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct RawEntry {
+    #[serde(rename = "type")]
+    pub entry_type: Option<String>,
+    pub uuid: Option<String>,
+    #[serde(rename = "parentUuid")]
+    pub parent_uuid: Option<serde_json::Value>,
+    pub timestamp: Option<String>,
+    pub cwd: Option<String>,
+    pub version: Option<String>,
+    #[serde(rename = "gitBranch")]
+    pub git_branch: Option<String>,
+    #[serde(rename = "sessionId")]
+    pub session_id: Option<String>,
+    pub message: Option<RawMessageContent>,
+    // Progress message fields
+    pub data: Option<ProgressData>,
+    #[serde(rename = "parentToolUseID")]
+    pub parent_tool_use_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct ProgressData {
+    #[serde(rename = "agentId")]
+    pub agent_id: Option<String>,
+}
+
+// ============================================================================
+// Processed types for frontend
+// ============================================================================
+
+/// A thinking block from an assistant message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThinkingBlock {
+    /// Always `thinking`
+    #[serde(rename = "type")]
+    pub block_type: String,
+    /// The thinking text
+    pub thinking: String,
+    /// Signature the API attaches to verify the block
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+}
+
+/// A tool call made by an assistant message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolUseBlock {
+    /// Always `tool_use`
+    #[serde(rename = "type")]
+    pub block_type: String,
+    /// Tool use id, matched by the result's `tool_use_id`
+    pub id: String,
+    /// Tool name, e.g. `Bash` or `Task`
+    pub name: String,
+    /// Arguments the tool was called with
+    pub input: serde_json::Value,
+    /// Subagent started by this call, for `Task` calls
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "agentId")]
+    pub agent_id: Option<String>,
+}
+
+/// The result of a tool call, as returned in a user message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolResultBlock {
+    /// Always `tool_result`
+    #[serde(rename = "type")]
+    pub block_type: String,
+    /// Id of the [`ToolUseBlock`] this answers
+    pub tool_use_id: String,
+    /// Result content; a string or an array of content blocks
+    pub content: serde_json::Value,
+    /// Whether the tool reported an error
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_error: Option<bool>,
+}
+
+/// A user or assistant message with its content split by kind
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessedMessage {
+    /// Entry uuid
+    pub uuid: String,
+    /// Uuid of the entry this one follows
+    #[serde(rename = "parentUuid")]
+    pub parent_uuid: Option<String>,
+    /// Timestamp as written in the transcript
+    pub timestamp: String,
+    /// `user` or `assistant`
+    pub role: String,
+    /// Text blocks joined together
+    #[serde(rename = "textContent")]
+    pub text_content: String,
+    /// Thinking blocks, in order
+    #[serde(rename = "thinkingBlocks")]
+    pub thinking_blocks: Vec<ThinkingBlock>,
+    /// Tool calls, in order
+    #[serde(rename = "toolUseBlocks")]
+    pub tool_use_blocks: Vec<ToolUseBlock>,
+    /// Results of this message's tool calls, keyed by tool use id
+    #[serde(rename = "toolResults")]
+    pub tool_results: HashMap<String, ToolResultBlock>,
+    /// Model that wrote an assistant message
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// API message id; shared by the entries an assistant reply is split into
+    #[serde(default, rename = "messageId", skip_serializing_if = "Option::is_none")]
+    pub message_id: Option<String>,
+    /// Token usage; only set on the first entry of a split assistant message,
+    /// so sums don't double count
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<TokenUsage>,
+}
+
+/// The transcript of a subagent started by a `Task` tool call
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubagentSession {
+    /// Subagent id
+    #[serde(rename = "agentId")]
+    pub agent_id: String,
+    /// Id of the `Task` tool call that started it
+    #[serde(rename = "parentToolUseId")]
+    pub parent_tool_use_id: String,
+    /// The subagent's messages
+    pub messages: Vec<ProcessedMessage>,
+    /// Number of messages
+    #[serde(rename = "messageCount")]
+    pub message_count: usize,
+}
+
+/// What the session list shows for a session, without its messages
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSummary {
+    /// Session id, the transcript's file stem
+    pub id: String,
+    /// Decoded project path
+    pub project: String,
+    /// Project directory name as stored on disk
+    #[serde(rename = "projectEncoded")]
+    pub project_encoded: String,
+    /// Label of the data root the session was read from
+    #[serde(default)]
+    pub source: String,
+    /// Start of the first user message
+    #[serde(rename = "firstMessage")]
+    pub first_message: String,
+    /// Number of displayable messages
+    #[serde(rename = "messageCount")]
+    pub message_count: usize,
+    /// First timestamp, ms since epoch
+    #[serde(rename = "startTime")]
+    pub start_time: Option<i64>,
+    /// Last timestamp, ms since epoch
+    #[serde(rename = "endTime")]
+    pub end_time: Option<i64>,
+    /// Git branch at the start of the session
+    #[serde(rename = "gitBranch")]
+    pub git_branch: Option<String>,
+    /// First model used
+    pub model: Option<String>,
+    /// Path of the transcript file
+    #[serde(rename = "filePath")]
+    pub file_path: String,
+    /// Token usage over the whole session
+    #[serde(default)]
+    pub usage: TokenUsage,
+    /// Token usage per model
+    #[serde(default, rename = "modelUsage")]
+    pub model_usage: HashMap<String, TokenUsage>,
+    /// The same usage split by model and time, for reports over periods
+    #[serde(default, rename = "usageSlices")]
+    pub usage_slices: Vec<UsageSlice>,
+    /// Estimated cost; filled from the price table when listed, not
+    /// meaningful in the index
+    #[serde(default, rename = "costUsd")]
+    pub cost_usd: Option<f64>,
+}
+
+/// Token usage of one model within a [`USAGE_SLICE_MS`] window of a session
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsageSlice {
+    /// Start of the window, ms since epoch
+    pub start: i64,
+    /// Model the usage belongs to
+    pub model: String,
+    /// Tokens used in the window
+    pub usage: TokenUsage,
+}
+
+/// Width of a [`UsageSlice`]; 15 minutes, so slices fall within one local
+/// day in every time zone
+pub const USAGE_SLICE_MS: i64 = 15 * 60 * 1000;
+
+/// A fully parsed session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    /// Session id, the transcript's file stem
+    pub id: String,
+    /// Decoded project path
+    pub project: String,
+    /// Project directory name as stored on disk
+    #[serde(rename = "projectEncoded")]
+    pub project_encoded: String,
+    /// Label of the data root the session was read from
+    #[serde(default)]
+    pub source: String,
+    /// Git branch at the start of the session
+    #[serde(rename = "gitBranch")]
+    pub git_branch: Option<String>,
+    /// Working directory of the session
+    pub cwd: String,
+    /// Claude Code version that wrote the transcript
+    pub version: String,
+    /// First timestamp, ms since epoch
+    #[serde(rename = "startTime")]
+    pub start_time: Option<i64>,
+    /// Last timestamp, ms since epoch
+    #[serde(rename = "endTime")]
+    pub end_time: Option<i64>,
+    /// Displayable messages, in order
+    pub messages: Vec<ProcessedMessage>,
+    /// Path of the transcript file
+    #[serde(rename = "filePath")]
+    pub file_path: String,
+    /// Subagent transcripts, keyed by agent id
+    pub subagents: HashMap<String, SubagentSession>,
+}
+
+/// The sessions of one project within one data root
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectGroup {
+    /// Decoded project path
+    pub project: String,
+    /// Project directory name as stored on disk
+    #[serde(rename = "projectEncoded")]
+    pub project_encoded: String,
+    /// Label of the data root; the same project may appear under several
+    #[serde(default)]
+    pub source: String,
+    /// Session summaries, newest first
+    pub sessions: Vec<SessionSummary>,
+    /// Token usage summed over the sessions
+    #[serde(default)]
+    pub usage: TokenUsage,
+    /// Estimated cost summed over the sessions
+    #[serde(default, rename = "costUsd")]
+    pub cost_usd: Option<f64>,
+}
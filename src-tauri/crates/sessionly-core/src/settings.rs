@@ -0,0 +1,37 @@
+//! The parts of Sessionly's settings file that reading sessions depends on.
+//!
+//! The app owns the file and writes the rest of it; this only reads, so the
+//! CLI and MCP server follow the app's data roots and redaction rules.
+
+use crate::redaction::RedactionConfig;
+use crate::session_store::DataRoot;
+use serde::Deserialize;
+use std::fs;
+use std::path::PathBuf;
+
+/// Settings shared by the app, the CLI and the MCP server
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SharedSettings {
+    /// Redaction applied to exports
+    #[serde(default)]
+    pub redaction: RedactionConfig,
+    /// Session directories scanned besides the Claude config directory
+    #[serde(default, rename = "dataRoots")]
+    pub data_roots: Vec<DataRoot>,
+}
+
+/// Path of Sessionly's settings file
+pub fn settings_path() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_default()
+        .join("sessionly")
+        .join("settings.json")
+}
+
+/// Load the shared settings, falling back to defaults if missing or unreadable
+pub fn load() -> SharedSettings {
+    fs::read_to_string(settings_path())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
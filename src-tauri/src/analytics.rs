@@ -0,0 +1,320 @@
+use crate::session_store;
+use crate::session_types::{ProcessedMessage, ProjectGroup, Session, SessionSummary};
+use chrono::{Local, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+const NO_BRANCH: &str = "(no branch)";
+
+/// Time window over session start times, in ms since epoch; open-ended if unset
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+pub struct StatsWindow {
+    pub since: Option<i64>,
+    pub until: Option<i64>,
+}
+
+impl StatsWindow {
+    fn contains(&self, summary: &SessionSummary) -> bool {
+        let Some(start) = summary.start_time else {
+            return self.since.is_none() && self.until.is_none();
+        };
+        self.since.is_none_or(|since| start >= since) && self.until.is_none_or(|until| start <= until)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ToolStats {
+    pub calls: usize,
+    pub errors: usize,
+    #[serde(rename = "errorRate")]
+    pub error_rate: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsageStats {
+    #[serde(rename = "sessionCount")]
+    pub session_count: usize,
+    #[serde(rename = "messageCount")]
+    pub message_count: usize,
+    #[serde(rename = "avgMessagesPerSession")]
+    pub avg_messages_per_session: f64,
+    #[serde(rename = "avgDurationMs")]
+    pub avg_duration_ms: Option<f64>,
+    /// Local date -> sessions started that day
+    #[serde(rename = "sessionsPerDay")]
+    pub sessions_per_day: BTreeMap<String, usize>,
+    #[serde(rename = "toolCalls")]
+    pub tool_calls: BTreeMap<String, ToolStats>,
+    /// Model -> assistant replies; a reply split across entries counts once
+    pub models: BTreeMap<String, usize>,
+    #[serde(skip)]
+    duration_total_ms: i64,
+    #[serde(skip)]
+    duration_count: usize,
+}
+
+impl UsageStats {
+    /// Count a message; `replies` holds the API message ids already counted
+    fn add_message<'a>(&mut self, message: &'a ProcessedMessage, replies: &mut HashSet<&'a str>) {
+        let new_reply = message.message_id.as_deref().is_none_or(|id| replies.insert(id));
+        if message.role == "assistant" && new_reply {
+            if let Some(model) = &message.model {
+                *self.models.entry(model.clone()).or_default() += 1;
+            }
+        }
+        for tool_use in &message.tool_use_blocks {
+            let stats = self.tool_calls.entry(tool_use.name.clone()).or_default();
+            stats.calls += 1;
+            let failed = message
+                .tool_results
+                .get(&tool_use.id)
+                .is_some_and(|r| r.is_error == Some(true));
+            if failed {
+                stats.errors += 1;
+            }
+        }
+    }
+
+    fn add_session(&mut self, summary: &SessionSummary, session: &Session) {
+        self.session_count += 1;
+        self.message_count += summary.message_count;
+
+        if let Some(start) = summary.start_time {
+            if let Some(day) = Local.timestamp_millis_opt(start).single() {
+                *self.sessions_per_day.entry(day.format("%Y-%m-%d").to_string()).or_default() += 1;
+            }
+            if let Some(end) = summary.end_time {
+                self.duration_total_ms += end - start;
+                self.duration_count += 1;
+            }
+        }
+
+        let subagent_messages = session.subagents.values().flat_map(|s| &s.messages);
+        let mut replies = HashSet::new();
+        for message in session.messages.iter().chain(subagent_messages) {
+            self.add_message(message, &mut replies);
+        }
+    }
+
+    fn finish(&mut self) {
+        if self.session_count > 0 {
+            self.avg_messages_per_session = self.message_count as f64 / self.session_count as f64;
+        }
+        if self.duration_count > 0 {
+            self.avg_duration_ms = Some(self.duration_total_ms as f64 / self.duration_count as f64);
+        }
+        for stats in self.tool_calls.values_mut() {
+            stats.error_rate = stats.errors as f64 / stats.calls as f64;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Statistics {
+    pub overall: UsageStats,
+    /// Keyed by `session_store::project_key`, so the same project in two data
+    /// roots stays apart
+    #[serde(rename = "byProject")]
+    pub by_project: BTreeMap<String, UsageStats>,
+    #[serde(rename = "byBranch")]
+    pub by_branch: BTreeMap<String, UsageStats>,
+}
+
+impl Statistics {
+    fn add_session(&mut self, group: &ProjectGroup, summary: &SessionSummary, session: &Session) {
+        let branch = summary.git_branch.clone().unwrap_or_else(|| NO_BRANCH.to_string());
+        self.overall.add_session(summary, session);
+        self.by_project
+            .entry(session_store::project_key(&group.source, &group.project_encoded))
+            .or_default()
+            .add_session(summary, session);
+        self.by_branch.entry(branch).or_default().add_session(summary, session);
+    }
+
+    fn finish(&mut self) {
+        self.overall.finish();
+        self.by_project.values_mut().for_each(UsageStats::finish);
+        self.by_branch.values_mut().for_each(UsageStats::finish);
+    }
+}
+
+/// Aggregate statistics over sessions that started within the window
+pub fn get_statistics(window: StatsWindow) -> Statistics {
+    let mut stats = Statistics::default();
+
+    for group in session_store::get_all_sessions() {
+        for summary in group.sessions.iter().filter(|s| window.contains(s)) {
+            let session =
+                session_store::get_session(&summary.id, &summary.project_encoded, Some(&summary.source));
+            let Some(session) = session else {
+                continue;
+            };
+            stats.add_session(&group, summary, &session);
+        }
+    }
+
+    stats.finish();
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session_types::{SubagentSession, ToolResultBlock, ToolUseBlock};
+    use std::collections::HashMap;
+
+    fn message(role: &str, message_id: Option<&str>, model: Option<&str>) -> ProcessedMessage {
+        ProcessedMessage {
+            uuid: String::new(),
+            parent_uuid: None,
+            timestamp: String::new(),
+            role: role.to_string(),
+            text_content: String::new(),
+            thinking_blocks: Vec::new(),
+            tool_use_blocks: Vec::new(),
+            tool_results: HashMap::new(),
+            model: model.map(String::from),
+            message_id: message_id.map(String::from),
+            usage: None,
+        }
+    }
+
+    /// An assistant message calling `tool`, with its result if `failed` is set
+    fn tool_call(id: &str, tool: &str, failed: Option<bool>) -> ProcessedMessage {
+        let mut message = message("assistant", Some(id), Some("claude-test"));
+        message.tool_use_blocks.push(ToolUseBlock {
+            block_type: "tool_use".to_string(),
+            id: id.to_string(),
+            name: tool.to_string(),
+            input: serde_json::Value::Null,
+            agent_id: None,
+        });
+        if let Some(failed) = failed {
+            message.tool_results.insert(
+                id.to_string(),
+                ToolResultBlock {
+                    block_type: "tool_result".to_string(),
+                    tool_use_id: id.to_string(),
+                    content: serde_json::Value::Null,
+                    is_error: Some(failed),
+                },
+            );
+        }
+        message
+    }
+
+    fn summary(id: &str, start: Option<i64>, end: Option<i64>, branch: Option<&str>) -> SessionSummary {
+        SessionSummary {
+            id: id.to_string(),
+            project: "/work/app".to_string(),
+            project_encoded: "-work-app".to_string(),
+            source: String::new(),
+            first_message: String::new(),
+            message_count: 4,
+            start_time: start,
+            end_time: end,
+            git_branch: branch.map(String::from),
+            model: None,
+            file_path: String::new(),
+            usage: Default::default(),
+            model_usage: HashMap::new(),
+            usage_slices: Vec::new(),
+            cost_usd: None,
+        }
+    }
+
+    fn session(messages: Vec<ProcessedMessage>, subagent: Vec<ProcessedMessage>) -> Session {
+        Session {
+            id: String::new(),
+            project: String::new(),
+            project_encoded: String::new(),
+            source: String::new(),
+            git_branch: None,
+            cwd: String::new(),
+            version: String::new(),
+            start_time: None,
+            end_time: None,
+            messages,
+            file_path: String::new(),
+            subagents: [(
+                "a1".to_string(),
+                SubagentSession {
+                    agent_id: "a1".to_string(),
+                    parent_tool_use_id: "t0".to_string(),
+                    message_count: subagent.len(),
+                    messages: subagent,
+                },
+            )]
+            .into_iter()
+            .collect(),
+        }
+    }
+
+    fn group(source: &str) -> ProjectGroup {
+        ProjectGroup {
+            project: "/work/app".to_string(),
+            project_encoded: "-work-app".to_string(),
+            source: source.to_string(),
+            sessions: Vec::new(),
+            usage: Default::default(),
+            cost_usd: None,
+        }
+    }
+
+    #[test]
+    fn counts_replies_once_and_tool_errors() {
+        let mut stats = UsageStats::default();
+        let session = session(
+            vec![
+                message("user", None, None),
+                // One reply split into a text entry and a tool call entry
+                message("assistant", Some("m1"), Some("claude-test")),
+                tool_call("m1", "Bash", Some(true)),
+                tool_call("m2", "Bash", Some(false)),
+            ],
+            vec![tool_call("m3", "Read", None), tool_call("m4", "Bash", None)],
+        );
+        stats.add_session(&summary("s1", None, None, None), &session);
+        stats.finish();
+
+        assert_eq!(stats.models["claude-test"], 4);
+        assert_eq!((stats.tool_calls["Bash"].calls, stats.tool_calls["Bash"].errors), (3, 1));
+        assert!((stats.tool_calls["Bash"].error_rate - 1.0 / 3.0).abs() < 1e-9);
+        assert_eq!(stats.tool_calls["Read"].error_rate, 0.0);
+    }
+
+    #[test]
+    fn finish_averages_over_sessions_with_times() {
+        let mut stats = UsageStats::default();
+        stats.finish();
+        assert_eq!((stats.avg_messages_per_session, stats.avg_duration_ms), (0.0, None));
+
+        let day = Local.with_ymd_and_hms(2026, 3, 1, 12, 0, 0).unwrap().timestamp_millis();
+        let empty = session(Vec::new(), Vec::new());
+        stats.add_session(&summary("s1", Some(day), Some(day + 1_000), None), &empty);
+        stats.add_session(&summary("s2", Some(day), Some(day + 3_000), None), &empty);
+        stats.add_session(&summary("s3", None, None, None), &empty);
+        stats.finish();
+
+        assert_eq!(stats.session_count, 3);
+        assert_eq!(stats.avg_messages_per_session, 4.0);
+        assert_eq!(stats.avg_duration_ms, Some(2_000.0));
+        assert_eq!(stats.sessions_per_day, BTreeMap::from([("2026-03-01".to_string(), 2)]));
+    }
+
+    #[test]
+    fn projects_are_kept_apart_by_data_root() {
+        let mut stats = Statistics::default();
+        let empty = session(Vec::new(), Vec::new());
+        stats.add_session(&group("default"), &summary("s1", None, None, Some("main")), &empty);
+        stats.add_session(&group("work"), &summary("s2", None, None, None), &empty);
+        stats.add_session(&group("work"), &summary("s3", None, None, Some("main")), &empty);
+        stats.finish();
+
+        assert_eq!(stats.overall.session_count, 3);
+        let projects: Vec<_> = stats.by_project.iter().map(|(k, s)| (k.as_str(), s.session_count)).collect();
+        assert_eq!(projects, [("-work-app", 1), ("work:-work-app", 2)]);
+        let branches: Vec<_> = stats.by_branch.iter().map(|(k, s)| (k.as_str(), s.session_count)).collect();
+        assert_eq!(branches, [(NO_BRANCH, 1), ("main", 2)]);
+    }
+}
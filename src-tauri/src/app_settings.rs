@@ -0,0 +1,74 @@
+use crate::hooks::HookServerSettings;
+use crate::notification_rules::NotificationSettings;
+use crate::pricing::PriceTable;
+use crate::session_activity::ActivitySettings;
+use crate::tool_timing::ToolTimingSettings;
+use crate::redaction::RedactionConfig;
+use crate::session_store::DataRoot;
+use crate::webhooks::WebhookSettings;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// Serializes load-modify-save cycles so concurrent updates don't drop each other
+static UPDATE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Sessionly's own persisted settings (not Claude's settings.json)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AppSettings {
+    #[serde(default)]
+    pub pricing: PriceTable,
+    #[serde(default)]
+    pub redaction: RedactionConfig,
+    #[serde(default, rename = "hookServer")]
+    pub hook_server: HookServerSettings,
+    /// Shared secret hook requests must carry
+    #[serde(default, rename = "hookToken")]
+    pub hook_token: Option<String>,
+    #[serde(default)]
+    pub activity: ActivitySettings,
+    #[serde(default, rename = "toolTiming")]
+    pub tool_timing: ToolTimingSettings,
+    #[serde(default)]
+    pub notifications: NotificationSettings,
+    #[serde(default)]
+    pub webhooks: WebhookSettings,
+    /// Session directories scanned besides the Claude config directory
+    #[serde(default, rename = "dataRoots")]
+    pub data_roots: Vec<DataRoot>,
+}
+
+/// Get the settings file path, shared with `sessionly_core::settings`
+pub fn get_settings_path() -> PathBuf {
+    sessionly_core::settings::settings_path()
+}
+
+/// Load settings, falling back to defaults if missing or unreadable
+pub fn load() -> AppSettings {
+    fs::read_to_string(get_settings_path())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Write settings atomically, so a crash mid-write can't leave a truncated file
+pub fn save(settings: &AppSettings) -> Result<(), Box<dyn std::error::Error>> {
+    let path = get_settings_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, serde_json::to_string_pretty(settings)?)?;
+    fs::rename(&tmp_path, &path)?;
+    Ok(())
+}
+
+/// Load, modify and save settings in one step
+pub fn update<F: FnOnce(&mut AppSettings)>(f: F) -> Result<AppSettings, Box<dyn std::error::Error>> {
+    let _guard = UPDATE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut settings = load();
+    f(&mut settings);
+    save(&settings)?;
+    Ok(settings)
+}
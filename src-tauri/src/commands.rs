@@ -1,20 +1,172 @@
-use crate::session_store;
+use crate::analytics::{self, Statistics, StatsWindow};
+use crate::app_settings;
+use crate::hooks::HookServerSettings;
+use crate::notification_rules::NotificationSettings;
+use crate::pricing::{self, PriceTable, UsageBucket, UsagePeriod};
+use crate::redaction::{self, RedactionConfig, RedactionReport};
+use crate::session_activity::{ActivityEntry, ActivitySettings};
+use crate::session_monitor::TrackedSessionInfo;
+use crate::session_search::{self, SearchHit, SearchQuery};
+use crate::session_store::{self, DataRoot, TailUpdate};
 use crate::session_types::{ProjectGroup, Session};
+use crate::tool_timing::{LatencyStats, StuckCall, ToolTimingSettings};
+use crate::webhooks::{DeliveryRecord, WebhookEndpoint, WebhookSettings};
 use crate::AppState;
-use tauri::State;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use tauri::{AppHandle, State};
+
+/// List projects with session and project costs filled in from the price table
+fn priced_projects(mut groups: Vec<ProjectGroup>) -> Vec<ProjectGroup> {
+    pricing::apply_costs(&mut groups, &app_settings::load().pricing);
+    groups
+}
 
 #[tauri::command]
 pub async fn get_projects() -> Vec<ProjectGroup> {
-    tokio::task::spawn_blocking(|| session_store::get_all_sessions())
+    tokio::task::spawn_blocking(|| priced_projects(session_store::get_all_sessions()))
+        .await
+        .unwrap_or_default()
+}
+
+#[tauri::command]
+pub async fn rebuild_session_index() -> Vec<ProjectGroup> {
+    tokio::task::spawn_blocking(|| priced_projects(session_store::rebuild_index()))
         .await
         .unwrap_or_default()
 }
 
 #[tauri::command]
-pub async fn get_session(session_id: String, project_encoded: String) -> Option<Session> {
-    tokio::task::spawn_blocking(move || session_store::get_session(&session_id, &project_encoded))
+pub async fn get_usage_report(period: UsagePeriod) -> Vec<UsageBucket> {
+    tokio::task::spawn_blocking(move || {
+        let table = app_settings::load().pricing;
+        pricing::usage_report(&session_store::get_all_sessions(), period, &table)
+    })
+    .await
+    .unwrap_or_default()
+}
+
+#[tauri::command]
+pub async fn get_statistics(window: StatsWindow) -> Statistics {
+    tokio::task::spawn_blocking(move || analytics::get_statistics(window))
         .await
-        .unwrap_or(None)
+        .unwrap_or_default()
+}
+
+#[tauri::command]
+pub fn get_price_table() -> PriceTable {
+    app_settings::load().pricing
+}
+
+#[tauri::command]
+pub fn set_price_table(table: PriceTable) -> Result<(), String> {
+    app_settings::update(|settings| settings.pricing = table)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_data_roots() -> Vec<DataRoot> {
+    app_settings::load().data_roots
+}
+
+/// Save the extra data roots, rescan with them and watch them for changes
+#[tauri::command]
+pub fn set_data_roots(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    roots: Vec<DataRoot>,
+) -> Result<(), String> {
+    session_store::validate_data_roots(&roots)?;
+    let previous = app_settings::load().data_roots;
+    // Saved first, so a failure leaves the roots in use unchanged
+    app_settings::update(|settings| settings.data_roots = roots.clone()).map_err(|e| e.to_string())?;
+    session_store::set_data_roots(roots);
+
+    // Keep the running watcher until the new one is up, so a failure leaves
+    // live updates working with the old roots
+    let open_sessions = state.open_sessions.clone();
+    match crate::session_watcher::SessionWatcher::start(app, open_sessions) {
+        Ok(watcher) => {
+            *state.session_watcher.lock().unwrap() = Some(watcher);
+            Ok(())
+        }
+        Err(e) => {
+            session_store::set_data_roots(previous.clone());
+            if let Err(save_error) = app_settings::update(|settings| settings.data_roots = previous) {
+                eprintln!("Failed to restore saved data roots: {}", save_error);
+            }
+            Err(e)
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn get_session(
+    session_id: String,
+    project_encoded: String,
+    source: Option<String>,
+) -> Option<Session> {
+    tokio::task::spawn_blocking(move || {
+        session_store::get_session(&session_id, &project_encoded, source.as_deref())
+    })
+    .await
+    .unwrap_or(None)
+}
+
+#[tauri::command]
+pub async fn search_sessions(query: SearchQuery) -> Vec<SearchHit> {
+    tokio::task::spawn_blocking(move || session_search::search(&query))
+        .await
+        .unwrap_or_default()
+}
+
+#[tauri::command]
+pub async fn watch_session(
+    state: State<'_, AppState>,
+    session_id: String,
+    project_encoded: String,
+    source: Option<String>,
+    known_messages: usize,
+) -> Result<TailUpdate, String> {
+    let open_sessions = state.open_sessions.clone();
+    tokio::task::spawn_blocking(move || {
+        open_sessions.open(&session_id, &project_encoded, source.as_deref(), known_messages)
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Stop tailing a session and refresh its summary, which isn't kept up to
+/// date while it is tailed
+#[tauri::command]
+pub async fn unwatch_session(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    session_id: String,
+    source: String,
+) -> Result<(), String> {
+    let Some(path) = state.open_sessions.close(&source, &session_id) else {
+        return Ok(());
+    };
+    tokio::task::spawn_blocking(move || {
+        crate::session_watcher::emit_summary(&app, &path, &app_settings::load().pricing)
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn refresh_session(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    session_id: String,
+    source: String,
+) -> Result<(), String> {
+    let open_sessions = state.open_sessions.clone();
+    tokio::task::spawn_blocking(move || open_sessions.refresh(&app, &source, &session_id))
+        .await
+        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -30,31 +182,216 @@ pub fn get_native_theme(window: tauri::Window) -> String {
     }
 }
 
+/// A rendered session and what was redacted from it
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionExport {
+    pub content: String,
+    pub report: RedactionReport,
+}
+
+/// Load a session, apply the configured redaction and render it
+async fn export_session(
+    session_id: String,
+    project_encoded: String,
+    source: Option<String>,
+    render: fn(&Session) -> String,
+) -> Result<SessionExport, String> {
+    tokio::task::spawn_blocking(move || {
+        let mut session = session_store::get_session(&session_id, &project_encoded, source.as_deref())
+            .ok_or_else(|| "Session not found".to_string())?;
+        let report = redaction::redact_session(&mut session, &app_settings::load().redaction)?;
+        Ok(SessionExport {
+            content: render(&session),
+            report,
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
 #[tauri::command]
-pub async fn export_session_markdown(session_id: String, project_encoded: String) -> Result<String, String> {
+pub async fn export_session_markdown(
+    session_id: String,
+    project_encoded: String,
+    source: Option<String>,
+) -> Result<SessionExport, String> {
+    export_session(session_id, project_encoded, source, crate::markdown_export::session_to_markdown).await
+}
+
+#[tauri::command]
+pub async fn export_session_html(
+    session_id: String,
+    project_encoded: String,
+    source: Option<String>,
+) -> Result<SessionExport, String> {
+    export_session(session_id, project_encoded, source, crate::html_export::session_to_html).await
+}
+
+#[tauri::command]
+pub async fn export_session_json(
+    session_id: String,
+    project_encoded: String,
+    source: Option<String>,
+) -> Result<SessionExport, String> {
+    export_session(session_id, project_encoded, source, crate::json_export::session_to_json).await
+}
+
+#[tauri::command]
+pub async fn export_session_jsonl(
+    session_id: String,
+    project_encoded: String,
+    source: Option<String>,
+) -> Result<SessionExport, String> {
+    export_session(session_id, project_encoded, source, crate::json_export::session_to_jsonl).await
+}
+
+/// Report what an export of this session would redact with the current settings
+#[tauri::command]
+pub async fn preview_redaction(
+    session_id: String,
+    project_encoded: String,
+    source: Option<String>,
+) -> Result<RedactionReport, String> {
     tokio::task::spawn_blocking(move || {
-        let session = session_store::get_session(&session_id, &project_encoded)
+        let mut session = session_store::get_session(&session_id, &project_encoded, source.as_deref())
             .ok_or_else(|| "Session not found".to_string())?;
-        Ok(crate::markdown_export::session_to_markdown(&session))
+        redaction::redact_session(&mut session, &app_settings::load().redaction)
     })
     .await
     .map_err(|e| e.to_string())?
 }
 
+#[tauri::command]
+pub fn get_redaction_config() -> RedactionConfig {
+    app_settings::load().redaction
+}
+
+#[tauri::command]
+pub fn set_redaction_config(config: RedactionConfig) -> Result<(), String> {
+    // Reject invalid custom patterns before saving them
+    for custom in &config.custom_patterns {
+        regex::Regex::new(&custom.pattern).map_err(|e| format!("Invalid pattern '{}': {}", custom.name, e))?;
+    }
+    app_settings::update(|settings| settings.redaction = config)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_redaction_detectors() -> Vec<&'static str> {
+    redaction::builtin_detectors()
+}
+
+#[tauri::command]
+pub fn get_monitored_sessions(state: State<'_, AppState>) -> Vec<TrackedSessionInfo> {
+    state.session_monitor.sessions()
+}
+
+#[tauri::command]
+pub fn get_session_activity(session_id: String, state: State<'_, AppState>) -> Vec<ActivityEntry> {
+    state.session_monitor.activity(&session_id)
+}
+
+#[tauri::command]
+pub fn get_activity_settings() -> ActivitySettings {
+    app_settings::load().activity
+}
+
+#[tauri::command]
+pub fn set_activity_settings(settings: ActivitySettings, state: State<'_, AppState>) -> Result<(), String> {
+    app_settings::update(|s| s.activity = settings).map_err(|e| e.to_string())?;
+    state.session_monitor.configure_activity(settings);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_tool_latency(project: Option<String>, state: State<'_, AppState>) -> BTreeMap<String, LatencyStats> {
+    state.session_monitor.tool_latency(project.as_deref())
+}
+
+#[tauri::command]
+pub fn get_stuck_tool_calls(state: State<'_, AppState>) -> Vec<StuckCall> {
+    state.session_monitor.stuck_tool_calls()
+}
+
+#[tauri::command]
+pub fn get_tool_timing_settings() -> ToolTimingSettings {
+    app_settings::load().tool_timing
+}
+
+#[tauri::command]
+pub fn set_tool_timing_settings(settings: ToolTimingSettings, state: State<'_, AppState>) -> Result<(), String> {
+    app_settings::update(|s| s.tool_timing = settings).map_err(|e| e.to_string())?;
+    state.session_monitor.configure_tool_timing(settings);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_notification_settings() -> NotificationSettings {
+    app_settings::load().notifications
+}
+
+#[tauri::command]
+pub fn set_notification_settings(settings: NotificationSettings, state: State<'_, AppState>) -> Result<(), String> {
+    settings.validate()?;
+    app_settings::update(|s| s.notifications = settings.clone()).map_err(|e| e.to_string())?;
+    state.session_monitor.configure_notifications(settings);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_webhook_settings() -> WebhookSettings {
+    app_settings::load().webhooks
+}
+
+#[tauri::command]
+pub fn set_webhook_settings(settings: WebhookSettings, state: State<'_, AppState>) -> Result<(), String> {
+    settings.validate()?;
+    app_settings::update(|s| s.webhooks = settings.clone()).map_err(|e| e.to_string())?;
+    state.session_monitor.configure_webhooks(settings);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_webhook_deliveries(state: State<'_, AppState>) -> Vec<DeliveryRecord> {
+    state.session_monitor.webhook_deliveries()
+}
+
+/// Send a sample event to an endpoint, which need not be saved yet
+#[tauri::command]
+pub async fn test_webhook(endpoint: WebhookEndpoint, state: State<'_, AppState>) -> Result<DeliveryRecord, String> {
+    let monitor = state.session_monitor.clone();
+    tokio::task::spawn_blocking(move || monitor.test_webhook(&endpoint))
+        .await
+        .map_err(|e| e.to_string())
+}
+
 // Hooks commands
+
+/// Hook command for the running server, or for the configured port if it isn't running
+fn current_hook_command(state: &AppState) -> Result<String, String> {
+    if let Some(server) = state.hook_server.lock().unwrap().as_ref() {
+        return Ok(server.hook_command());
+    }
+    let settings = app_settings::load().hook_server;
+    let token = crate::hooks::load_or_create_token()?;
+    Ok(crate::hooks::hook_command(settings.bind_address, settings.port, &token))
+}
+
 #[tauri::command]
 pub fn hooks_get_status(state: State<'_, AppState>) -> crate::hooks::HookStatus {
-    let running = state
-        .hook_server
+    let server = state.hook_server.lock().unwrap();
+    let running = server.as_ref().map(|s| s.is_running()).unwrap_or(false);
+    let port = server
         .as_ref()
-        .map(|s| s.is_running())
-        .unwrap_or(false);
-    crate::hooks::get_status(running)
+        .map(|s| s.port())
+        .unwrap_or_else(|| app_settings::load().hook_server.port);
+    crate::hooks::get_status(running, port)
 }
 
 #[tauri::command]
-pub fn hooks_install() -> Result<(), String> {
-    crate::hooks::install_hooks().map_err(|e| e.to_string())
+pub fn hooks_install(state: State<'_, AppState>) -> Result<(), String> {
+    crate::hooks::install_hooks(&current_hook_command(&state)?).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -63,27 +400,69 @@ pub fn hooks_uninstall() -> Result<(), String> {
 }
 
 #[tauri::command]
-pub fn hooks_is_installed() -> bool {
-    crate::hooks::is_hooks_installed()
+pub fn get_hook_server_settings() -> HookServerSettings {
+    app_settings::load().hook_server
 }
 
+/// Restart the hook server with new settings, saving them and repointing
+/// installed hooks once it is listening. If it can't start, the previous
+/// server is brought back and nothing is saved.
 #[tauri::command]
-pub fn send_native_notification(title: String, body: String) -> Result<(), String> {
-    let escaped_body = body.replace('\\', "\\\\").replace('"', "\\\"");
-    let escaped_title = title.replace('\\', "\\\\").replace('"', "\\\"");
+pub fn set_hook_server_settings(
+    settings: HookServerSettings,
+    state: State<'_, AppState>,
+) -> Result<crate::hooks::HookStatus, String> {
+    let previous = app_settings::load().hook_server;
+    let token = crate::hooks::load_or_create_token()?;
 
-    #[cfg(target_os = "macos")]
-    {
-        std::process::Command::new("osascript")
-            .arg("-e")
-            .arg(format!(
-                "display notification \"{}\" with title \"{}\"",
-                escaped_body, escaped_title
-            ))
-            .output()
-            .map_err(|e| format!("osascript failed: {}", e))?;
+    let mut server = state.hook_server.lock().unwrap();
+    // Drop the old server first so its port is free to rebind
+    let was_running = server.take().is_some();
+    let new_server = match crate::hooks::HookServer::start(state.session_monitor.clone(), &settings, token.clone()) {
+        Ok(new_server) => new_server,
+        Err(e) => {
+            if was_running {
+                *server = crate::hooks::HookServer::start(state.session_monitor.clone(), &previous, token)
+                    .inspect_err(|e| eprintln!("Failed to restart hook server: {}", e))
+                    .ok();
+            }
+            return Err(e);
+        }
+    };
+    let status = crate::hooks::get_status(new_server.is_running(), new_server.port());
+    let command = new_server.hook_command();
+    *server = Some(new_server);
+    drop(server);
+
+    app_settings::update(|s| s.hook_server = settings).map_err(|e| e.to_string())?;
+    if crate::hooks::is_hooks_installed() {
+        crate::hooks::install_hooks(&command).map_err(|e| e.to_string())?;
     }
+    Ok(status)
+}
 
-    Ok(())
+/// Replace the hook token; the running server and installed hooks switch over immediately
+#[tauri::command]
+pub fn rotate_hook_token(state: State<'_, AppState>) -> Result<(), String> {
+    let token = crate::hooks::generate_token()?;
+    app_settings::update(|s| s.hook_token = Some(token.clone())).map_err(|e| e.to_string())?;
+    if let Some(server) = state.hook_server.lock().unwrap().as_ref() {
+        server.set_token(token);
+    }
+    crate::hooks::install_hooks(&current_hook_command(&state)?).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn hooks_is_installed() -> bool {
+    crate::hooks::is_hooks_installed()
+}
+
+/// Show a native notification, returning the backend that delivered it
+#[tauri::command]
+pub async fn send_native_notification(app: AppHandle, title: String, body: String) -> Result<String, String> {
+    tokio::task::spawn_blocking(move || crate::notifier::notify(&app, &title, &body))
+        .await
+        .map_err(|e| e.to_string())?
+        .map(String::from)
 }
 
@@ -1,19 +1,90 @@
 use crate::session_monitor::SessionMonitor;
 use serde::{Deserialize, Serialize};
 use std::fs;
+use std::net::{IpAddr, Ipv4Addr};
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 
-const HOOK_IDENTIFIER: &str = "localhost:19823/sessionly";
-const HOOK_COMMAND: &str = "curl -s -X POST http://localhost:19823/sessionly -d @- || true";
+pub const DEFAULT_PORT: u16 = 19823;
+const HOOK_PATH: &str = "/sessionly";
+const TOKEN_HEADER: &str = "X-Sessionly-Token";
+/// Ports after the configured one to try before letting the OS pick
+const PORT_FALLBACK_RANGE: u16 = 10;
 
-/// Check if a hook entry (flat or nested format) contains the Sessionly identifier.
+/// Persisted hook server settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HookServerSettings {
+    pub port: u16,
+    #[serde(rename = "bindAddress")]
+    pub bind_address: IpAddr,
+}
+
+impl Default for HookServerSettings {
+    fn default() -> Self {
+        Self {
+            port: DEFAULT_PORT,
+            bind_address: IpAddr::V4(Ipv4Addr::LOCALHOST),
+        }
+    }
+}
+
+/// Generate a random 256-bit token, hex encoded
+pub fn generate_token() -> Result<String, String> {
+    let mut bytes = [0u8; 32];
+    getrandom::fill(&mut bytes).map_err(|e| format!("Failed to generate hook token: {}", e))?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// The persisted hook token, generated on first use
+pub fn load_or_create_token() -> Result<String, String> {
+    if let Some(token) = crate::app_settings::load().hook_token {
+        return Ok(token);
+    }
+    let token = generate_token()?;
+    crate::app_settings::update(|s| s.hook_token = Some(token.clone())).map_err(|e| e.to_string())?;
+    Ok(token)
+}
+
+/// Compare tokens without short-circuiting on the first differing byte
+fn tokens_match(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// The curl command Claude Code runs for each hook event
+pub fn hook_command(bind_address: IpAddr, port: u16, token: &str) -> String {
+    let host = match bind_address {
+        addr if addr.is_loopback() || addr.is_unspecified() => "localhost".to_string(),
+        IpAddr::V4(addr) => addr.to_string(),
+        IpAddr::V6(addr) => format!("[{}]", addr),
+    };
+    format!(
+        "curl -s -X POST -H '{}: {}' http://{}:{}{} -d @- || true",
+        TOKEN_HEADER, token, host, port, HOOK_PATH
+    )
+}
+
+/// Whether a command posts to a Sessionly hook server, on any host or port
+fn is_sessionly_command(cmd: &str) -> bool {
+    cmd.split_whitespace().any(|word| {
+        word.trim_matches(|c| c == '"' || c == '\'')
+            .strip_prefix("http://")
+            .and_then(|rest| rest.split_once('/'))
+            .is_some_and(|(_, path)| HOOK_PATH.strip_prefix('/') == Some(path))
+    })
+}
+
+/// Check if a hook entry (flat or nested format) contains a Sessionly command.
 fn entry_contains_identifier(item: &serde_json::Value) -> bool {
     if let Some(obj) = item.as_object() {
         // Flat format: { "command": "curl ... localhost:19823/sessionly ..." }
         if let Some(cmd) = obj.get("command").and_then(|c| c.as_str()) {
-            if cmd.contains(HOOK_IDENTIFIER) {
+            if is_sessionly_command(cmd) {
                 return true;
             }
         }
@@ -21,7 +92,7 @@ fn entry_contains_identifier(item: &serde_json::Value) -> bool {
         if let Some(hooks_arr) = obj.get("hooks").and_then(|h| h.as_array()) {
             for hook in hooks_arr {
                 if let Some(cmd) = hook.get("command").and_then(|c| c.as_str()) {
-                    if cmd.contains(HOOK_IDENTIFIER) {
+                    if is_sessionly_command(cmd) {
                         return true;
                     }
                 }
@@ -31,6 +102,30 @@ fn entry_contains_identifier(item: &serde_json::Value) -> bool {
     false
 }
 
+/// Point every Sessionly command in a hook entry at `command`
+fn rewrite_entry_command(item: &mut serde_json::Value, command: &str) {
+    let Some(obj) = item.as_object_mut() else {
+        return;
+    };
+    let mut targets: Vec<&mut serde_json::Value> = vec![];
+    for (key, value) in obj.iter_mut() {
+        match (key.as_str(), value) {
+            // Flat format
+            ("command", cmd) => targets.push(cmd),
+            // Nested format
+            ("hooks", serde_json::Value::Array(hooks_arr)) => {
+                targets.extend(hooks_arr.iter_mut().filter_map(|hook| hook.get_mut("command")))
+            }
+            _ => {}
+        }
+    }
+    for cmd in targets {
+        if cmd.as_str().is_some_and(is_sessionly_command) {
+            *cmd = serde_json::json!(command);
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HookStatus {
     #[serde(rename = "serverRunning")]
@@ -40,17 +135,15 @@ pub struct HookStatus {
     pub hooks_installed: bool,
 }
 
+/// Claude's settings.json, under `$CLAUDE_CONFIG_DIR` when set
 fn get_settings_path() -> PathBuf {
-    dirs::home_dir()
-        .unwrap_or_default()
-        .join(".claude")
-        .join("settings.json")
+    crate::session_store::get_claude_dir().join("settings.json")
 }
 
-pub fn get_status(server_running: bool) -> HookStatus {
+pub fn get_status(server_running: bool, port: u16) -> HookStatus {
     HookStatus {
         server_running,
-        port: 19823,
+        port,
         hooks_installed: is_hooks_installed(),
     }
 }
@@ -60,13 +153,24 @@ pub fn is_hooks_installed() -> bool {
     if !settings_path.exists() {
         return false;
     }
-    match fs::read_to_string(&settings_path) {
-        Ok(content) => content.contains(HOOK_IDENTIFIER),
-        Err(_) => false,
-    }
+    let settings: serde_json::Value = match fs::read_to_string(&settings_path) {
+        Ok(content) => serde_json::from_str(&content).unwrap_or_default(),
+        Err(_) => return false,
+    };
+    settings
+        .get("hooks")
+        .and_then(|h| h.as_object())
+        .is_some_and(|hooks| {
+            hooks
+                .values()
+                .filter_map(|entries| entries.as_array())
+                .any(|arr| arr.iter().any(entry_contains_identifier))
+        })
 }
 
-pub fn install_hooks() -> Result<(), Box<dyn std::error::Error>> {
+/// Install Sessionly hooks pointing at `command`, rewriting any existing ones
+/// (e.g. after a port change). settings.json is only written if it changed.
+pub fn install_hooks(command: &str) -> Result<(), Box<dyn std::error::Error>> {
     let settings_path = get_settings_path();
 
     if let Some(parent) = settings_path.parent() {
@@ -79,6 +183,7 @@ pub fn install_hooks() -> Result<(), Box<dyn std::error::Error>> {
     } else {
         serde_json::json!({})
     };
+    let original = settings.clone();
 
     // New Claude Code hook format: { "matcher": "...", "hooks": [{ "type": "command", ... }] }
     // Events with matchers use "*" to match all tools
@@ -86,11 +191,15 @@ pub fn install_hooks() -> Result<(), Box<dyn std::error::Error>> {
         ("PreToolUse", Some("*")),
         ("PostToolUse", Some("*")),
         ("PostToolUseFailure", Some("*")),
+        ("PermissionRequest", Some("*")),
         ("Stop", None),
         ("Notification", Some("*")),
         ("SessionStart", None),
         ("SessionEnd", None),
         ("UserPromptSubmit", None),
+        ("SubagentStart", None),
+        ("SubagentStop", None),
+        ("PreCompact", None),
     ];
 
     let hooks = settings
@@ -112,10 +221,14 @@ pub fn install_hooks() -> Result<(), Box<dyn std::error::Error>> {
         // Check if Sessionly hook already exists (flat or nested format)
         let already_exists = arr.iter().any(entry_contains_identifier);
 
-        if !already_exists {
+        if already_exists {
+            for item in arr.iter_mut().filter(|item| entry_contains_identifier(item)) {
+                rewrite_entry_command(item, command);
+            }
+        } else {
             let hook_obj = serde_json::json!({
                 "type": "command",
-                "command": HOOK_COMMAND,
+                "command": command,
                 "async": true,
                 "timeout": 5
             });
@@ -129,6 +242,10 @@ pub fn install_hooks() -> Result<(), Box<dyn std::error::Error>> {
         }
     }
 
+    if settings == original && settings_path.exists() {
+        return Ok(());
+    }
+
     let content = serde_json::to_string_pretty(&settings)?;
     fs::write(&settings_path, content)?;
 
@@ -170,17 +287,44 @@ pub fn uninstall_hooks() -> Result<(), Box<dyn std::error::Error>> {
 
 pub struct HookServer {
     server_running: Arc<AtomicBool>,
+    bind_address: IpAddr,
+    port: u16,
+    token: Arc<RwLock<String>>,
+    thread: Option<std::thread::JoinHandle<()>>,
+}
+
+/// Bind the configured port, then the next few, then any free port
+fn bind(settings: &HookServerSettings) -> Result<tiny_http::Server, String> {
+    let fallback_ports = (1..=PORT_FALLBACK_RANGE).filter_map(|offset| settings.port.checked_add(offset));
+    let mut last_error = String::new();
+    for port in std::iter::once(settings.port).chain(fallback_ports).chain([0]) {
+        match tiny_http::Server::http((settings.bind_address, port)) {
+            Ok(server) => return Ok(server),
+            Err(e) => last_error = e.to_string(),
+        }
+    }
+    Err(format!("Failed to bind hook server: {}", last_error))
 }
 
 impl HookServer {
-    pub fn start(monitor: Arc<SessionMonitor>) -> Result<Self, String> {
-        let server = tiny_http::Server::http("127.0.0.1:19823")
-            .map_err(|e| format!("Failed to bind hook server: {}", e))?;
+    pub fn start(
+        monitor: Arc<SessionMonitor>,
+        settings: &HookServerSettings,
+        token: String,
+    ) -> Result<Self, String> {
+        let server = bind(settings)?;
+        let port = server
+            .server_addr()
+            .to_ip()
+            .map(|addr| addr.port())
+            .unwrap_or(settings.port);
 
         let server_running = Arc::new(AtomicBool::new(true));
         let running = server_running.clone();
+        let token = Arc::new(RwLock::new(token));
+        let expected_token = token.clone();
 
-        std::thread::spawn(move || {
+        let thread = std::thread::spawn(move || {
             while running.load(Ordering::Relaxed) {
                 let mut request = match server.recv_timeout(std::time::Duration::from_secs(1)) {
                     Ok(Some(req)) => req,
@@ -188,15 +332,33 @@ impl HookServer {
                     Err(_) => break,
                 };
 
-                if request.method() != &tiny_http::Method::Post
-                    || request.url() != "/sessionly"
-                {
+                // Hook events are posted to HOOK_PATH; any GET is for the REST API
+                let is_hook = request.method() == &tiny_http::Method::Post
+                    && request.url() == HOOK_PATH;
+                if !is_hook && request.method() != &tiny_http::Method::Get {
                     let response = tiny_http::Response::from_string("Not Found")
                         .with_status_code(404);
                     let _ = request.respond(response);
                     continue;
                 }
 
+                let authorized = request.headers().iter().any(|h| {
+                    h.field.equiv(TOKEN_HEADER)
+                        && tokens_match(h.value.as_str(), &expected_token.read().unwrap())
+                });
+                if !authorized {
+                    let response = tiny_http::Response::from_string("Unauthorized")
+                        .with_status_code(401);
+                    let _ = request.respond(response);
+                    continue;
+                }
+
+                if !is_hook {
+                    // Reading history can be slow; don't hold up hook events
+                    std::thread::spawn(move || crate::rest_api::respond(request));
+                    continue;
+                }
+
                 let mut body = String::new();
                 if request.as_reader().read_to_string(&mut body).is_err() {
                     let response = tiny_http::Response::from_string("Bad Request")
@@ -206,13 +368,13 @@ impl HookServer {
                 }
 
                 match serde_json::from_str::<crate::session_monitor::HookEventPayload>(&body) {
-                    Ok(payload) => {
+                    Ok(payload) if crate::session_monitor::is_valid_session_id(&payload.session_id) => {
                         monitor.handle_hook_event(payload);
                         let response = tiny_http::Response::from_string("OK")
                             .with_status_code(200);
                         let _ = request.respond(response);
                     }
-                    Err(_) => {
+                    _ => {
                         let response = tiny_http::Response::from_string("Bad Request")
                             .with_status_code(400);
                         let _ = request.respond(response);
@@ -221,16 +383,41 @@ impl HookServer {
             }
         });
 
-        Ok(Self { server_running })
+        Ok(Self {
+            server_running,
+            bind_address: settings.bind_address,
+            port,
+            token,
+            thread: Some(thread),
+        })
     }
 
     pub fn is_running(&self) -> bool {
         self.server_running.load(Ordering::Relaxed)
     }
+
+    /// The port actually bound, which may differ from the configured one
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// Hook command that reaches this server
+    pub fn hook_command(&self) -> String {
+        hook_command(self.bind_address, self.port, &self.token.read().unwrap())
+    }
+
+    /// Accept only requests carrying this token from now on
+    pub fn set_token(&self, token: String) {
+        *self.token.write().unwrap() = token;
+    }
 }
 
 impl Drop for HookServer {
     fn drop(&mut self) {
         self.server_running.store(false, Ordering::Relaxed);
+        // Wait for the loop to exit so the port is released before a restart
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
     }
 }
@@ -1,17 +1,35 @@
+mod analytics;
+mod app_settings;
 mod commands;
 mod hooks;
-mod markdown_export;
+mod notification_rules;
+mod notifier;
+mod pricing;
+mod rest_api;
+mod session_activity;
 mod session_monitor;
-mod session_store;
-mod session_types;
+mod session_state;
+mod session_watcher;
+mod tool_timing;
+mod tray;
+mod webhooks;
+
+// Parsing, search and export live in sessionly-core; re-exported so
+// `crate::session_store` and friends keep working here
+pub use sessionly_core::{
+    html_export, json_export, markdown_export, redaction, session_search, session_store,
+    session_types,
+};
 
 use session_monitor::SessionMonitor;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use tauri::Manager;
 
 pub struct AppState {
     pub session_monitor: Arc<SessionMonitor>,
-    pub hook_server: Option<hooks::HookServer>,
+    pub hook_server: Mutex<Option<hooks::HookServer>>,
+    pub session_watcher: Mutex<Option<session_watcher::SessionWatcher>>,
+    pub open_sessions: Arc<session_watcher::OpenSessions>,
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -27,12 +45,18 @@ pub fn run() {
         .setup(|app| {
             let handle = app.handle().clone();
 
+            // Scan configured data roots before anything reads sessions
+            session_store::set_data_roots(app_settings::load().data_roots);
+
             let monitor = Arc::new(SessionMonitor::new(handle.clone()));
 
             // Start hook server
-            let hook_server = match hooks::HookServer::start(monitor.clone()) {
+            let hook_settings = app_settings::load().hook_server;
+            let hook_server = match hooks::load_or_create_token()
+                .and_then(|token| hooks::HookServer::start(monitor.clone(), &hook_settings, token))
+            {
                 Ok(server) => {
-                    println!("Hook server started on 127.0.0.1:19823");
+                    println!("Hook server started on {}:{}", hook_settings.bind_address, server.port());
                     Some(server)
                 }
                 Err(e) => {
@@ -41,37 +65,91 @@ pub fn run() {
                 }
             };
 
-            // Auto-install hooks if not installed
-            if !hooks::is_hooks_installed() {
-                if let Err(e) = hooks::install_hooks() {
+            // Watch session files for live updates
+            let open_sessions = Arc::new(session_watcher::OpenSessions::default());
+            let session_watcher = match session_watcher::SessionWatcher::start(handle.clone(), open_sessions.clone()) {
+                Ok(watcher) => Some(watcher),
+                Err(e) => {
+                    eprintln!("Failed to start session watcher: {}", e);
+                    None
+                }
+            };
+
+            // Auto-install hooks, pointing existing ones at the port actually bound
+            if let Some(server) = &hook_server {
+                if let Err(e) = hooks::install_hooks(&server.hook_command()) {
                     eprintln!("Failed to auto-install hooks: {}", e);
                 }
             }
 
-            // Prune stale sessions every 30s
+            // List tracked sessions in the tray menu
+            tray::setup(&handle, monitor.clone());
+
+            // Prune stale sessions, report stuck tool calls and refresh the
+            // tray's time-in-state labels every 30s
             let monitor_prune = monitor.clone();
+            let handle_prune = handle.clone();
             std::thread::spawn(move || loop {
                 std::thread::sleep(std::time::Duration::from_secs(30));
                 monitor_prune.prune_stale();
+                monitor_prune.check_stuck_tool_calls();
+                tray::refresh(&handle_prune, &monitor_prune);
             });
 
             app.manage(AppState {
                 session_monitor: monitor,
-                hook_server,
+                hook_server: Mutex::new(hook_server),
+                session_watcher: Mutex::new(session_watcher),
+                open_sessions,
             });
 
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             commands::get_projects,
+            commands::rebuild_session_index,
+            commands::get_usage_report,
+            commands::get_statistics,
+            commands::get_price_table,
+            commands::set_price_table,
+            commands::get_data_roots,
+            commands::set_data_roots,
             commands::get_session,
+            commands::search_sessions,
+            commands::watch_session,
+            commands::unwatch_session,
+            commands::refresh_session,
             commands::get_version,
             commands::get_native_theme,
             commands::export_session_markdown,
+            commands::export_session_html,
+            commands::export_session_json,
+            commands::export_session_jsonl,
+            commands::preview_redaction,
+            commands::get_redaction_config,
+            commands::set_redaction_config,
+            commands::get_redaction_detectors,
+            commands::get_monitored_sessions,
+            commands::get_session_activity,
+            commands::get_activity_settings,
+            commands::set_activity_settings,
+            commands::get_tool_latency,
+            commands::get_stuck_tool_calls,
+            commands::get_tool_timing_settings,
+            commands::set_tool_timing_settings,
+            commands::get_notification_settings,
+            commands::set_notification_settings,
+            commands::get_webhook_settings,
+            commands::set_webhook_settings,
+            commands::get_webhook_deliveries,
+            commands::test_webhook,
             commands::hooks_get_status,
             commands::hooks_install,
             commands::hooks_uninstall,
             commands::hooks_is_installed,
+            commands::get_hook_server_settings,
+            commands::set_hook_server_settings,
+            commands::rotate_hook_token,
             commands::send_native_notification,
         ])
         .run(tauri::generate_context!())
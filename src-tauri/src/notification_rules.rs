@@ -0,0 +1,358 @@
+//! Decides which session state transitions produce a notification.
+//!
+//! Kept free of Tauri so rules can be evaluated and checked without a GUI.
+
+use crate::session_state::{SessionState, SessionStateInfo};
+use chrono::{DateTime, Local, NaiveTime};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+const TIME_FORMAT: &str = "%H:%M";
+
+/// Notify when a session moves between matching states
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationRule {
+    pub id: String,
+    pub name: String,
+    pub enabled: bool,
+    /// Project name to match; any project if unset
+    #[serde(default)]
+    pub project: Option<String>,
+    /// Previous states to match; any state if empty
+    #[serde(default)]
+    pub from: Vec<SessionState>,
+    /// New states to match
+    pub to: Vec<SessionState>,
+    /// Only fire once the session has been tracked at least this long
+    #[serde(default)]
+    pub min_session_secs: Option<u64>,
+    /// Title template; `{project}`, `{state}` and `{message}` are substituted
+    #[serde(default)]
+    pub title: Option<String>,
+    /// Body template, with the same placeholders as the title
+    #[serde(default)]
+    pub body: Option<String>,
+}
+
+/// A daily window without notifications, in local time. It may span midnight.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuietHours {
+    pub enabled: bool,
+    /// "HH:MM"
+    pub start: String,
+    /// "HH:MM"
+    pub end: String,
+}
+
+impl Default for QuietHours {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            start: "22:00".to_string(),
+            end: "08:00".to_string(),
+        }
+    }
+}
+
+impl QuietHours {
+    fn window(&self) -> Result<(NaiveTime, NaiveTime), String> {
+        let parse = |time: &str| {
+            NaiveTime::parse_from_str(time, TIME_FORMAT)
+                .map_err(|_| format!("Invalid time '{}', expected HH:MM", time))
+        };
+        Ok((parse(&self.start)?, parse(&self.end)?))
+    }
+
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        match self.window() {
+            Ok((start, end)) if start <= end => start <= time && time < end,
+            Ok((start, end)) => time >= start || time < end,
+            Err(_) => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationSettings {
+    pub enabled: bool,
+    /// Evaluated in order; the first matching rule wins
+    pub rules: Vec<NotificationRule>,
+    #[serde(default)]
+    pub quiet_hours: QuietHours,
+    /// Projects never notified about
+    #[serde(default)]
+    pub muted_projects: Vec<String>,
+    /// Suppress repeats of a rule for the same session within this window
+    #[serde(default = "default_dedupe_secs")]
+    pub dedupe_secs: u64,
+}
+
+fn default_dedupe_secs() -> u64 {
+    5
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            rules: default_rules(),
+            quiet_hours: QuietHours::default(),
+            muted_projects: Vec::new(),
+            dedupe_secs: default_dedupe_secs(),
+        }
+    }
+}
+
+impl NotificationSettings {
+    /// Check that the settings can be evaluated
+    pub fn validate(&self) -> Result<(), String> {
+        self.quiet_hours.window()?;
+        for rule in &self.rules {
+            if rule.to.is_empty() {
+                return Err(format!("Rule '{}' matches no states", rule.name));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn rule(id: &str, name: &str, to: SessionState, title: &str, body: &str) -> NotificationRule {
+    NotificationRule {
+        id: id.to_string(),
+        name: name.to_string(),
+        enabled: true,
+        project: None,
+        from: Vec::new(),
+        to: vec![to],
+        min_session_secs: None,
+        title: Some(title.to_string()),
+        body: Some(body.to_string()),
+    }
+}
+
+/// The rules Sessionly ships with: one per state that needs attention, each
+/// of which can be switched off in the settings
+pub fn default_rules() -> Vec<NotificationRule> {
+    vec![
+        rule(
+            "awaiting-permission",
+            "Needs permission",
+            SessionState::AwaitingPermission,
+            "{project} needs permission",
+            "{message}",
+        ),
+        rule(
+            "awaiting-input",
+            "Waiting for input",
+            SessionState::AwaitingInput,
+            "{project} needs your attention",
+            "{message}",
+        ),
+        rule(
+            "completed",
+            "Session finished",
+            SessionState::Completed,
+            "{project} finished",
+            "Session is waiting for your input",
+        ),
+        rule(
+            "error",
+            "Session error",
+            SessionState::Error,
+            "{project} hit an error",
+            "A tool failed — check the session",
+        ),
+    ]
+}
+
+/// A notification to deliver
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Notification {
+    pub rule_id: String,
+    pub session_id: String,
+    pub project: Option<String>,
+    pub title: String,
+    pub body: String,
+}
+
+fn state_name(state: SessionState) -> String {
+    serde_json::to_value(state)
+        .ok()
+        .and_then(|v| v.as_str().map(|s| s.replace('_', " ")))
+        .unwrap_or_default()
+}
+
+fn render(template: &str, change: &SessionStateInfo) -> String {
+    template
+        .replace("{project}", change.project.as_deref().unwrap_or("Claude Code"))
+        .replace("{state}", &state_name(change.state))
+        .replace("{message}", change.message.as_deref().unwrap_or_default())
+}
+
+impl NotificationRule {
+    fn matches(&self, change: &SessionStateInfo, session_age_ms: i64) -> bool {
+        self.enabled
+            && self.to.contains(&change.state)
+            && (self.from.is_empty() || self.from.contains(&change.prev_state))
+            && self
+                .project
+                .as_ref()
+                .is_none_or(|p| change.project.as_deref() == Some(p.as_str()))
+            && self
+                .min_session_secs
+                .is_none_or(|secs| session_age_ms >= secs as i64 * 1000)
+    }
+
+    fn notification(&self, change: &SessionStateInfo) -> Notification {
+        let title = self.title.as_deref().unwrap_or("{project}: {state}");
+        let mut body = render(self.body.as_deref().unwrap_or("{message}"), change);
+        if body.trim().is_empty() {
+            body = format!("Session is {}", state_name(change.state));
+        }
+        Notification {
+            rule_id: self.id.clone(),
+            session_id: change.session_id.clone(),
+            project: change.project.clone(),
+            title: render(title, change),
+            body,
+        }
+    }
+}
+
+/// Evaluates transitions against the rules, remembering recent notifications
+/// for de-duplication
+pub struct NotificationEngine {
+    settings: Mutex<NotificationSettings>,
+    /// (session id, rule id) -> ms since epoch
+    last_fired: Mutex<HashMap<(String, String), i64>>,
+}
+
+impl NotificationEngine {
+    pub fn new(settings: NotificationSettings) -> Self {
+        Self {
+            settings: Mutex::new(settings),
+            last_fired: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn configure(&self, settings: NotificationSettings) {
+        *self.settings.lock().unwrap() = settings;
+    }
+
+    /// The notification a transition should raise at `now`, if any
+    pub fn evaluate(
+        &self,
+        change: &SessionStateInfo,
+        session_age_ms: i64,
+        now: DateTime<Local>,
+    ) -> Option<Notification> {
+        let settings = self.settings.lock().unwrap();
+        if !settings.enabled || settings.quiet_hours.contains(now.time()) {
+            return None;
+        }
+        if let Some(project) = &change.project {
+            if settings.muted_projects.contains(project) {
+                return None;
+            }
+        }
+        let rule = settings.rules.iter().find(|r| r.matches(change, session_age_ms))?;
+
+        let now_ms = now.timestamp_millis();
+        let window_ms = settings.dedupe_secs as i64 * 1000;
+        let mut last_fired = self.last_fired.lock().unwrap();
+        last_fired.retain(|_, fired| now_ms - *fired < window_ms);
+        let key = (change.session_id.clone(), rule.id.clone());
+        if last_fired.contains_key(&key) {
+            return None;
+        }
+        last_fired.insert(key, now_ms);
+        Some(rule.notification(change))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(hour: u32, minute: u32, second: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 10, 1, hour, minute, second).single().unwrap()
+    }
+
+    fn change(session_id: &str, state: SessionState, project: &str) -> SessionStateInfo {
+        SessionStateInfo {
+            session_id: session_id.to_string(),
+            prev_state: SessionState::Working,
+            state,
+            aggregate_state: state,
+            project: Some(project.to_string()),
+            message: None,
+        }
+    }
+
+    fn engine(configure: impl FnOnce(&mut NotificationSettings)) -> NotificationEngine {
+        let mut settings = NotificationSettings::default();
+        configure(&mut settings);
+        NotificationEngine::new(settings)
+    }
+
+    #[test]
+    fn quiet_hours_spanning_midnight() {
+        let engine = engine(|s| s.quiet_hours.enabled = true);
+        let completed = |id: &str| change(id, SessionState::Completed, "app");
+        assert_eq!(engine.evaluate(&completed("s1"), 0, at(21, 59, 0)).unwrap().rule_id, "completed");
+        assert!(engine.evaluate(&completed("s2"), 0, at(22, 0, 0)).is_none());
+        assert!(engine.evaluate(&completed("s3"), 0, at(0, 30, 0)).is_none());
+        assert!(engine.evaluate(&completed("s4"), 0, at(7, 59, 0)).is_none());
+        assert!(engine.evaluate(&completed("s5"), 0, at(8, 0, 0)).is_some());
+    }
+
+    #[test]
+    fn quiet_hours_within_a_day() {
+        let quiet = QuietHours {
+            enabled: true,
+            start: "09:00".to_string(),
+            end: "17:00".to_string(),
+        };
+        assert!(!quiet.contains(at(8, 59, 0).time()));
+        assert!(quiet.contains(at(9, 0, 0).time()));
+        assert!(quiet.contains(at(16, 59, 0).time()));
+        assert!(!quiet.contains(at(17, 0, 0).time()));
+        assert!(!QuietHours { enabled: false, ..quiet }.contains(at(12, 0, 0).time()));
+    }
+
+    #[test]
+    fn muted_projects_are_skipped() {
+        let engine = engine(|s| s.muted_projects = vec!["noisy".to_string()]);
+        let now = at(12, 0, 0);
+        assert!(engine.evaluate(&change("s1", SessionState::Error, "noisy"), 0, now).is_none());
+        assert!(engine.evaluate(&change("s2", SessionState::Error, "app"), 0, now).is_some());
+    }
+
+    #[test]
+    fn minimum_session_age() {
+        let engine = engine(|s| {
+            let rule = s.rules.iter_mut().find(|r| r.id == "error").unwrap();
+            rule.min_session_secs = Some(60);
+        });
+        let error = change("s1", SessionState::Error, "app");
+        assert!(engine.evaluate(&error, 59_999, at(12, 0, 0)).is_none());
+        assert_eq!(engine.evaluate(&error, 60_000, at(12, 0, 0)).unwrap().rule_id, "error");
+    }
+
+    #[test]
+    fn repeats_within_five_seconds_are_dropped_per_session_and_rule() {
+        let engine = engine(|_| {});
+        let completed = change("s1", SessionState::Completed, "app");
+        assert!(engine.evaluate(&completed, 0, at(12, 0, 0)).is_some());
+        assert!(engine.evaluate(&completed, 0, at(12, 0, 4)).is_none());
+        // Another rule and another session are tracked separately
+        assert!(engine.evaluate(&change("s1", SessionState::Error, "app"), 0, at(12, 0, 4)).is_some());
+        assert!(engine.evaluate(&change("s2", SessionState::Completed, "app"), 0, at(12, 0, 4)).is_some());
+        assert!(engine.evaluate(&completed, 0, at(12, 0, 5)).is_some());
+    }
+}
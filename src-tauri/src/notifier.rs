@@ -0,0 +1,121 @@
+//! Native notification delivery with per-platform fallbacks.
+//!
+//! Backends are tried in order until one succeeds:
+//! - macOS: notification plugin, then `osascript`
+//! - Linux: `notify-send`, then `gdbus` against org.freedesktop.Notifications,
+//!   then the notification plugin
+//! - elsewhere: notification plugin
+
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+use std::process::Command;
+use tauri::AppHandle;
+use tauri_plugin_notification::NotificationExt;
+
+#[cfg(target_os = "linux")]
+const APP_NAME: &str = "Sessionly";
+/// How long freedesktop notifications stay up, in ms
+#[cfg(target_os = "linux")]
+const EXPIRE_TIMEOUT_MS: u32 = 5000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Backend {
+    Plugin,
+    #[cfg(target_os = "macos")]
+    Osascript,
+    #[cfg(target_os = "linux")]
+    NotifySend,
+    #[cfg(target_os = "linux")]
+    Gdbus,
+}
+
+impl Backend {
+    fn name(self) -> &'static str {
+        match self {
+            Backend::Plugin => "notification plugin",
+            #[cfg(target_os = "macos")]
+            Backend::Osascript => "osascript",
+            #[cfg(target_os = "linux")]
+            Backend::NotifySend => "notify-send",
+            #[cfg(target_os = "linux")]
+            Backend::Gdbus => "gdbus",
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+const BACKENDS: &[Backend] = &[Backend::Plugin, Backend::Osascript];
+#[cfg(target_os = "linux")]
+const BACKENDS: &[Backend] = &[Backend::NotifySend, Backend::Gdbus, Backend::Plugin];
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+const BACKENDS: &[Backend] = &[Backend::Plugin];
+
+/// Run a command, turning spawn failures and non-zero exits into errors
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+fn run(command: &mut Command) -> Result<(), String> {
+    let output = command.output().map_err(|e| e.to_string())?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    Err(match stderr.trim() {
+        "" => format!("exited with {}", output.status),
+        message => message.to_string(),
+    })
+}
+
+/// Quote a string as a GVariant text literal
+#[cfg(target_os = "linux")]
+fn gvariant_string(text: &str) -> String {
+    format!("'{}'", text.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+fn send_with(app: &AppHandle, backend: Backend, title: &str, body: &str) -> Result<(), String> {
+    match backend {
+        Backend::Plugin => app
+            .notification()
+            .builder()
+            .title(title)
+            .body(body)
+            .show()
+            .map_err(|e| e.to_string()),
+        // Title and body are passed as arguments so they need no escaping
+        #[cfg(target_os = "macos")]
+        Backend::Osascript => run(Command::new("osascript")
+            .args(["-e", "on run argv"])
+            .args(["-e", "display notification (item 2 of argv) with title (item 1 of argv)"])
+            .args(["-e", "end run"])
+            .args([title, body])),
+        #[cfg(target_os = "linux")]
+        Backend::NotifySend => run(Command::new("notify-send")
+            .arg(format!("--app-name={}", APP_NAME))
+            .arg(format!("--expire-time={}", EXPIRE_TIMEOUT_MS))
+            .arg("--")
+            .args([title, body])),
+        #[cfg(target_os = "linux")]
+        Backend::Gdbus => run(Command::new("gdbus")
+            .args(["call", "--session"])
+            .args(["--dest", "org.freedesktop.Notifications"])
+            .args(["--object-path", "/org/freedesktop/Notifications"])
+            .args(["--method", "org.freedesktop.Notifications.Notify"])
+            .arg(gvariant_string(APP_NAME))
+            .arg("0")
+            .arg(gvariant_string(""))
+            .arg(gvariant_string(title))
+            .arg(gvariant_string(body))
+            .args(["[]", "{}"])
+            .arg(EXPIRE_TIMEOUT_MS.to_string())),
+    }
+}
+
+/// Show a notification, returning the name of the backend that delivered it.
+/// Fails with every backend's error if none did.
+pub fn notify(app: &AppHandle, title: &str, body: &str) -> Result<&'static str, String> {
+    let mut errors = Vec::new();
+    for &backend in BACKENDS {
+        match send_with(app, backend, title, body) {
+            Ok(()) => return Ok(backend.name()),
+            Err(e) => errors.push(format!("{}: {}", backend.name(), e)),
+        }
+    }
+    Err(format!("No notification backend succeeded ({})", errors.join("; ")))
+}
@@ -0,0 +1,321 @@
+use crate::session_types::{ProjectGroup, TokenUsage};
+use chrono::{Local, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Prices in USD per million tokens
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ModelPrice {
+    pub input: f64,
+    pub output: f64,
+    #[serde(rename = "cacheWrite")]
+    pub cache_write: f64,
+    #[serde(rename = "cacheRead")]
+    pub cache_read: f64,
+}
+
+impl ModelPrice {
+    const fn new(input: f64, output: f64, cache_write: f64, cache_read: f64) -> Self {
+        Self {
+            input,
+            output,
+            cache_write,
+            cache_read,
+        }
+    }
+
+    pub fn cost(&self, usage: &TokenUsage) -> f64 {
+        (usage.input_tokens as f64 * self.input
+            + usage.output_tokens as f64 * self.output
+            + usage.cache_creation_input_tokens as f64 * self.cache_write
+            + usage.cache_read_input_tokens as f64 * self.cache_read)
+            / 1_000_000.0
+    }
+}
+
+/// Per-model prices keyed by model id prefix; the longest matching prefix wins,
+/// so "claude-opus-4-5" can override "claude-opus-4"
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PriceTable {
+    pub models: BTreeMap<String, ModelPrice>,
+}
+
+impl Default for PriceTable {
+    fn default() -> Self {
+        let models = [
+            ("claude-opus-4-5", ModelPrice::new(5.0, 25.0, 6.25, 0.5)),
+            ("claude-opus-4", ModelPrice::new(15.0, 75.0, 18.75, 1.5)),
+            ("claude-sonnet-4", ModelPrice::new(3.0, 15.0, 3.75, 0.3)),
+            ("claude-haiku-4-5", ModelPrice::new(1.0, 5.0, 1.25, 0.1)),
+            ("claude-3-7-sonnet", ModelPrice::new(3.0, 15.0, 3.75, 0.3)),
+            ("claude-3-5-sonnet", ModelPrice::new(3.0, 15.0, 3.75, 0.3)),
+            ("claude-3-5-haiku", ModelPrice::new(0.8, 4.0, 1.0, 0.08)),
+            ("claude-3-opus", ModelPrice::new(15.0, 75.0, 18.75, 1.5)),
+            ("claude-3-haiku", ModelPrice::new(0.25, 1.25, 0.3, 0.03)),
+        ];
+        Self {
+            models: models
+                .into_iter()
+                .map(|(name, price)| (name.to_string(), price))
+                .collect(),
+        }
+    }
+}
+
+impl PriceTable {
+    pub fn price_for(&self, model: &str) -> Option<&ModelPrice> {
+        self.models
+            .iter()
+            .filter(|(prefix, _)| model.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, price)| price)
+    }
+
+    /// Cost of per-model usage, or None if none of the models has a price
+    pub fn cost_of(&self, model_usage: &HashMap<String, TokenUsage>) -> Option<f64> {
+        let mut total: Option<f64> = None;
+        for (model, usage) in model_usage {
+            if let Some(price) = self.price_for(model) {
+                *total.get_or_insert(0.0) += price.cost(usage);
+            }
+        }
+        total
+    }
+}
+
+/// Fill in session and project costs from the price table
+pub fn apply_costs(groups: &mut [ProjectGroup], table: &PriceTable) {
+    for group in groups {
+        let mut group_cost: Option<f64> = None;
+        for session in &mut group.sessions {
+            session.cost_usd = table.cost_of(&session.model_usage);
+            if let Some(cost) = session.cost_usd {
+                *group_cost.get_or_insert(0.0) += cost;
+            }
+        }
+        group.cost_usd = group_cost;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UsagePeriod {
+    Day,
+    Week,
+    Month,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageBucket {
+    /// "2025-01-31", "2025-W05" or "2025-01"
+    pub period: String,
+    /// Sessions active in the period
+    #[serde(rename = "sessionCount")]
+    pub session_count: usize,
+    pub usage: TokenUsage,
+    #[serde(rename = "costUsd")]
+    pub cost_usd: Option<f64>,
+}
+
+impl UsageBucket {
+    fn new(period: String) -> Self {
+        Self {
+            period,
+            session_count: 0,
+            usage: TokenUsage::default(),
+            cost_usd: None,
+        }
+    }
+}
+
+/// Total usage and cost per local day, ISO week or month, newest first.
+/// Usage is attributed to the period it happened in, so a session running
+/// past midnight counts toward both days.
+pub fn usage_report(groups: &[ProjectGroup], period: UsagePeriod, table: &PriceTable) -> Vec<UsageBucket> {
+    let format = match period {
+        UsagePeriod::Day => "%Y-%m-%d",
+        UsagePeriod::Week => "%G-W%V",
+        UsagePeriod::Month => "%Y-%m",
+    };
+    let period_of = |ms: i64| Local.timestamp_millis_opt(ms).single().map(|t| t.format(format).to_string());
+
+    let mut buckets: BTreeMap<String, UsageBucket> = BTreeMap::new();
+    for session in groups.iter().flat_map(|g| &g.sessions) {
+        // Periods the session was active in, each counted once
+        let mut periods: HashSet<String> = HashSet::new();
+        for slice in &session.usage_slices {
+            let Some(key) = period_of(slice.start) else {
+                continue;
+            };
+            let bucket = buckets.entry(key.clone()).or_insert_with(|| UsageBucket::new(key.clone()));
+            bucket.usage += slice.usage;
+            if let Some(price) = table.price_for(&slice.model) {
+                *bucket.cost_usd.get_or_insert(0.0) += price.cost(&slice.usage);
+            }
+            periods.insert(key);
+        }
+        // Sessions without usage still count where they started
+        if periods.is_empty() {
+            periods.extend(session.start_time.and_then(period_of));
+        }
+        for key in periods {
+            buckets.entry(key.clone()).or_insert_with(|| UsageBucket::new(key)).session_count += 1;
+        }
+    }
+
+    buckets.into_values().rev().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session_types::{SessionSummary, UsageSlice};
+
+    fn table() -> PriceTable {
+        PriceTable {
+            models: [
+                ("claude-test".to_string(), ModelPrice::new(1.0, 2.0, 3.0, 4.0)),
+                ("claude-test-2".to_string(), ModelPrice::new(10.0, 20.0, 30.0, 40.0)),
+            ]
+            .into_iter()
+            .collect(),
+        }
+    }
+
+    fn usage(input: u64, output: u64, cache_write: u64, cache_read: u64) -> TokenUsage {
+        TokenUsage {
+            input_tokens: input,
+            output_tokens: output,
+            cache_creation_input_tokens: cache_write,
+            cache_read_input_tokens: cache_read,
+        }
+    }
+
+    fn local_ms(y: i32, m: u32, d: u32, h: u32, min: u32) -> i64 {
+        Local.with_ymd_and_hms(y, m, d, h, min, 0).unwrap().timestamp_millis()
+    }
+
+    fn session(id: &str, start_time: Option<i64>, slices: &[(i64, &str, TokenUsage)]) -> SessionSummary {
+        SessionSummary {
+            id: id.to_string(),
+            project: "/work/app".to_string(),
+            project_encoded: "-work-app".to_string(),
+            source: "default".to_string(),
+            first_message: String::new(),
+            message_count: 1,
+            start_time,
+            end_time: None,
+            git_branch: None,
+            model: None,
+            file_path: String::new(),
+            usage: TokenUsage::default(),
+            model_usage: HashMap::new(),
+            usage_slices: slices
+                .iter()
+                .map(|&(start, model, usage)| UsageSlice {
+                    start,
+                    model: model.to_string(),
+                    usage,
+                })
+                .collect(),
+            cost_usd: None,
+        }
+    }
+
+    fn group(sessions: Vec<SessionSummary>) -> ProjectGroup {
+        ProjectGroup {
+            project: "/work/app".to_string(),
+            project_encoded: "-work-app".to_string(),
+            source: "default".to_string(),
+            sessions,
+            usage: TokenUsage::default(),
+            cost_usd: None,
+        }
+    }
+
+    #[test]
+    fn prices_each_kind_of_token() {
+        let price = table().models["claude-test"];
+        assert_eq!(price.cost(&usage(1_000_000, 0, 0, 0)), 1.0);
+        assert_eq!(price.cost(&usage(0, 1_000_000, 0, 0)), 2.0);
+        assert_eq!(price.cost(&usage(0, 0, 1_000_000, 0)), 3.0);
+        assert_eq!(price.cost(&usage(0, 0, 0, 1_000_000)), 4.0);
+        assert_eq!(price.cost(&usage(500_000, 250_000, 2_000_000, 4_000_000)), 23.0);
+    }
+
+    #[test]
+    fn the_longest_matching_prefix_wins() {
+        let table = table();
+        assert_eq!(table.price_for("claude-test-2-20260101").unwrap().input, 10.0);
+        assert_eq!(table.price_for("claude-test-1").unwrap().input, 1.0);
+        assert!(table.price_for("gpt-5").is_none());
+        assert!(table.price_for("claude").is_none());
+    }
+
+    #[test]
+    fn unknown_models_are_left_unpriced() {
+        let table = table();
+        let unknown: HashMap<String, TokenUsage> =
+            [("gpt-5".to_string(), usage(1_000_000, 0, 0, 0))].into_iter().collect();
+        assert_eq!(table.cost_of(&unknown), None);
+
+        let mut mixed = unknown.clone();
+        mixed.insert("claude-test".to_string(), usage(0, 1_000_000, 0, 0));
+        assert_eq!(table.cost_of(&mixed), Some(2.0));
+
+        let mut groups = [group(vec![
+            session("s1", Some(0), &[]),
+            session("s2", Some(0), &[]),
+        ])];
+        groups[0].sessions[0].model_usage = unknown;
+        groups[0].sessions[1].model_usage = mixed;
+        apply_costs(&mut groups, &table);
+        assert_eq!(groups[0].sessions[0].cost_usd, None);
+        assert_eq!(groups[0].sessions[1].cost_usd, Some(2.0));
+        assert_eq!(groups[0].cost_usd, Some(2.0));
+    }
+
+    #[test]
+    fn usage_is_bucketed_by_the_local_period_it_happened_in() {
+        let tokens = usage(1_000_000, 0, 0, 0);
+        // Sunday 2026-03-01 23:45 to Monday 00:15, the start of ISO week 10
+        let groups = [group(vec![
+            session(
+                "s1",
+                Some(local_ms(2026, 3, 1, 23, 45)),
+                &[
+                    (local_ms(2026, 3, 1, 23, 45), "claude-test", tokens),
+                    (local_ms(2026, 3, 2, 0, 0), "claude-test", tokens),
+                    (local_ms(2026, 3, 2, 0, 15), "gpt-5", tokens),
+                ],
+            ),
+            session("s2", Some(local_ms(2026, 2, 28, 12, 0)), &[]),
+        ])];
+        let table = table();
+
+        let days = usage_report(&groups, UsagePeriod::Day, &table);
+        let days: Vec<_> = days
+            .iter()
+            .map(|b| (b.period.as_str(), b.session_count, b.usage.input_tokens, b.cost_usd))
+            .collect();
+        assert_eq!(
+            days,
+            [
+                ("2026-03-02", 1, 2_000_000, Some(1.0)),
+                ("2026-03-01", 1, 1_000_000, Some(1.0)),
+                ("2026-02-28", 1, 0, None),
+            ]
+        );
+
+        let weeks = usage_report(&groups, UsagePeriod::Week, &table);
+        let weeks: Vec<_> = weeks.iter().map(|b| (b.period.as_str(), b.session_count)).collect();
+        assert_eq!(weeks, [("2026-W10", 1), ("2026-W09", 2)]);
+
+        let months = usage_report(&groups, UsagePeriod::Month, &table);
+        let months: Vec<_> = months
+            .iter()
+            .map(|b| (b.period.as_str(), b.session_count, b.cost_usd))
+            .collect();
+        assert_eq!(months, [("2026-03", 1, Some(2.0)), ("2026-02", 1, None)]);
+    }
+}
@@ -0,0 +1,417 @@
+//! Read-only REST API over session history, served alongside the hook
+//! endpoint so editor extensions and scripts can query it while the app runs.
+//!
+//! Every endpoint is a `GET` guarded by the hook token. Lists are paginated
+//! with `offset` and `limit`; see `docs/rest-api.md` for the filters.
+
+use crate::redaction::{RedactionConfig, Redactor};
+use crate::session_store;
+use crate::session_types::{ProjectGroup, Session, SessionSummary, TokenUsage};
+use crate::{app_settings, html_export, json_export, markdown_export, redaction};
+use percent_encoding::percent_decode_str;
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use url::Url;
+
+const DEFAULT_LIMIT: usize = 50;
+const MAX_LIMIT: usize = 500;
+
+/// A slice of a longer list
+#[derive(Debug, Serialize)]
+struct Page<T> {
+    items: Vec<T>,
+    /// Matching items before pagination
+    total: usize,
+    offset: usize,
+    limit: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct ProjectInfo {
+    project: String,
+    #[serde(rename = "projectEncoded")]
+    project_encoded: String,
+    source: String,
+    #[serde(rename = "sessionCount")]
+    session_count: usize,
+    /// Latest session activity, ms since epoch
+    #[serde(rename = "lastActivity")]
+    last_activity: Option<i64>,
+    usage: TokenUsage,
+}
+
+impl From<&ProjectGroup> for ProjectInfo {
+    fn from(group: &ProjectGroup) -> Self {
+        Self {
+            project: group.project.clone(),
+            project_encoded: group.project_encoded.clone(),
+            source: group.source.clone(),
+            session_count: group.sessions.len(),
+            last_activity: group
+                .sessions
+                .iter()
+                .filter_map(|s| s.end_time.or(s.start_time))
+                .max(),
+            usage: group.usage,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Reply {
+    status: u16,
+    content_type: &'static str,
+    body: String,
+}
+
+impl Reply {
+    fn json<T: Serialize>(value: &T) -> Self {
+        match serde_json::to_string(value) {
+            Ok(body) => Self {
+                status: 200,
+                content_type: "application/json",
+                body,
+            },
+            Err(e) => Self::error(500, &e.to_string()),
+        }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Self {
+            status,
+            content_type: "application/json",
+            body: serde_json::json!({ "error": message }).to_string(),
+        }
+    }
+}
+
+/// Decoded query string parameters
+struct Query(HashMap<String, String>);
+
+impl Query {
+    fn new(url: &Url) -> Self {
+        Self(url.query_pairs().into_owned().collect())
+    }
+
+    fn get(&self, name: &str) -> Option<&str> {
+        self.0.get(name).map(String::as_str).filter(|v| !v.is_empty())
+    }
+
+    fn usize(&self, name: &str, default: usize) -> Result<usize, Reply> {
+        self.get(name).map_or(Ok(default), |v| {
+            v.parse().map_err(|_| {
+                Reply::error(400, &format!("'{}' must be a non-negative integer", name))
+            })
+        })
+    }
+
+    /// A time given as ms since epoch or RFC 3339
+    fn time(&self, name: &str) -> Result<Option<i64>, Reply> {
+        let Some(value) = self.get(name) else {
+            return Ok(None);
+        };
+        value
+            .parse()
+            .ok()
+            .or_else(|| session_store::parse_timestamp(value))
+            .map(Some)
+            .ok_or_else(|| {
+                Reply::error(400, &format!("'{}' must be ms since epoch or RFC 3339", name))
+            })
+    }
+
+    fn flag(&self, name: &str, default: bool) -> Result<bool, Reply> {
+        match self.get(name) {
+            None => Ok(default),
+            Some("true" | "1") => Ok(true),
+            Some("false" | "0") => Ok(false),
+            Some(_) => Err(Reply::error(400, &format!("'{}' must be true or false", name))),
+        }
+    }
+}
+
+fn paginate<T>(items: Vec<T>, query: &Query) -> Result<Page<T>, Reply> {
+    let offset = query.usize("offset", 0)?;
+    let limit = query.usize("limit", DEFAULT_LIMIT)?.min(MAX_LIMIT);
+    let total = items.len();
+    let items = items.into_iter().skip(offset).take(limit).collect();
+    Ok(Page {
+        items,
+        total,
+        offset,
+        limit,
+    })
+}
+
+fn contains_ignore_case(haystack: &str, needle: &str) -> bool {
+    haystack.to_lowercase().contains(&needle.to_lowercase())
+}
+
+/// Redactor for listed text, unless `redact=false` or redaction is off
+fn text_redactor(query: &Query, config: &RedactionConfig) -> Result<Option<Redactor>, Reply> {
+    if !query.flag("redact", true)? || !config.enabled {
+        return Ok(None);
+    }
+    Redactor::for_text(config)
+        .map(Some)
+        .map_err(|e| Reply::error(500, &e))
+}
+
+fn redact(redactor: &mut Option<Redactor>, text: &mut String) {
+    if let Some(redactor) = redactor {
+        *text = redactor.redact_str(text);
+    }
+}
+
+/// Redact the free text and paths of a listed session
+fn redact_summary(summary: &mut SessionSummary, redactor: &mut Option<Redactor>) {
+    redact(redactor, &mut summary.project);
+    redact(redactor, &mut summary.first_message);
+    redact(redactor, &mut summary.file_path);
+}
+
+fn list_projects(query: &Query) -> Result<Reply, Reply> {
+    let mut redactor = text_redactor(query, &app_settings::load().redaction)?;
+    let mut projects: Vec<ProjectInfo> = session_store::get_all_sessions()
+        .iter()
+        .filter(|group| query.get("source").is_none_or(|s| group.source == s))
+        .map(ProjectInfo::from)
+        .map(|mut project| {
+            redact(&mut redactor, &mut project.project);
+            project
+        })
+        .filter(|project| query.get("q").is_none_or(|q| contains_ignore_case(&project.project, q)))
+        .collect();
+    projects.sort_by_key(|p| Reverse(p.last_activity));
+    Ok(Reply::json(&paginate(projects, query)?))
+}
+
+/// Sessions, newest first, in one project or across all of them
+fn list_sessions(project_encoded: Option<&str>, query: &Query) -> Result<Reply, Reply> {
+    let mut redactor = text_redactor(query, &app_settings::load().redaction)?;
+    let source = query.get("source");
+    let groups: Vec<ProjectGroup> = session_store::get_all_sessions()
+        .into_iter()
+        .filter(|g| source.is_none_or(|s| g.source == s))
+        .collect();
+    if let Some(encoded) = project_encoded {
+        if !groups.iter().any(|g| g.project_encoded == encoded) {
+            return Err(Reply::error(404, "Project not found"));
+        }
+    }
+    // Encoded or decoded path, as for search
+    let project = project_encoded.or(query.get("project"));
+    let branch = query.get("branch");
+    let model = query.get("model");
+    let text = query.get("q");
+    let since = query.time("since")?;
+    let until = query.time("until")?;
+
+    let mut sessions: Vec<SessionSummary> = groups
+        .into_iter()
+        .filter(|g| project.is_none_or(|p| g.project_encoded == p || g.project == p))
+        .flat_map(|g| g.sessions)
+        .filter(|s| branch.is_none_or(|b| s.git_branch.as_deref() == Some(b)))
+        .filter(|s| model.is_none_or(|m| s.model.as_deref().is_some_and(|sm| sm.contains(m))))
+        // Redacted before matching `q`, so searches can't probe for secrets
+        .map(|mut s| {
+            redact_summary(&mut s, &mut redactor);
+            s
+        })
+        .filter(|s| text.is_none_or(|q| contains_ignore_case(&s.first_message, q)))
+        .filter(|s| since.is_none_or(|t| s.start_time.is_some_and(|start| start >= t)))
+        .filter(|s| until.is_none_or(|t| s.start_time.is_some_and(|start| start <= t)))
+        .collect();
+    sessions.sort_by_key(|s| Reverse(s.start_time));
+    Ok(Reply::json(&paginate(sessions, query)?))
+}
+
+/// Reject values that would leave the projects directory when joined to it
+fn path_component(name: &str, value: &str) -> Result<(), Reply> {
+    if value.contains(['/', '\\']) || value.contains("..") {
+        return Err(Reply::error(400, &format!("Invalid {}", name)));
+    }
+    Ok(())
+}
+
+/// The project and data root a session belongs to, from `?project=` and
+/// `?source=` or the session index
+fn find_project(session_id: &str, query: &Query) -> Result<(String, Option<String>), Reply> {
+    let source = query.get("source");
+    if let Some(project) = query.get("project") {
+        path_component("project", project)?;
+        return Ok((project.to_string(), source.map(String::from)));
+    }
+    session_store::get_all_sessions()
+        .into_iter()
+        .filter(|g| source.is_none_or(|s| g.source == s))
+        .flat_map(|g| g.sessions)
+        .find(|s| s.id == session_id)
+        .map(|s| (s.project_encoded, Some(s.source)))
+        .ok_or_else(|| Reply::error(404, "Session not found"))
+}
+
+/// Load a session, redacted unless `redact=false`
+fn load_session(session_id: &str, query: &Query) -> Result<Session, Reply> {
+    path_component("session id", session_id)?;
+    let redact = query.flag("redact", true)?;
+    let (project_encoded, source) = find_project(session_id, query)?;
+    let mut session = session_store::get_session(session_id, &project_encoded, source.as_deref())
+        .ok_or_else(|| Reply::error(404, "Session not found"))?;
+    if redact {
+        redaction::redact_session(&mut session, &app_settings::load().redaction)
+            .map_err(|e| Reply::error(500, &e))?;
+    }
+    Ok(session)
+}
+
+fn get_session(session_id: &str, query: &Query) -> Result<Reply, Reply> {
+    Ok(Reply::json(&load_session(session_id, query)?))
+}
+
+/// Render a session like the app's export
+fn export_session(session_id: &str, query: &Query) -> Result<Reply, Reply> {
+    let format = query.get("format").unwrap_or("md");
+    let (render, content_type): (fn(&Session) -> String, _) = match format {
+        "md" | "markdown" => (markdown_export::session_to_markdown, "text/markdown; charset=utf-8"),
+        "html" => (html_export::session_to_html, "text/html; charset=utf-8"),
+        "json" => (json_export::session_to_json, "application/json"),
+        "jsonl" => (json_export::session_to_jsonl, "application/x-ndjson"),
+        _ => {
+            return Err(Reply::error(
+                400,
+                &format!("Unknown format '{}', expected md, html, json or jsonl", format),
+            ))
+        }
+    };
+    let session = load_session(session_id, query)?;
+    Ok(Reply {
+        status: 200,
+        content_type,
+        body: render(&session),
+    })
+}
+
+fn route(url: &Url) -> Reply {
+    let segments: Vec<String> = url
+        .path_segments()
+        .map(|segments| {
+            segments
+                .filter(|s| !s.is_empty())
+                .map(|s| percent_decode_str(s).decode_utf8_lossy().into_owned())
+                .collect()
+        })
+        .unwrap_or_default();
+    let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+    let query = Query::new(url);
+    let result = match segments.as_slice() {
+        ["projects"] => list_projects(&query),
+        ["projects", project, "sessions"] => list_sessions(Some(project), &query),
+        ["sessions"] => list_sessions(None, &query),
+        ["sessions", id] => get_session(id, &query),
+        ["sessions", id, "export"] => export_session(id, &query),
+        _ => Err(Reply::error(404, "Not Found")),
+    };
+    match result {
+        Ok(reply) | Err(reply) => reply,
+    }
+}
+
+/// Answer an authorized `GET` request
+pub fn respond(request: tiny_http::Request) {
+    let reply = match Url::parse("http://localhost").and_then(|base| base.join(request.url())) {
+        Ok(url) => route(&url),
+        Err(_) => Reply::error(400, "Invalid URL"),
+    };
+    let mut response = tiny_http::Response::from_string(reply.body).with_status_code(reply.status);
+    if let Ok(header) = tiny_http::Header::from_bytes("Content-Type", reply.content_type) {
+        response = response.with_header(header);
+    }
+    let _ = request.respond(response);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get(path: &str) -> Reply {
+        route(&Url::parse("http://localhost").unwrap().join(path).unwrap())
+    }
+
+    #[test]
+    fn rejects_paths_outside_the_projects_directory() {
+        for path in [
+            "/sessions/..%2F..%2Fsecret?project=p",
+            "/sessions/a%5Cb/export?project=p",
+            "/sessions/s1?project=..%2F..%2Fetc",
+            "/sessions/s1/export?project=..",
+            "/sessions/s1?project=a%5Cb",
+        ] {
+            let reply = get(path);
+            assert_eq!(reply.status, 400, "{} was accepted: {}", path, reply.body);
+        }
+    }
+
+    #[test]
+    fn rejects_an_invalid_redact_flag() {
+        assert_eq!(get("/sessions/s1?project=p&redact=maybe").status, 400);
+        assert_eq!(get("/sessions?redact=maybe").status, 400);
+    }
+
+    #[test]
+    fn listed_sessions_are_redacted_unless_opted_out() {
+        const KEY: &str = "sk-ant-REDACTED";
+        let home = dirs::home_dir().unwrap().to_string_lossy().into_owned();
+        let listed = || SessionSummary {
+            id: "s1".to_string(),
+            project: format!("{}/demo", home),
+            project_encoded: "-demo".to_string(),
+            source: "default".to_string(),
+            first_message: format!("use {} for the API", KEY),
+            message_count: 2,
+            start_time: Some(0),
+            end_time: None,
+            git_branch: None,
+            model: None,
+            file_path: format!("{}/.claude/projects/-demo/s1.jsonl", home),
+            usage: TokenUsage::default(),
+            model_usage: Default::default(),
+            usage_slices: Vec::new(),
+            cost_usd: None,
+        };
+        let config = RedactionConfig {
+            anonymize_paths: true,
+            ..RedactionConfig::default()
+        };
+        let query = |url: &str| Query::new(&Url::parse(url).unwrap());
+
+        let mut redactor = text_redactor(&query("http://localhost/sessions"), &config).unwrap();
+        let mut summary = listed();
+        redact_summary(&mut summary, &mut redactor);
+        assert!(
+            !summary.first_message.contains(KEY),
+            "{}",
+            summary.first_message
+        );
+        assert_eq!(summary.project, "~/demo");
+        assert_eq!(summary.file_path, "~/.claude/projects/-demo/s1.jsonl");
+
+        for (url, config) in [
+            ("http://localhost/sessions?redact=false", config.clone()),
+            (
+                "http://localhost/sessions",
+                RedactionConfig {
+                    enabled: false,
+                    ..config.clone()
+                },
+            ),
+        ] {
+            let mut redactor = text_redactor(&query(url), &config).unwrap();
+            let mut summary = listed();
+            redact_summary(&mut summary, &mut redactor);
+            assert!(summary.first_message.contains(KEY));
+            assert!(summary.project.starts_with(&home));
+        }
+    }
+}
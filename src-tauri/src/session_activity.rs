@@ -0,0 +1,391 @@
+use crate::session_monitor::{is_valid_session_id, HookEventPayload};
+use crate::session_state::SessionState;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SUMMARY_MAX_CHARS: usize = 120;
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActivitySettings {
+    /// Keep each session's timeline on disk so it survives restarts
+    pub persist: bool,
+    /// Maximum events kept per session
+    pub capacity: usize,
+    /// Delete saved timelines not written to for this many days; 0 keeps them
+    #[serde(default = "default_retention_days")]
+    pub retention_days: u32,
+}
+
+fn default_retention_days() -> u32 {
+    30
+}
+
+impl Default for ActivitySettings {
+    fn default() -> Self {
+        Self {
+            persist: false,
+            capacity: 200,
+            retention_days: default_retention_days(),
+        }
+    }
+}
+
+/// One hook event in a session's timeline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivityEntry {
+    /// ms since epoch
+    pub timestamp: i64,
+    pub event: String,
+    pub tool_name: Option<String>,
+    pub tool_use_id: Option<String>,
+    /// Short description, e.g. the file for Edit or the command for Bash
+    pub summary: Option<String>,
+    /// State after the event, if it changed the session's state
+    pub state: Option<SessionState>,
+    /// How long the tool call took, for PostToolUse/PostToolUseFailure
+    #[serde(default)]
+    pub duration_ms: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivityEvent {
+    pub session_id: String,
+    pub entry: ActivityEntry,
+}
+
+fn truncate(text: &str) -> String {
+    let line = text.lines().next().unwrap_or_default().trim();
+    if line.chars().count() > SUMMARY_MAX_CHARS {
+        let head: String = line.chars().take(SUMMARY_MAX_CHARS).collect();
+        format!("{}…", head)
+    } else if text.trim().lines().nth(1).is_some() {
+        format!("{}…", line)
+    } else {
+        line.to_string()
+    }
+}
+
+/// The most telling field of a tool's input
+fn tool_summary(tool_name: &str, input: &serde_json::Value) -> Option<String> {
+    let field = match tool_name {
+        "Edit" | "MultiEdit" | "Write" | "Read" => "file_path",
+        "NotebookEdit" => "notebook_path",
+        "Bash" => "command",
+        "Grep" | "Glob" => "pattern",
+        "WebFetch" => "url",
+        "WebSearch" => "query",
+        "Task" => "description",
+        _ => return None,
+    };
+    input.get(field).and_then(|v| v.as_str()).map(truncate)
+}
+
+/// Build a timeline entry from a hook payload
+pub fn entry_from_payload(
+    payload: &HookEventPayload,
+    state: Option<SessionState>,
+    timestamp: i64,
+) -> ActivityEntry {
+    let summary = match payload.hook_event_name.as_str() {
+        "UserPromptSubmit" => payload.prompt.as_deref().map(truncate),
+        "Notification" => payload.message.as_deref().map(truncate),
+        _ => payload
+            .tool_name
+            .as_deref()
+            .zip(payload.tool_input.as_ref())
+            .and_then(|(name, input)| tool_summary(name, input)),
+    };
+    ActivityEntry {
+        timestamp,
+        event: payload.hook_event_name.clone(),
+        tool_name: payload.tool_name.clone(),
+        tool_use_id: payload.tool_use_id.clone(),
+        summary,
+        state,
+        duration_ms: None,
+    }
+}
+
+fn invalid_session_id() -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidInput, "invalid session id")
+}
+
+/// Get the directory persisted timelines are kept in
+pub fn get_activity_dir() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_default()
+        .join("sessionly")
+        .join("activity")
+}
+
+/// A session's recent events and how many lines its saved file holds
+#[derive(Default)]
+struct Timeline {
+    entries: VecDeque<ActivityEntry>,
+    /// Lines in the saved file, which grows by appending until compacted
+    saved_lines: usize,
+}
+
+/// Bounded per-session event timelines
+pub struct ActivityLog {
+    timelines: Mutex<HashMap<String, Timeline>>,
+    settings: Mutex<ActivitySettings>,
+    dir: PathBuf,
+}
+
+impl ActivityLog {
+    pub fn new(settings: ActivitySettings, dir: PathBuf) -> Self {
+        Self {
+            timelines: Mutex::new(HashMap::new()),
+            settings: Mutex::new(settings),
+            dir,
+        }
+    }
+
+    pub fn configure(&self, settings: ActivitySettings) {
+        *self.settings.lock().unwrap() = settings;
+    }
+
+    /// Where a session's timeline is saved; None for ids that aren't plain
+    /// names, which would otherwise resolve outside the activity directory
+    fn file_path(&self, session_id: &str) -> Option<PathBuf> {
+        is_valid_session_id(session_id).then(|| self.dir.join(format!("{}.jsonl", session_id)))
+    }
+
+    /// Read a saved timeline, keeping the last `capacity` entries and
+    /// compacting the file if it held more
+    fn load(&self, session_id: &str, capacity: usize) -> Timeline {
+        let Some(content) = self
+            .file_path(session_id)
+            .and_then(|path| fs::read_to_string(path).ok())
+        else {
+            return Timeline::default();
+        };
+        let mut entries: VecDeque<ActivityEntry> = content
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect();
+        let saved_lines = content.lines().count();
+        while entries.len() > capacity {
+            entries.pop_front();
+        }
+        let mut timeline = Timeline {
+            entries,
+            saved_lines,
+        };
+        if saved_lines > capacity {
+            self.compact(session_id, &mut timeline);
+        }
+        timeline
+    }
+
+    /// Rewrite a session's saved file to hold just its in-memory entries
+    fn compact(&self, session_id: &str, timeline: &mut Timeline) {
+        match self.save(session_id, &timeline.entries) {
+            Ok(()) => timeline.saved_lines = timeline.entries.len(),
+            Err(e) => eprintln!("Failed to save activity for {}: {}", session_id, e),
+        }
+    }
+
+    fn save(&self, session_id: &str, entries: &VecDeque<ActivityEntry>) -> std::io::Result<()> {
+        let path = self.file_path(session_id).ok_or_else(invalid_session_id)?;
+        fs::create_dir_all(&self.dir)?;
+        let mut content = String::new();
+        for entry in entries {
+            content.push_str(&serde_json::to_string(entry)?);
+            content.push('\n');
+        }
+        let tmp_path = path.with_extension("jsonl.tmp");
+        fs::write(&tmp_path, content)?;
+        fs::rename(&tmp_path, &path)
+    }
+
+    fn append(&self, session_id: &str, entry: &ActivityEntry) -> std::io::Result<()> {
+        let path = self.file_path(session_id).ok_or_else(invalid_session_id)?;
+        fs::create_dir_all(&self.dir)?;
+        let mut line = serde_json::to_string(entry)?;
+        line.push('\n');
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?
+            .write_all(line.as_bytes())
+    }
+
+    /// Add an event to a session's timeline. Saved timelines are appended to
+    /// and only rewritten once they hold twice the capacity, so recording
+    /// stays cheap on the hook thread.
+    pub fn record(&self, session_id: &str, entry: ActivityEntry) {
+        let settings = *self.settings.lock().unwrap();
+        let capacity = settings.capacity.max(1);
+        let mut timelines = self.timelines.lock().unwrap();
+        let timeline = timelines.entry(session_id.to_string()).or_insert_with(|| {
+            if settings.persist {
+                self.load(session_id, capacity)
+            } else {
+                Timeline::default()
+            }
+        });
+        if settings.persist {
+            match self.append(session_id, &entry) {
+                Ok(()) => timeline.saved_lines += 1,
+                Err(e) => eprintln!("Failed to save activity for {}: {}", session_id, e),
+            }
+        }
+        timeline.entries.push_back(entry);
+        while timeline.entries.len() > capacity {
+            timeline.entries.pop_front();
+        }
+        if settings.persist && timeline.saved_lines > capacity * 2 {
+            self.compact(session_id, timeline);
+        }
+    }
+
+    /// A session's timeline, oldest first
+    pub fn entries(&self, session_id: &str) -> Vec<ActivityEntry> {
+        if let Some(timeline) = self.timelines.lock().unwrap().get(session_id) {
+            return timeline.entries.iter().cloned().collect();
+        }
+        let settings = *self.settings.lock().unwrap();
+        if settings.persist {
+            return self
+                .load(session_id, settings.capacity.max(1))
+                .entries
+                .into();
+        }
+        Vec::new()
+    }
+
+    /// Forget in-memory timelines of sessions that are neither kept nor active
+    /// since `cutoff` (ms since epoch). Saved timelines stay on disk until they
+    /// are older than the retention period.
+    pub fn retain(&self, keep: impl Fn(&str) -> bool, cutoff: i64) {
+        self.timelines
+            .lock()
+            .unwrap()
+            .retain(|session_id, timeline| {
+                keep(session_id)
+                    || timeline
+                        .entries
+                        .back()
+                        .is_some_and(|entry| entry.timestamp >= cutoff)
+            });
+
+        let retention_days = self.settings.lock().unwrap().retention_days;
+        if retention_days == 0 {
+            return;
+        }
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis() as i64);
+        let expired = now - i64::from(retention_days) * DAY_MS;
+        let Ok(dir) = fs::read_dir(&self.dir) else {
+            return;
+        };
+        for file in dir.flatten() {
+            let path = file.path();
+            let Some(session_id) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            let modified = file
+                .metadata()
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_millis() as i64)
+                .unwrap_or(0);
+            if !keep(session_id) && modified < expired {
+                let _ = fs::remove_file(&path);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SESSION: &str = "0b7c9c1e-4a2f-4d8e-9a51-3f0e6d2c8b17";
+
+    fn entry(timestamp: i64) -> ActivityEntry {
+        ActivityEntry {
+            timestamp,
+            event: "PreToolUse".to_string(),
+            tool_name: Some("Bash".to_string()),
+            tool_use_id: Some(format!("toolu_{}", timestamp)),
+            summary: Some("cargo test".to_string()),
+            state: None,
+            duration_ms: None,
+        }
+    }
+
+    fn settings(persist: bool, capacity: usize) -> ActivitySettings {
+        ActivitySettings {
+            persist,
+            capacity,
+            retention_days: 0,
+        }
+    }
+
+    fn timestamps(entries: &[ActivityEntry]) -> Vec<i64> {
+        entries.iter().map(|e| e.timestamp).collect()
+    }
+
+    #[test]
+    fn record_keeps_the_most_recent_entries_up_to_capacity() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = ActivityLog::new(settings(false, 3), dir.path().to_path_buf());
+        for timestamp in 1..=5 {
+            log.record(SESSION, entry(timestamp));
+        }
+        assert_eq!(timestamps(&log.entries(SESSION)), vec![3, 4, 5]);
+        assert!(fs::read_dir(dir.path()).unwrap().next().is_none());
+    }
+
+    #[test]
+    fn saved_timelines_load_after_a_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = ActivityLog::new(settings(true, 10), dir.path().to_path_buf());
+        log.record(SESSION, entry(1));
+        log.record(SESSION, entry(2));
+
+        let restarted = ActivityLog::new(settings(true, 10), dir.path().to_path_buf());
+        let entries = restarted.entries(SESSION);
+        assert_eq!(timestamps(&entries), vec![1, 2]);
+        assert_eq!(entries[0].summary.as_deref(), Some("cargo test"));
+
+        restarted.record(SESSION, entry(3));
+        assert_eq!(timestamps(&restarted.entries(SESSION)), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn saved_timelines_are_compacted_and_trimmed_on_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(format!("{}.jsonl", SESSION));
+        let log = ActivityLog::new(settings(true, 3), dir.path().to_path_buf());
+        for timestamp in 1..=20 {
+            log.record(SESSION, entry(timestamp));
+            assert!(fs::read_to_string(&path).unwrap().lines().count() <= 6);
+        }
+
+        let restarted = ActivityLog::new(settings(true, 2), dir.path().to_path_buf());
+        assert_eq!(timestamps(&restarted.entries(SESSION)), vec![19, 20]);
+        assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 2);
+    }
+
+    #[test]
+    fn invalid_session_ids_are_never_saved() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = ActivityLog::new(settings(true, 3), dir.path().join("activity"));
+        log.record("../escape", entry(1));
+        assert_eq!(timestamps(&log.entries("../escape")), vec![1]);
+        assert!(!dir.path().join("activity").exists());
+        assert!(!dir.path().join("escape.jsonl").exists());
+    }
+}
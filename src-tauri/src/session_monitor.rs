@@ -1,32 +1,205 @@
+use crate::notification_rules::{Notification, NotificationEngine, NotificationSettings};
+use crate::notifier;
+use crate::session_activity::{self, ActivityEntry, ActivityEvent, ActivityLog, ActivitySettings};
+use crate::session_state::{SessionState, SessionStateInfo};
+use crate::tool_timing::{self, LatencyStats, StuckCall, ToolTimingSettings, ToolTimings};
+use crate::webhooks::{DeliveryRecord, WebhookEndpoint, WebhookEvent, WebhookForwarder, WebhookSettings};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::sync::Mutex;
-use std::time::Instant;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, UNIX_EPOCH};
 use tauri::{AppHandle, Emitter};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "lowercase")]
-pub enum SessionState {
-    Idle,
-    Working,
-    Completed,
-    Error,
+/// How long a session may go without hook events or transcript writes
+/// before it is dropped, depending on whether it is mid-turn
+const ACTIVE_TIMEOUT_MS: i64 = 30 * 60 * 1000;
+const SETTLED_TIMEOUT_MS: i64 = 10 * 60 * 1000;
+
+/// How often tracked sessions are saved, if they changed
+const SAVE_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Transition {
+    state: SessionState,
+    active_subagents: usize,
+}
+
+/// State for a Notification event. Older Claude Code versions send no
+/// `notification_type`, so fall back to the message text. A permission prompt
+/// usually follows a PermissionRequest and keeps the session awaiting permission.
+fn notification_state(payload: &HookEventPayload) -> Option<SessionState> {
+    match payload.notification_type.as_deref() {
+        Some("permission_prompt") => Some(SessionState::AwaitingPermission),
+        Some("idle_prompt" | "elicitation_dialog") => Some(SessionState::AwaitingInput),
+        Some("auth_success") => None,
+        Some(_) => Some(SessionState::Completed),
+        None => {
+            let message = payload.message.as_deref().unwrap_or_default().to_lowercase();
+            if message.contains("permission") {
+                Some(SessionState::AwaitingPermission)
+            } else if message.contains("waiting for your input") {
+                Some(SessionState::AwaitingInput)
+            } else {
+                Some(SessionState::Completed)
+            }
+        }
+    }
 }
 
-#[derive(Debug)]
+/// State after a hook event given the number of running subagents,
+/// or None for events that don't affect state
+fn transition(payload: &HookEventPayload, active_subagents: usize) -> Option<Transition> {
+    // Tool events from inside a subagent arrive with the parent's session id
+    let busy = if active_subagents > 0 {
+        SessionState::Subagent
+    } else {
+        SessionState::Working
+    };
+    let (state, active_subagents) = match payload.hook_event_name.as_str() {
+        "SessionStart" => (SessionState::Idle, 0),
+        "UserPromptSubmit" => (SessionState::Thinking, 0),
+        "PreToolUse" | "PostToolUse" => (busy, active_subagents),
+        "PostToolUseFailure" => (SessionState::Error, active_subagents),
+        "PermissionRequest" => (SessionState::AwaitingPermission, active_subagents),
+        "SubagentStart" => (SessionState::Subagent, active_subagents + 1),
+        "SubagentStop" => {
+            let remaining = active_subagents.saturating_sub(1);
+            let state = if remaining > 0 {
+                SessionState::Subagent
+            } else {
+                SessionState::Working
+            };
+            (state, remaining)
+        }
+        "PreCompact" => (SessionState::Compacting, active_subagents),
+        "Stop" => (SessionState::Completed, 0),
+        "Notification" => (notification_state(payload)?, active_subagents),
+        "SessionEnd" => (SessionState::Ended, 0),
+        _ => return None,
+    };
+    Some(Transition {
+        state,
+        active_subagents,
+    })
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct TrackedSession {
     state: SessionState,
     cwd: Option<String>,
-    last_update: Instant,
+    /// Time of the last hook event, in ms since epoch
+    last_update: i64,
+    #[serde(default)]
+    active_subagents: usize,
+    #[serde(default)]
+    transcript_path: Option<String>,
+    /// When the session entered its current state, in ms since epoch
+    #[serde(default)]
+    state_since: i64,
+    /// When the session was first seen, in ms since epoch
+    #[serde(default)]
+    started_at: i64,
+}
+
+impl TrackedSession {
+    /// Whether the session still looks alive, given its transcript's mtime.
+    /// Without one (e.g. the transcript isn't written yet), the last hook event decides.
+    fn is_live(&self, now: i64, transcript_mtime: Option<i64>) -> bool {
+        let last_activity = transcript_mtime.map_or(self.last_update, |mtime| mtime.max(self.last_update));
+        let timeout = if self.state.is_settled() {
+            SETTLED_TIMEOUT_MS
+        } else {
+            ACTIVE_TIMEOUT_MS
+        };
+        now - last_activity < timeout
+    }
+}
+
+/// Modification time of a transcript, in ms since epoch
+fn transcript_mtime(path: &str) -> Option<i64> {
+    let modified = fs::metadata(path).ok()?.modified().ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_millis() as i64)
+}
+
+/// Locate a session's transcript when the hook payload didn't include it
+fn find_transcript(session_id: &str) -> Option<String> {
+    let file_name = format!("{}.jsonl", session_id);
+    crate::session_store::list_projects()
+        .iter()
+        .map(|project| project.path.join(&file_name))
+        .find(|path| path.exists())
+        .map(|path| path.to_string_lossy().to_string())
+}
+
+/// The encoded project directory a transcript lives in
+fn project_encoded(transcript_path: Option<&str>) -> Option<String> {
+    transcript_path.and_then(|p| {
+        Path::new(p)
+            .parent()
+            .and_then(|dir| dir.file_name())
+            .and_then(|n| n.to_str())
+            .map(String::from)
+    })
+}
+
+/// Extract last path component as project name
+fn project_name(cwd: Option<&str>) -> Option<String> {
+    cwd.and_then(|p| {
+        Path::new(p)
+            .file_name()
+            .and_then(|n| n.to_str())
+            .map(String::from)
+    })
+}
+
+/// Get the persisted monitor state path
+pub fn get_state_path() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_default()
+        .join("sessionly")
+        .join("live-sessions.json")
+}
+
+fn load_sessions(path: &Path) -> HashMap<String, TrackedSession> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_sessions(path: &Path, sessions: &HashMap<String, TrackedSession>) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, serde_json::to_vec(sessions)?)?;
+    fs::rename(&tmp_path, path)
 }
 
+/// A tracked session as reported to the frontend
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct SessionStateInfo {
+pub struct TrackedSessionInfo {
     pub session_id: String,
-    pub prev_state: SessionState,
     pub state: SessionState,
-    pub aggregate_state: SessionState,
     pub project: Option<String>,
+    /// Directory under the projects dir holding the transcript, if known
+    pub project_encoded: Option<String>,
+    /// Label of the data root holding the transcript, if known
+    pub source: Option<String>,
+    pub last_update: i64,
+    /// When the session entered its current state
+    pub state_since: i64,
+}
+
+/// Whether a hook payload's session id is a plain, UUID-style name.
+/// It is used in file names, so anything else is rejected.
+pub fn is_valid_session_id(session_id: &str) -> bool {
+    !session_id.is_empty()
+        && session_id.len() <= 128
+        && session_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
 }
 
 #[derive(Debug, Deserialize)]
@@ -35,99 +208,458 @@ pub struct HookEventPayload {
     pub hook_event_name: String,
     pub tool_name: Option<String>,
     pub cwd: Option<String>,
+    pub transcript_path: Option<String>,
+    pub tool_input: Option<serde_json::Value>,
+    pub tool_use_id: Option<String>,
+    pub prompt: Option<String>,
+    pub notification_type: Option<String>,
+    pub message: Option<String>,
+}
+
+/// Save tracked sessions every SAVE_INTERVAL while they have unsaved changes,
+/// so hook events don't wait on disk writes. Stops once the sessions are dropped.
+fn spawn_saver(sessions: Weak<Mutex<HashMap<String, TrackedSession>>>, unsaved: Arc<AtomicBool>, path: PathBuf) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(SAVE_INTERVAL);
+        let Some(sessions) = sessions.upgrade() else {
+            break;
+        };
+        if unsaved.swap(false, Ordering::Relaxed) {
+            let snapshot = sessions.lock().unwrap().clone();
+            if let Err(e) = save_sessions(&path, &snapshot) {
+                eprintln!("Failed to save session monitor state: {}", e);
+            }
+        }
+    });
 }
 
 pub struct SessionMonitor {
-    sessions: Mutex<HashMap<String, TrackedSession>>,
+    sessions: Arc<Mutex<HashMap<String, TrackedSession>>>,
+    /// Set when `sessions` changed since it was last saved
+    unsaved: Arc<AtomicBool>,
+    activity: ActivityLog,
+    timings: ToolTimings,
+    notifications: NotificationEngine,
+    webhooks: WebhookForwarder,
     app_handle: AppHandle,
 }
 
 impl SessionMonitor {
+    /// Create a monitor, restoring sessions tracked before the last restart
     pub fn new(app_handle: AppHandle) -> Self {
-        Self {
-            sessions: Mutex::new(HashMap::new()),
+        let state_path = get_state_path();
+        let settings = crate::app_settings::load();
+        let sessions = Arc::new(Mutex::new(load_sessions(&state_path)));
+        let unsaved = Arc::new(AtomicBool::new(false));
+        spawn_saver(Arc::downgrade(&sessions), unsaved.clone(), state_path);
+        let monitor = Self {
+            sessions,
+            unsaved,
+            activity: ActivityLog::new(settings.activity, session_activity::get_activity_dir()),
+            timings: ToolTimings::new(settings.tool_timing, tool_timing::get_stats_path()),
+            notifications: NotificationEngine::new(settings.notifications),
+            webhooks: WebhookForwarder::new(settings.webhooks),
             app_handle,
-        }
+        };
+        monitor.prune_stale();
+        monitor
+    }
+
+    /// Have the tracked sessions saved shortly
+    fn persist(&self) {
+        self.unsaved.store(true, Ordering::Relaxed);
     }
 
     pub fn handle_hook_event(&self, payload: HookEventPayload) {
-        let new_state = match payload.hook_event_name.as_str() {
-            "PreToolUse" | "PostToolUse" => SessionState::Working,
-            "PostToolUseFailure" => SessionState::Error,
-            "Stop" | "Notification" => SessionState::Completed,
-            _ => return,
+        let state = self.apply_state_change(&payload);
+        self.webhooks
+            .forward(WebhookEvent::from_hook(&payload, project_name(payload.cwd.as_deref())));
+
+        let now = chrono::Utc::now().timestamp_millis();
+        let mut entry = session_activity::entry_from_payload(&payload, state, now);
+        entry.duration_ms = self.track_tool_timing(&payload, entry.summary.clone(), now);
+        self.activity.record(&payload.session_id, entry.clone());
+        let event = ActivityEvent {
+            session_id: payload.session_id,
+            entry,
         };
+        let _ = self.app_handle.emit("session-activity", &event);
+    }
 
-        let (prev_state, aggregate, cwd) = {
-            let mut sessions = self.sessions.lock().unwrap();
-            let prev_state = sessions
+    /// Pair tool start and end events, returning the duration of a finished call
+    fn track_tool_timing(&self, payload: &HookEventPayload, summary: Option<String>, now: i64) -> Option<i64> {
+        let tool_use_id = payload.tool_use_id.as_deref();
+        match payload.hook_event_name.as_str() {
+            "PreToolUse" => {
+                let tool_name = payload.tool_name.as_deref().unwrap_or_default();
+                self.timings.start(
+                    &payload.session_id,
+                    tool_use_id?,
+                    tool_name,
+                    payload.cwd.clone(),
+                    summary,
+                    now,
+                );
+                None
+            }
+            "PostToolUse" => self.timings.finish(tool_use_id?, false, now),
+            "PostToolUseFailure" => self.timings.finish(tool_use_id?, true, now),
+            // Calls still pending at the end of a turn were interrupted
+            "Stop" | "SessionEnd" => {
+                self.timings.abandon_session(&payload.session_id);
+                None
+            }
+            _ => None,
+        }
+    }
+
+    /// Update the tracked session for a hook event and emit the state change,
+    /// returning the new state if the event affected it
+    fn apply_state_change(&self, payload: &HookEventPayload) -> Option<SessionState> {
+        // Events that never change state don't need the transcript looked up
+        transition(payload, 0)?;
+        // Searching the project directories is slow, so do it before taking the lock
+        let known_transcript = payload.transcript_path.is_some()
+            || self
+                .sessions
+                .lock()
+                .unwrap()
                 .get(&payload.session_id)
-                .map(|s| s.state)
-                .unwrap_or(SessionState::Idle);
-
-            // Update cwd only if the payload provides one
-            let cwd = payload
-                .cwd
-                .or_else(|| sessions.get(&payload.session_id).and_then(|s| s.cwd.clone()));
-
-            sessions.insert(
-                payload.session_id.clone(),
-                TrackedSession {
-                    state: new_state,
-                    cwd: cwd.clone(),
-                    last_update: Instant::now(),
-                },
-            );
+                .is_some_and(|s| s.transcript_path.is_some());
+        let found_transcript = if known_transcript {
+            None
+        } else {
+            find_transcript(&payload.session_id)
+        };
+
+        let (prev_state, new_state, aggregate, cwd, started_at) = {
+            let mut sessions = self.sessions.lock().unwrap();
+            let existing = sessions.get(&payload.session_id);
+            let prev_state = existing.map(|s| s.state).unwrap_or(SessionState::Idle);
+            let active_subagents = existing.map(|s| s.active_subagents).unwrap_or(0);
+
+            let next = transition(payload, active_subagents)?;
+
+            // Update cwd and transcript only if the payload provides them
+            let cwd = payload.cwd.clone().or_else(|| existing.and_then(|s| s.cwd.clone()));
+            let transcript_path = payload
+                .transcript_path
+                .clone()
+                .or_else(|| existing.and_then(|s| s.transcript_path.clone()))
+                .or(found_transcript);
+
+            let now = chrono::Utc::now().timestamp_millis();
+            let state_since = match existing {
+                Some(s) if s.state == next.state && s.state_since > 0 => s.state_since,
+                _ => now,
+            };
+            let started_at = existing.map(|s| s.started_at).filter(|&t| t > 0).unwrap_or(now);
+
+            if next.state == SessionState::Ended {
+                sessions.remove(&payload.session_id);
+            } else {
+                sessions.insert(
+                    payload.session_id.clone(),
+                    TrackedSession {
+                        state: next.state,
+                        cwd: cwd.clone(),
+                        last_update: now,
+                        active_subagents: next.active_subagents,
+                        transcript_path,
+                        state_since,
+                        started_at,
+                    },
+                );
+            }
+            self.persist();
 
             let aggregate = self.compute_aggregate(&sessions);
-            (prev_state, aggregate, cwd)
-        }; // Lock dropped here before any I/O
-
-        // Extract last path component as project name
-        let project = cwd.as_deref().and_then(|p| {
-            std::path::Path::new(p)
-                .file_name()
-                .and_then(|n| n.to_str())
-                .map(String::from)
-        });
+            (prev_state, next.state, aggregate, cwd, started_at)
+        }; // Lock dropped here before emitting
+
+        let project = project_name(cwd.as_deref());
 
         let info = SessionStateInfo {
-            session_id: payload.session_id,
+            session_id: payload.session_id.clone(),
             prev_state,
             state: new_state,
             aggregate_state: aggregate,
             project,
+            message: payload.message.clone(),
         };
         let _ = self.app_handle.emit("session-state-changed", &info);
+        self.webhooks.forward(WebhookEvent::from_state_change(&info));
+
+        let now = chrono::Local::now();
+        let session_age_ms = now.timestamp_millis() - started_at;
+        if let Some(notification) = self.notifications.evaluate(&info, session_age_ms, now) {
+            self.deliver(notification);
+        }
+        Some(new_state)
     }
 
-    fn compute_aggregate(&self, sessions: &HashMap<String, TrackedSession>) -> SessionState {
-        let mut has_error = false;
-        let mut has_working = false;
-        let mut has_completed = false;
-
-        for s in sessions.values() {
-            match s.state {
-                SessionState::Error => has_error = true,
-                SessionState::Working => has_working = true,
-                SessionState::Completed => has_completed = true,
-                SessionState::Idle => {}
+    /// Show a notification natively without holding up the hook response,
+    /// reporting delivery failures as a `notification-failed` event
+    fn deliver(&self, notification: Notification) {
+        let _ = self.app_handle.emit("session-notification", &notification);
+        let app_handle = self.app_handle.clone();
+        std::thread::spawn(move || {
+            if let Err(e) = notifier::notify(&app_handle, &notification.title, &notification.body) {
+                eprintln!("Failed to deliver notification: {}", e);
+                let _ = app_handle.emit("notification-failed", &e);
             }
-        }
+        });
+    }
 
-        if has_error {
-            SessionState::Error
-        } else if has_working {
-            SessionState::Working
-        } else if has_completed {
-            SessionState::Completed
-        } else {
-            SessionState::Idle
+    /// Recent hook events for a session, oldest first
+    pub fn activity(&self, session_id: &str) -> Vec<ActivityEntry> {
+        self.activity.entries(session_id)
+    }
+
+    pub fn configure_activity(&self, settings: ActivitySettings) {
+        self.activity.configure(settings);
+    }
+
+    /// Per-tool call durations for one project directory, or across all projects
+    pub fn tool_latency(&self, project: Option<&str>) -> BTreeMap<String, LatencyStats> {
+        self.timings.latency(project)
+    }
+
+    /// Tool calls running past the stuck threshold
+    pub fn stuck_tool_calls(&self) -> Vec<StuckCall> {
+        self.timings.stuck_calls(chrono::Utc::now().timestamp_millis(), false)
+    }
+
+    pub fn configure_tool_timing(&self, settings: ToolTimingSettings) {
+        self.timings.configure(settings);
+    }
+
+    pub fn configure_notifications(&self, settings: NotificationSettings) {
+        self.notifications.configure(settings);
+    }
+
+    pub fn configure_webhooks(&self, settings: WebhookSettings) {
+        self.webhooks.configure(settings);
+    }
+
+    /// Deliver a sample event to an endpoint, blocking until done
+    pub fn test_webhook(&self, endpoint: &WebhookEndpoint) -> DeliveryRecord {
+        self.webhooks.test(endpoint)
+    }
+
+    /// Recent webhook deliveries, newest first
+    pub fn webhook_deliveries(&self) -> Vec<DeliveryRecord> {
+        self.webhooks.deliveries()
+    }
+
+    /// Emit an event for each tool call that newly crossed the stuck threshold
+    pub fn check_stuck_tool_calls(&self) {
+        let now = chrono::Utc::now().timestamp_millis();
+        for call in self.timings.stuck_calls(now, true) {
+            let _ = self.app_handle.emit("tool-call-stuck", &call);
         }
     }
 
+    fn compute_aggregate(&self, sessions: &HashMap<String, TrackedSession>) -> SessionState {
+        sessions
+            .values()
+            .map(|s| s.state)
+            .max_by_key(|state| state.priority())
+            .unwrap_or(SessionState::Idle)
+    }
+
+    /// Currently tracked sessions, most recently updated first
+    pub fn sessions(&self) -> Vec<TrackedSessionInfo> {
+        let sessions = self.sessions.lock().unwrap();
+        let mut infos: Vec<TrackedSessionInfo> = sessions
+            .iter()
+            .map(|(id, s)| TrackedSessionInfo {
+                session_id: id.clone(),
+                state: s.state,
+                project: project_name(s.cwd.as_deref()),
+                project_encoded: project_encoded(s.transcript_path.as_deref()),
+                source: s
+                    .transcript_path
+                    .as_deref()
+                    .map(|p| crate::session_store::source_for_path(Path::new(p))),
+                last_update: s.last_update,
+                // Sessions persisted before state_since was tracked
+                state_since: if s.state_since > 0 { s.state_since } else { s.last_update },
+            })
+            .collect();
+        infos.sort_by_key(|info| std::cmp::Reverse(info.last_update));
+        infos
+    }
+
+    /// Drop sessions with no recent hook events or transcript writes, emitting
+    /// `session-state-changed` as `ended` for each so listeners stop showing them
     pub fn prune_stale(&self) {
+        let now = chrono::Utc::now().timestamp_millis();
+        // Sessions stale by their last hook event may still be writing their
+        // transcript. Its mtime is read without holding the lock, so hook
+        // events aren't held up by the filesystem.
+        let candidates: Vec<(String, Option<String>)> = self
+            .sessions
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, s)| !s.is_live(now, None))
+            .map(|(id, s)| (id.clone(), s.transcript_path.clone()))
+            .collect();
+        let mtimes: HashMap<String, Option<i64>> = candidates
+            .into_iter()
+            .map(|(id, path)| (id, path.as_deref().and_then(transcript_mtime)))
+            .collect();
+
         let mut sessions = self.sessions.lock().unwrap();
-        sessions.retain(|_, s| s.last_update.elapsed().as_secs() < 60);
+        let mut pruned: Vec<(String, TrackedSession)> = Vec::new();
+        sessions.retain(|id, s| {
+            let live = s.is_live(now, mtimes.get(id).copied().flatten());
+            if !live {
+                pruned.push((id.clone(), s.clone()));
+            }
+            live
+        });
+        if !pruned.is_empty() {
+            self.persist();
+        }
+        let live: HashSet<String> = sessions.keys().cloned().collect();
+        let aggregate = self.compute_aggregate(&sessions);
+        drop(sessions);
+
+        // Keep ended sessions' timelines around as long as settled sessions
+        self.activity.retain(|id| live.contains(id), now - SETTLED_TIMEOUT_MS);
+        self.timings.retain_sessions(|id| live.contains(id));
+
+        for (session_id, session) in pruned {
+            let info = SessionStateInfo {
+                session_id,
+                prev_state: session.state,
+                state: SessionState::Ended,
+                aggregate_state: aggregate,
+                project: project_name(session.cwd.as_deref()),
+                message: None,
+            };
+            let _ = self.app_handle.emit("session-state-changed", &info);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(name: &str, notification_type: Option<&str>, message: Option<&str>) -> HookEventPayload {
+        serde_json::from_value(serde_json::json!({
+            "session_id": "s1",
+            "hook_event_name": name,
+            "notification_type": notification_type,
+            "message": message,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn session_ids_must_be_plain_names() {
+        assert!(is_valid_session_id("5f0c8a10-3b1e-4c8a-9d2f-7a6e1b2c3d4e"));
+        for id in ["", "../../x", "a/b", "a\\b", "..", "s1.jsonl", "s 1", &"a".repeat(129)] {
+            assert!(!is_valid_session_id(id), "{:?} was accepted", id);
+        }
+    }
+
+    #[test]
+    fn session_without_transcript_yet_is_live_until_it_times_out() {
+        let session = TrackedSession {
+            state: SessionState::Working,
+            cwd: None,
+            last_update: 1_000,
+            active_subagents: 0,
+            transcript_path: Some("/missing/s1.jsonl".to_string()),
+            state_since: 1_000,
+            started_at: 1_000,
+        };
+        assert!(session.is_live(1_000 + ACTIVE_TIMEOUT_MS - 1, None));
+        assert!(!session.is_live(1_000 + ACTIVE_TIMEOUT_MS, None));
+        assert!(session.is_live(1_000 + ACTIVE_TIMEOUT_MS, Some(2_000)));
+    }
+
+    #[test]
+    fn sessions_with_a_tool_error_time_out_like_active_ones() {
+        let session = |state| TrackedSession {
+            state,
+            cwd: None,
+            last_update: 0,
+            active_subagents: 0,
+            transcript_path: None,
+            state_since: 0,
+            started_at: 0,
+        };
+        assert!(!session(SessionState::Completed).is_live(SETTLED_TIMEOUT_MS, None));
+        assert!(session(SessionState::Error).is_live(SETTLED_TIMEOUT_MS, None));
+        assert!(!session(SessionState::Error).is_live(ACTIVE_TIMEOUT_MS, None));
+    }
+
+    /// (event, notification_type, message, running subagents) -> (state, running subagents)
+    type Case = (&'static str, Option<&'static str>, Option<&'static str>, usize, Option<(SessionState, usize)>);
+
+    #[test]
+    fn transition_table() {
+        use SessionState::*;
+        let cases: &[Case] = &[
+            ("SessionStart", None, None, 2, Some((Idle, 0))),
+            ("UserPromptSubmit", None, None, 1, Some((Thinking, 0))),
+            ("PreToolUse", None, None, 0, Some((Working, 0))),
+            ("PreToolUse", None, None, 1, Some((Subagent, 1))),
+            ("PostToolUse", None, None, 0, Some((Working, 0))),
+            ("PostToolUse", None, None, 2, Some((Subagent, 2))),
+            ("PostToolUseFailure", None, None, 1, Some((Error, 1))),
+            ("PermissionRequest", None, None, 0, Some((AwaitingPermission, 0))),
+            ("SubagentStart", None, None, 0, Some((Subagent, 1))),
+            ("SubagentStart", None, None, 1, Some((Subagent, 2))),
+            ("SubagentStop", None, None, 2, Some((Subagent, 1))),
+            ("SubagentStop", None, None, 1, Some((Working, 0))),
+            ("SubagentStop", None, None, 0, Some((Working, 0))),
+            ("PreCompact", None, None, 1, Some((Compacting, 1))),
+            ("Stop", None, None, 1, Some((Completed, 0))),
+            ("SessionEnd", None, None, 1, Some((Ended, 0))),
+            ("Notification", Some("permission_prompt"), None, 0, Some((AwaitingPermission, 0))),
+            ("Notification", Some("permission_prompt"), None, 1, Some((AwaitingPermission, 1))),
+            ("Notification", Some("idle_prompt"), None, 1, Some((AwaitingInput, 1))),
+            ("Notification", Some("elicitation_dialog"), None, 0, Some((AwaitingInput, 0))),
+            ("Notification", Some("auth_success"), None, 0, None),
+            ("Notification", Some("something_new"), None, 0, Some((Completed, 0))),
+            (
+                "Notification",
+                None,
+                Some("Claude needs your permission to use Bash"),
+                0,
+                Some((AwaitingPermission, 0)),
+            ),
+            (
+                "Notification",
+                None,
+                Some("Claude is waiting for your input"),
+                0,
+                Some((AwaitingInput, 0)),
+            ),
+            ("Notification", None, Some("Task finished"), 0, Some((Completed, 0))),
+            ("Notification", None, None, 0, Some((Completed, 0))),
+            ("UnknownEvent", None, None, 1, None),
+        ];
+        for &(name, notification_type, message, subagents, expected) in cases {
+            let expected = expected.map(|(state, active_subagents)| Transition {
+                state,
+                active_subagents,
+            });
+            assert_eq!(
+                transition(&event(name, notification_type, message), subagents),
+                expected,
+                "{} {:?} {:?} with {} subagents",
+                name,
+                notification_type,
+                message,
+                subagents
+            );
+        }
     }
 }
@@ -0,0 +1,60 @@
+//! States the session monitor tracks and the changes it reports.
+//!
+//! Kept free of Tauri so notification rules and webhooks can use them without a GUI.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionState {
+    Idle,
+    /// Prompt submitted, no tool use yet
+    Thinking,
+    Working,
+    /// Blocked on a permission prompt
+    AwaitingPermission,
+    /// Claude Code notified that it is waiting on the user
+    AwaitingInput,
+    /// A subagent is running
+    Subagent,
+    Compacting,
+    Completed,
+    Error,
+    /// Only reported in the final event before the session is dropped
+    Ended,
+}
+
+impl SessionState {
+    /// Precedence when summarizing several sessions into one state
+    pub fn priority(self) -> u8 {
+        match self {
+            SessionState::Ended => 0,
+            SessionState::Idle => 1,
+            SessionState::Completed => 2,
+            SessionState::Compacting => 3,
+            SessionState::Thinking => 4,
+            SessionState::Subagent => 5,
+            SessionState::Working => 6,
+            SessionState::Error => 7,
+            SessionState::AwaitingPermission => 8,
+            SessionState::AwaitingInput => 9,
+        }
+    }
+
+    /// Whether the session is settled between turns rather than mid-turn.
+    /// A tool error doesn't end the turn, so `Error` is not settled.
+    pub fn is_settled(self) -> bool {
+        matches!(self, SessionState::Idle | SessionState::Completed | SessionState::Ended)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionStateInfo {
+    pub session_id: String,
+    pub prev_state: SessionState,
+    pub state: SessionState,
+    pub aggregate_state: SessionState,
+    pub project: Option<String>,
+    /// Notification text, for Notification events
+    pub message: Option<String>,
+}
@@ -0,0 +1,500 @@
+use crate::pricing::PriceTable;
+use crate::session_store::{self, SessionTail, TailUpdate};
+use crate::session_types::SubagentSession;
+use notify::{EventKind, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+/// Quiet period used to coalesce bursts of writes to the same file
+const DEBOUNCE: Duration = Duration::from_millis(300);
+/// Upper bound on how long changes are held back while writes keep arriving
+const MAX_DELAY: Duration = Duration::from_secs(1);
+/// How often to check whether a missing `projects/` directory has appeared
+const RETRY_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionRemoved {
+    #[serde(rename = "sessionId")]
+    pub session_id: String,
+    #[serde(rename = "projectEncoded")]
+    pub project_encoded: String,
+    pub source: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionMessagesAppended {
+    #[serde(rename = "sessionId")]
+    pub session_id: String,
+    #[serde(rename = "projectEncoded")]
+    pub project_encoded: String,
+    pub source: String,
+    #[serde(flatten)]
+    pub update: TailUpdate,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SubagentUpdated {
+    #[serde(rename = "sessionId")]
+    pub session_id: String,
+    #[serde(rename = "projectEncoded")]
+    pub project_encoded: String,
+    pub source: String,
+    pub subagent: SubagentSession,
+}
+
+/// A session the frontend currently has open and wants tailed
+#[derive(Debug)]
+struct OpenSession {
+    project_encoded: String,
+    tail: SessionTail,
+}
+
+/// (source, session id); the same session can be open from several data roots
+type SessionKey = (String, String);
+
+/// Incremental readers for the sessions open in the frontend
+#[derive(Debug, Default)]
+pub struct OpenSessions {
+    sessions: Mutex<HashMap<SessionKey, OpenSession>>,
+}
+
+impl OpenSessions {
+    /// Start tailing a session from its current end. The caller has already
+    /// loaded `known_messages` messages with `get_session`; whatever was
+    /// appended since is returned so nothing written in between is lost.
+    pub fn open(
+        &self,
+        session_id: &str,
+        project_encoded: &str,
+        source: Option<&str>,
+        known_messages: usize,
+    ) -> TailUpdate {
+        let Some(file_path) = session_store::session_file_path(session_id, project_encoded, source)
+        else {
+            return TailUpdate::default();
+        };
+        let source = session_store::source_for_path(&file_path);
+        let mut tail = SessionTail::new(&file_path);
+        let update = match tail.read_new() {
+            Ok(update) => catch_up(update, known_messages),
+            Err(e) => {
+                eprintln!("Failed to read session {}: {}", session_id, e);
+                TailUpdate::default()
+            }
+        };
+
+        self.sessions.lock().unwrap().insert(
+            (source, session_id.to_string()),
+            OpenSession {
+                project_encoded: project_encoded.to_string(),
+                tail,
+            },
+        );
+        update
+    }
+
+    /// Stop tailing a session, returning its file if it was open
+    pub fn close(&self, source: &str, session_id: &str) -> Option<PathBuf> {
+        self.sessions
+            .lock()
+            .unwrap()
+            .remove(&(source.to_string(), session_id.to_string()))
+            .map(|open| open.tail.file_path().to_path_buf())
+    }
+
+    /// Read whatever was appended to an open session since the last read and
+    /// emit it the same way the watcher does, so listeners see every update
+    pub fn refresh(&self, app_handle: &AppHandle, source: &str, session_id: &str) {
+        let mut sessions = self.sessions.lock().unwrap();
+        let key = (source.to_string(), session_id.to_string());
+        if let Some(open) = sessions.get_mut(&key) {
+            publish_appended(app_handle, &key, open);
+        }
+    }
+}
+
+/// Drop the first `known` messages of a full read, keeping their tool results
+/// in case some arrived after the caller loaded them. A file with fewer
+/// messages than that was rewritten, so the whole read is returned as a reset.
+fn catch_up(mut update: TailUpdate, known: usize) -> TailUpdate {
+    if update.messages.len() < known {
+        update.reset = true;
+        return update;
+    }
+    for message in update.messages.drain(..known) {
+        update.tool_results.extend(message.tool_results);
+    }
+    update
+}
+
+/// Emit a session's summary with its cost, so the session list can update
+pub fn emit_summary(app_handle: &AppHandle, path: &Path, prices: &PriceTable) {
+    if let Some(mut summary) = session_store::get_session_summary(path) {
+        summary.cost_usd = prices.cost_of(&summary.model_usage);
+        let _ = app_handle.emit("session-summary-updated", &summary);
+    }
+}
+
+/// Read new content of an open session and emit it, along with subagents
+/// whose files were written before the main session linked them
+fn publish_appended(app_handle: &AppHandle, key: &SessionKey, open: &mut OpenSession) {
+    let (source, session_id) = key;
+    let update = match open.tail.read_new() {
+        Ok(update) => update,
+        Err(e) => {
+            eprintln!("Failed to read session {}: {}", session_id, e);
+            return;
+        }
+    };
+
+    if let Some(project_dir) = open.tail.file_path().parent() {
+        for (agent_id, parent_tool_use_id) in &update.agent_links {
+            let file_name = format!("agent-{}.jsonl", agent_id);
+            let sub_path = project_dir.join(session_id).join("subagents").join(&file_name);
+            let sub_path = if sub_path.exists() { sub_path } else { project_dir.join(&file_name) };
+            emit_subagent(
+                app_handle,
+                session_id,
+                &open.project_encoded,
+                source,
+                agent_id,
+                parent_tool_use_id,
+                &sub_path,
+            );
+        }
+    }
+
+    if update.reset || !update.messages.is_empty() || !update.tool_results.is_empty() {
+        let _ = app_handle.emit(
+            "session-messages-appended",
+            SessionMessagesAppended {
+                session_id: session_id.to_string(),
+                project_encoded: open.project_encoded.clone(),
+                source: source.clone(),
+                update,
+            },
+        );
+    }
+}
+
+enum ChangedFile {
+    /// `{project}/{session}.jsonl`
+    Session {
+        source: String,
+        project_encoded: String,
+        session_id: String,
+    },
+    /// `{project}/{session}/subagents/agent-{id}.jsonl`, or the legacy
+    /// `{project}/agent-{id}.jsonl` where the session is unknown
+    Subagent {
+        source: String,
+        project_encoded: String,
+        session_id: Option<String>,
+        agent_id: String,
+    },
+}
+
+/// A watched `projects/` directory and the label of its data root
+struct WatchedDir {
+    source: String,
+    projects_dir: PathBuf,
+}
+
+fn classify(dirs: &[WatchedDir], path: &Path) -> Option<ChangedFile> {
+    let (source, relative) = dirs
+        .iter()
+        .filter_map(|dir| Some((&dir.source, path.strip_prefix(&dir.projects_dir).ok()?)))
+        .max_by_key(|(_, relative)| std::cmp::Reverse(relative.components().count()))?;
+    let source = source.clone();
+    let parts: Vec<&str> = relative.iter().filter_map(|p| p.to_str()).collect();
+    let file_name = *parts.last()?;
+    let stem = file_name.strip_suffix(".jsonl")?;
+
+    match parts.as_slice() {
+        [project, _] => match stem.strip_prefix("agent-") {
+            Some(agent_id) => Some(ChangedFile::Subagent {
+                source,
+                project_encoded: project.to_string(),
+                session_id: None,
+                agent_id: agent_id.to_string(),
+            }),
+            None => Some(ChangedFile::Session {
+                source,
+                project_encoded: project.to_string(),
+                session_id: stem.to_string(),
+            }),
+        },
+        [project, session, "subagents", _] => Some(ChangedFile::Subagent {
+            source,
+            project_encoded: project.to_string(),
+            session_id: Some(session.to_string()),
+            agent_id: stem.strip_prefix("agent-")?.to_string(),
+        }),
+        _ => None,
+    }
+}
+
+/// Watches the `projects/` directory of every data root and pushes session
+/// changes to the frontend
+pub struct SessionWatcher {
+    _watcher: Arc<Mutex<notify::RecommendedWatcher>>,
+}
+
+impl SessionWatcher {
+    pub fn start(app_handle: AppHandle, open: Arc<OpenSessions>) -> Result<Self, String> {
+        let (tx, rx) = mpsc::channel::<notify::Result<notify::Event>>();
+
+        let mut watcher = notify::recommended_watcher(tx)
+            .map_err(|e| format!("Failed to create watcher: {}", e))?;
+        let mut dirs = Vec::new();
+        // Directories that don't exist yet, e.g. before Claude Code's first run
+        let mut missing = Vec::new();
+        for root in session_store::data_roots() {
+            let projects_dir = root.projects_dir();
+            if !projects_dir.is_dir() {
+                missing.push(projects_dir.clone());
+            } else if let Err(e) = watcher.watch(&projects_dir, RecursiveMode::Recursive) {
+                eprintln!("Failed to watch {}: {}", projects_dir.display(), e);
+            }
+            dirs.push(WatchedDir {
+                source: root.label,
+                projects_dir,
+            });
+        }
+        let watcher = Arc::new(Mutex::new(watcher));
+        let retry_watcher = Arc::downgrade(&watcher);
+
+        std::thread::spawn(move || {
+            let mut pending: HashSet<PathBuf> = HashSet::new();
+            // Session files whose summary is out of date
+            let mut stale_summaries: HashSet<PathBuf> = HashSet::new();
+            let mut last_flush = Instant::now();
+            let mut last_retry = Instant::now();
+            loop {
+                if !missing.is_empty() && last_retry.elapsed() >= RETRY_INTERVAL {
+                    last_retry = Instant::now();
+                    let Some(watcher) = retry_watcher.upgrade() else {
+                        break;
+                    };
+                    let mut watcher = watcher.lock().unwrap();
+                    missing.retain(|dir| {
+                        if !dir.is_dir() {
+                            return true;
+                        }
+                        let result = watcher.watch(dir, RecursiveMode::Recursive);
+                        if let Err(e) = &result {
+                            eprintln!("Failed to watch {}: {}", dir.display(), e);
+                        }
+                        result.is_err()
+                    });
+                }
+
+                let quiet = match rx.recv_timeout(DEBOUNCE) {
+                    Ok(Ok(event)) => {
+                        if !matches!(event.kind, EventKind::Access(_)) {
+                            pending.extend(
+                                event
+                                    .paths
+                                    .into_iter()
+                                    .filter(|p| p.extension().is_some_and(|e| e == "jsonl")),
+                            );
+                        }
+                        false
+                    }
+                    Ok(Err(e)) => {
+                        eprintln!("Session watcher error: {}", e);
+                        false
+                    }
+                    Err(mpsc::RecvTimeoutError::Timeout) => true,
+                    Err(mpsc::RecvTimeoutError::Disconnected) => break,
+                };
+
+                if !pending.is_empty() && (quiet || last_flush.elapsed() >= MAX_DELAY) {
+                    for path in pending.drain() {
+                        let Some(changed) = classify(&dirs, &path) else {
+                            continue;
+                        };
+                        if handle_change(&app_handle, &open, &path, changed) {
+                            stale_summaries.insert(path);
+                        } else {
+                            stale_summaries.remove(&path);
+                        }
+                    }
+                    last_flush = Instant::now();
+                }
+
+                // A summary re-parses the whole file, so wait until writes stop
+                if quiet && !stale_summaries.is_empty() {
+                    let prices = crate::app_settings::load().pricing;
+                    for path in stale_summaries.drain() {
+                        emit_summary(&app_handle, &path, &prices);
+                    }
+                }
+            }
+        });
+
+        Ok(Self { _watcher: watcher })
+    }
+}
+
+/// Publish a change, returning whether the session's summary needs
+/// recomputing. Sessions being tailed are left until they're closed, since
+/// their messages are already streamed to the frontend.
+fn handle_change(
+    app_handle: &AppHandle,
+    open: &OpenSessions,
+    path: &Path,
+    changed: ChangedFile,
+) -> bool {
+    match changed {
+        ChangedFile::Session {
+            source,
+            project_encoded,
+            session_id,
+        } => {
+            if !path.exists() {
+                open.close(&source, &session_id);
+                let _ = app_handle.emit(
+                    "session-removed",
+                    SessionRemoved {
+                        session_id,
+                        project_encoded,
+                        source,
+                    },
+                );
+                return false;
+            }
+
+            let mut sessions = open.sessions.lock().unwrap();
+            let key = (source, session_id);
+            match sessions.get_mut(&key) {
+                Some(tailed) => {
+                    publish_appended(app_handle, &key, tailed);
+                    false
+                }
+                None => true,
+            }
+        }
+        ChangedFile::Subagent {
+            source,
+            project_encoded,
+            session_id,
+            agent_id,
+        } => {
+            let sessions = open.sessions.lock().unwrap();
+            let owner = sessions.iter().find(|((tailed_source, id), tailed)| {
+                *tailed_source == source
+                    && tailed.project_encoded == project_encoded
+                    && session_id.as_ref().is_none_or(|s| s == id)
+                    && tailed.tail.agent_links().contains_key(&agent_id)
+            });
+            if let Some(((_, owner_id), tailed)) = owner {
+                let parent_tool_use_id = &tailed.tail.agent_links()[&agent_id];
+                emit_subagent(
+                    app_handle,
+                    owner_id,
+                    &project_encoded,
+                    &source,
+                    &agent_id,
+                    parent_tool_use_id,
+                    path,
+                );
+            }
+            false
+        }
+    }
+}
+
+fn emit_subagent(
+    app_handle: &AppHandle,
+    session_id: &str,
+    project_encoded: &str,
+    source: &str,
+    agent_id: &str,
+    parent_tool_use_id: &str,
+    path: &Path,
+) {
+    let messages = session_store::parse_subagent_file(path);
+    if messages.is_empty() {
+        return;
+    }
+    let _ = app_handle.emit(
+        "session-subagent-updated",
+        SubagentUpdated {
+            session_id: session_id.to_string(),
+            project_encoded: project_encoded.to_string(),
+            source: source.to_string(),
+            subagent: SubagentSession {
+                agent_id: agent_id.to_string(),
+                parent_tool_use_id: parent_tool_use_id.to_string(),
+                message_count: messages.len(),
+                messages,
+            },
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session_types::{ProcessedMessage, ToolResultBlock};
+
+    fn message(uuid: &str) -> ProcessedMessage {
+        ProcessedMessage {
+            uuid: uuid.to_string(),
+            parent_uuid: None,
+            timestamp: String::new(),
+            role: "assistant".to_string(),
+            text_content: String::new(),
+            thinking_blocks: Vec::new(),
+            tool_use_blocks: Vec::new(),
+            tool_results: HashMap::new(),
+            model: None,
+            message_id: None,
+            usage: None,
+        }
+    }
+
+    fn read(uuids: &[&str]) -> TailUpdate {
+        TailUpdate {
+            messages: uuids.iter().map(|uuid| message(uuid)).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn catch_up_returns_messages_appended_after_the_load() {
+        let mut full = read(&["u1", "u2", "u3"]);
+        let result = ToolResultBlock {
+            block_type: "tool_result".to_string(),
+            tool_use_id: "t1".to_string(),
+            content: serde_json::json!("done"),
+            is_error: None,
+        };
+        full.messages[0].tool_results.insert("t1".to_string(), result);
+
+        let update = catch_up(full, 2);
+        assert!(!update.reset);
+        let uuids: Vec<&str> = update.messages.iter().map(|m| m.uuid.as_str()).collect();
+        assert_eq!(uuids, ["u3"]);
+        // A result that may have arrived after the load is passed on
+        assert!(update.tool_results.contains_key("t1"));
+    }
+
+    #[test]
+    fn catch_up_is_empty_when_nothing_was_appended() {
+        let update = catch_up(read(&["u1", "u2"]), 2);
+        assert!(!update.reset && update.messages.is_empty());
+    }
+
+    #[test]
+    fn catch_up_resets_a_file_that_shrank() {
+        let update = catch_up(read(&["u1"]), 2);
+        assert!(update.reset);
+        assert_eq!(update.messages.len(), 1);
+    }
+}
@@ -0,0 +1,361 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+
+const UNKNOWN_PROJECT: &str = "(unknown)";
+
+/// How often latency stats are saved, if they changed
+const SAVE_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolTimingSettings {
+    /// Tool calls running longer than this are reported as stuck
+    pub stuck_threshold_secs: u64,
+}
+
+impl Default for ToolTimingSettings {
+    fn default() -> Self {
+        Self {
+            stuck_threshold_secs: 300,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct PendingCall {
+    session_id: String,
+    tool_name: String,
+    /// Working directory of the session, identifying its project
+    cwd: Option<String>,
+    started_at: i64,
+    summary: Option<String>,
+    reported_stuck: bool,
+}
+
+/// A tool call with no PostToolUse after the stuck threshold
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StuckCall {
+    pub session_id: String,
+    pub tool_use_id: String,
+    pub tool_name: String,
+    /// Project name, the last component of `cwd`
+    pub project: Option<String>,
+    pub cwd: Option<String>,
+    pub summary: Option<String>,
+    pub started_at: i64,
+    pub elapsed_ms: i64,
+}
+
+/// Durations of completed calls of one tool
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LatencyStats {
+    pub count: u64,
+    pub failures: u64,
+    pub total_ms: i64,
+    pub min_ms: Option<i64>,
+    pub max_ms: Option<i64>,
+    #[serde(skip_deserializing)]
+    pub avg_ms: Option<f64>,
+}
+
+impl LatencyStats {
+    fn add(&mut self, duration_ms: i64, failed: bool) {
+        self.count += 1;
+        if failed {
+            self.failures += 1;
+        }
+        self.total_ms += duration_ms;
+        self.min_ms = Some(self.min_ms.map_or(duration_ms, |min| min.min(duration_ms)));
+        self.max_ms = Some(self.max_ms.map_or(duration_ms, |max| max.max(duration_ms)));
+    }
+
+    fn merge(&mut self, other: &LatencyStats) {
+        self.count += other.count;
+        self.failures += other.failures;
+        self.total_ms += other.total_ms;
+        self.min_ms = match (self.min_ms, other.min_ms) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        self.max_ms = match (self.max_ms, other.max_ms) {
+            (Some(a), Some(b)) => Some(a.max(b)),
+            (a, b) => a.or(b),
+        };
+    }
+
+    fn finish(mut self) -> Self {
+        self.avg_ms = (self.count > 0).then(|| self.total_ms as f64 / self.count as f64);
+        self
+    }
+}
+
+/// Project directory -> tool -> stats
+type LatencyTable = BTreeMap<String, BTreeMap<String, LatencyStats>>;
+
+/// Get the persisted latency statistics path
+pub fn get_stats_path() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_default()
+        .join("sessionly")
+        .join("tool-latency.json")
+}
+
+fn load_stats(path: &Path) -> LatencyTable {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_stats(path: &Path, stats: &LatencyTable) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, serde_json::to_vec(stats)?)?;
+    fs::rename(&tmp_path, path)
+}
+
+/// Save the stats if they changed since they were last saved
+fn save_if_changed(stats: &Mutex<LatencyTable>, unsaved: &AtomicBool, path: &Path) {
+    if unsaved.swap(false, Ordering::Relaxed) {
+        let snapshot = stats.lock().unwrap().clone();
+        if let Err(e) = save_stats(path, &snapshot) {
+            eprintln!("Failed to save tool latency stats: {}", e);
+        }
+    }
+}
+
+/// Save the stats every SAVE_INTERVAL while they have unsaved changes, so
+/// finishing a call doesn't wait on disk writes. Stops once the stats are dropped.
+fn spawn_saver(stats: Weak<Mutex<LatencyTable>>, unsaved: Arc<AtomicBool>, path: PathBuf) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(SAVE_INTERVAL);
+        let Some(stats) = stats.upgrade() else {
+            break;
+        };
+        save_if_changed(&stats, &unsaved, &path);
+    });
+}
+
+/// Pairs PreToolUse with PostToolUse/PostToolUseFailure by tool_use_id
+pub struct ToolTimings {
+    pending: Mutex<HashMap<String, PendingCall>>,
+    stats: Arc<Mutex<LatencyTable>>,
+    /// Set when `stats` changed since they were last saved
+    unsaved: Arc<AtomicBool>,
+    settings: Mutex<ToolTimingSettings>,
+}
+
+impl ToolTimings {
+    pub fn new(settings: ToolTimingSettings, stats_path: PathBuf) -> Self {
+        let stats = Arc::new(Mutex::new(load_stats(&stats_path)));
+        let unsaved = Arc::new(AtomicBool::new(false));
+        spawn_saver(Arc::downgrade(&stats), unsaved.clone(), stats_path);
+        Self {
+            pending: Mutex::new(HashMap::new()),
+            stats,
+            unsaved,
+            settings: Mutex::new(settings),
+        }
+    }
+
+    pub fn configure(&self, settings: ToolTimingSettings) {
+        *self.settings.lock().unwrap() = settings;
+    }
+
+    pub fn start(
+        &self,
+        session_id: &str,
+        tool_use_id: &str,
+        tool_name: &str,
+        cwd: Option<String>,
+        summary: Option<String>,
+        now: i64,
+    ) {
+        self.pending.lock().unwrap().insert(
+            tool_use_id.to_string(),
+            PendingCall {
+                session_id: session_id.to_string(),
+                tool_name: tool_name.to_string(),
+                cwd,
+                started_at: now,
+                summary,
+                reported_stuck: false,
+            },
+        );
+    }
+
+    /// Record a finished call, returning its duration if its start was seen
+    pub fn finish(&self, tool_use_id: &str, failed: bool, now: i64) -> Option<i64> {
+        let call = self.pending.lock().unwrap().remove(tool_use_id)?;
+        let duration_ms = (now - call.started_at).max(0);
+        // Keyed by the full directory so same-named repos stay apart
+        let project = call.cwd.unwrap_or_else(|| UNKNOWN_PROJECT.to_string());
+        self.stats
+            .lock()
+            .unwrap()
+            .entry(project)
+            .or_default()
+            .entry(call.tool_name)
+            .or_default()
+            .add(duration_ms, failed);
+        self.unsaved.store(true, Ordering::Relaxed);
+        Some(duration_ms)
+    }
+
+    /// Forget in-flight calls of a session, e.g. after it was interrupted or ended
+    pub fn abandon_session(&self, session_id: &str) {
+        self.pending.lock().unwrap().retain(|_, call| call.session_id != session_id);
+    }
+
+    pub fn retain_sessions(&self, keep: impl Fn(&str) -> bool) {
+        self.pending.lock().unwrap().retain(|_, call| keep(&call.session_id));
+    }
+
+    /// Calls running past the threshold, oldest first. With `only_new`, each
+    /// call is returned once and then considered reported.
+    pub fn stuck_calls(&self, now: i64, only_new: bool) -> Vec<StuckCall> {
+        let threshold_ms = self.settings.lock().unwrap().stuck_threshold_secs as i64 * 1000;
+        let mut pending = self.pending.lock().unwrap();
+        let mut stuck: Vec<StuckCall> = pending
+            .iter_mut()
+            .filter(|(_, call)| now - call.started_at >= threshold_ms)
+            .filter(|(_, call)| !(only_new && call.reported_stuck))
+            .map(|(tool_use_id, call)| {
+                if only_new {
+                    call.reported_stuck = true;
+                }
+                StuckCall {
+                    session_id: call.session_id.clone(),
+                    tool_use_id: tool_use_id.clone(),
+                    tool_name: call.tool_name.clone(),
+                    project: call
+                        .cwd
+                        .as_deref()
+                        .and_then(|cwd| Path::new(cwd).file_name())
+                        .map(|name| name.to_string_lossy().into_owned()),
+                    cwd: call.cwd.clone(),
+                    summary: call.summary.clone(),
+                    started_at: call.started_at,
+                    elapsed_ms: now - call.started_at,
+                }
+            })
+            .collect();
+        stuck.sort_by_key(|call| call.started_at);
+        stuck
+    }
+
+    /// Per-tool latency for one project directory, or across all projects
+    pub fn latency(&self, project: Option<&str>) -> BTreeMap<String, LatencyStats> {
+        let stats = self.stats.lock().unwrap();
+        let mut merged: BTreeMap<String, LatencyStats> = BTreeMap::new();
+        let projects = stats
+            .iter()
+            .filter(|(name, _)| project.is_none_or(|p| p == name.as_str()));
+        for (_, tools) in projects {
+            for (tool, tool_stats) in tools {
+                merged.entry(tool.clone()).or_default().merge(tool_stats);
+            }
+        }
+        merged.into_iter().map(|(tool, s)| (tool, s.finish())).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timings(dir: &Path) -> ToolTimings {
+        let timings = ToolTimings::new(
+            ToolTimingSettings {
+                stuck_threshold_secs: 60,
+            },
+            dir.join("tool-latency.json"),
+        );
+        timings.start("s1", "t1", "Bash", Some("/work/app".to_string()), None, 0);
+        timings
+    }
+
+    #[test]
+    fn listing_stuck_calls_does_not_mark_them_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let timings = timings(dir.path());
+        assert!(timings.stuck_calls(59_999, true).is_empty());
+        assert_eq!(timings.stuck_calls(60_000, false).len(), 1);
+        assert_eq!(timings.stuck_calls(60_000, true).len(), 1);
+        assert!(timings.stuck_calls(61_000, true).is_empty());
+        assert_eq!(timings.stuck_calls(61_000, false).len(), 1);
+    }
+
+    #[test]
+    fn stuck_calls_name_the_project_by_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let call = &timings(dir.path()).stuck_calls(60_000, false)[0];
+        assert_eq!(call.project.as_deref(), Some("app"));
+        assert_eq!(call.cwd.as_deref(), Some("/work/app"));
+    }
+
+    #[test]
+    fn finish_pairs_with_the_matching_start() {
+        let dir = tempfile::tempdir().unwrap();
+        let timings = timings(dir.path());
+        timings.start("s1", "t2", "Read", None, None, 100);
+        assert_eq!(timings.finish("unknown", false, 500), None);
+        assert_eq!(timings.finish("t2", false, 350), Some(250));
+        assert_eq!(timings.finish("t2", false, 400), None);
+        assert_eq!(timings.finish("t1", false, 1_000), Some(1_000));
+        assert!(timings.stuck_calls(100_000, false).is_empty());
+    }
+
+    #[test]
+    fn abandoned_calls_are_not_timed() {
+        let dir = tempfile::tempdir().unwrap();
+        let timings = timings(dir.path());
+        timings.abandon_session("s1");
+        assert_eq!(timings.finish("t1", false, 1_000), None);
+        assert!(timings.latency(None).is_empty());
+    }
+
+    #[test]
+    fn latency_counts_failures_and_averages_durations() {
+        let dir = tempfile::tempdir().unwrap();
+        let timings = timings(dir.path());
+        timings.start("s1", "t2", "Bash", Some("/work/app".to_string()), None, 0);
+        timings.start("s2", "t3", "Bash", Some("/other/app".to_string()), None, 0);
+        timings.finish("t1", false, 100);
+        timings.finish("t2", true, 400);
+        timings.finish("t3", false, 1_000);
+
+        let app = &timings.latency(Some("/work/app"))["Bash"];
+        assert_eq!((app.count, app.failures, app.total_ms), (2, 1, 500));
+        assert_eq!((app.min_ms, app.max_ms, app.avg_ms), (Some(100), Some(400), Some(250.0)));
+
+        let all = &timings.latency(None)["Bash"];
+        assert_eq!((all.count, all.failures, all.total_ms), (3, 1, 1_500));
+        assert_eq!((all.min_ms, all.max_ms, all.avg_ms), (Some(100), Some(1_000), Some(500.0)));
+
+        assert!(timings.latency(Some("/missing")).is_empty());
+    }
+
+    #[test]
+    fn changed_stats_are_saved_and_reloaded() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tool-latency.json");
+        let timings = timings(dir.path());
+        save_if_changed(&timings.stats, &timings.unsaved, &path);
+        assert!(!path.exists());
+
+        timings.finish("t1", true, 200);
+        save_if_changed(&timings.stats, &timings.unsaved, &path);
+        assert!(!timings.unsaved.load(Ordering::Relaxed));
+
+        let reloaded = ToolTimings::new(ToolTimingSettings::default(), path);
+        let bash = &reloaded.latency(None)["Bash"];
+        assert_eq!((bash.count, bash.failures, bash.avg_ms), (1, 1, Some(200.0)));
+    }
+}
@@ -0,0 +1,142 @@
+//! Tray menu listing the sessions tracked by the session monitor.
+
+use crate::session_monitor::{SessionMonitor, TrackedSessionInfo};
+use crate::session_state::SessionState;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tauri::menu::{IsMenuItem, Menu, MenuEvent, MenuItem, PredefinedMenuItem};
+use tauri::{AppHandle, Emitter, Listener, Manager};
+
+/// Id of the tray icon created from `tauri.conf.json`
+const TRAY_ID: &str = "main";
+const SESSION_ITEM_PREFIX: &str = "session:";
+const SHOW_ITEM_ID: &str = "show";
+const QUIT_ITEM_ID: &str = "quit";
+
+/// Asks the frontend to open a session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenSessionRequest {
+    pub session_id: String,
+    pub project_encoded: String,
+    pub source: Option<String>,
+}
+
+fn state_label(state: SessionState) -> &'static str {
+    match state {
+        SessionState::Idle => "Idle",
+        SessionState::Thinking => "Thinking",
+        SessionState::Working => "Working",
+        SessionState::AwaitingPermission => "Needs permission",
+        SessionState::AwaitingInput => "Waiting for input",
+        SessionState::Subagent => "Running subagent",
+        SessionState::Compacting => "Compacting",
+        SessionState::Completed => "Completed",
+        SessionState::Error => "Error",
+        SessionState::Ended => "Ended",
+    }
+}
+
+/// Compact duration such as "45s", "12m" or "3h 5m"
+fn format_elapsed(ms: i64) -> String {
+    let secs = (ms / 1000).max(0);
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m", secs / 60),
+        _ => format!("{}h {}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+fn session_label(session: &TrackedSessionInfo, now: i64) -> String {
+    format!(
+        "{} — {} · {}",
+        session.project.as_deref().unwrap_or("Unknown Project"),
+        state_label(session.state),
+        format_elapsed(now - session.state_since)
+    )
+}
+
+fn build_menu(app: &AppHandle, sessions: &[TrackedSessionInfo]) -> tauri::Result<Menu<tauri::Wry>> {
+    let now = chrono::Utc::now().timestamp_millis();
+    let mut items: Vec<Box<dyn IsMenuItem<tauri::Wry>>> = Vec::new();
+
+    if sessions.is_empty() {
+        items.push(Box::new(MenuItem::new(app, "No active sessions", false, None::<&str>)?));
+    }
+    for session in sessions {
+        // Without a transcript location there is nothing to open
+        let item = MenuItem::with_id(
+            app,
+            format!("{}{}", SESSION_ITEM_PREFIX, session.session_id),
+            session_label(session, now),
+            session.project_encoded.is_some(),
+            None::<&str>,
+        )?;
+        items.push(Box::new(item));
+    }
+    items.push(Box::new(PredefinedMenuItem::separator(app)?));
+    items.push(Box::new(MenuItem::with_id(app, SHOW_ITEM_ID, "Show Sessionly", true, None::<&str>)?));
+    items.push(Box::new(MenuItem::with_id(app, QUIT_ITEM_ID, "Quit", true, None::<&str>)?));
+
+    let refs: Vec<&dyn IsMenuItem<tauri::Wry>> = items.iter().map(|item| item.as_ref()).collect();
+    Menu::with_items(app, &refs)
+}
+
+/// Rebuild the tray menu from the monitor's current sessions
+pub fn refresh(app: &AppHandle, monitor: &SessionMonitor) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    let sessions = monitor.sessions();
+    let result = build_menu(app, &sessions).and_then(|menu| tray.set_menu(Some(menu)));
+    if let Err(e) = result {
+        eprintln!("Failed to update tray menu: {}", e);
+    }
+}
+
+fn show_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+fn handle_menu_event(app: &AppHandle, monitor: &SessionMonitor, event: MenuEvent) {
+    let id = event.id().as_ref();
+    if id == QUIT_ITEM_ID {
+        app.exit(0);
+        return;
+    }
+    show_main_window(app);
+
+    let Some(session_id) = id.strip_prefix(SESSION_ITEM_PREFIX) else {
+        return;
+    };
+    let session = monitor.sessions().into_iter().find(|s| s.session_id == session_id);
+    if let Some((project_encoded, source)) =
+        session.and_then(|s| Some((s.project_encoded?, s.source)))
+    {
+        let request = OpenSessionRequest {
+            session_id: session_id.to_string(),
+            project_encoded,
+            source,
+        };
+        let _ = app.emit("open-session", &request);
+    }
+}
+
+/// Attach the session menu to the tray and keep it in sync with state changes
+pub fn setup(app: &AppHandle, monitor: Arc<SessionMonitor>) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+
+    let menu_monitor = monitor.clone();
+    tray.on_menu_event(move |app, event| handle_menu_event(app, &menu_monitor, event));
+
+    let handle = app.clone();
+    let listen_monitor = monitor.clone();
+    app.listen_any("session-state-changed", move |_| refresh(&handle, &listen_monitor));
+
+    refresh(app, &monitor);
+}
@@ -0,0 +1,617 @@
+//! Forwarding of session state changes and raw hook events to HTTP endpoints.
+//!
+//! Templates are JSON text with `{{field}}` placeholders. String values are
+//! escaped for use inside a JSON string, and `{{payload}}` inserts the whole
+//! event as a JSON object. Without a template the event itself is posted.
+
+use crate::session_monitor::HookEventPayload;
+use crate::session_state::{SessionState, SessionStateInfo};
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+const LOG_CAPACITY: usize = 200;
+/// Events waiting for one endpoint before new ones are dropped
+const QUEUE_CAPACITY: usize = 500;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookEndpoint {
+    pub id: String,
+    pub name: String,
+    pub url: String,
+    pub enabled: bool,
+    /// Events to forward: `state:<state>` or `hook:<HookEventName>`, with `*`
+    /// matching any state or hook event
+    #[serde(default = "default_events")]
+    pub events: Vec<String>,
+    /// Project names to forward events for; all projects if empty
+    #[serde(default)]
+    pub projects: Vec<String>,
+    /// Request body template; the event as JSON if unset
+    #[serde(default)]
+    pub template: Option<String>,
+    /// Extra request headers, e.g. for authentication
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// Attempts after the first before giving up
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+}
+
+fn default_events() -> Vec<String> {
+    vec!["state:completed".to_string(), "state:error".to_string()]
+}
+
+fn default_max_retries() -> u32 {
+    3
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WebhookSettings {
+    #[serde(default)]
+    pub endpoints: Vec<WebhookEndpoint>,
+}
+
+impl WebhookSettings {
+    /// Check that every endpoint can be delivered to
+    pub fn validate(&self) -> Result<(), String> {
+        for endpoint in &self.endpoints {
+            reqwest::Url::parse(&endpoint.url)
+                .map_err(|e| format!("Invalid URL for '{}': {}", endpoint.name, e))?;
+            for filter in &endpoint.events {
+                if !(filter.starts_with("state:") || filter.starts_with("hook:")) {
+                    return Err(format!(
+                        "Invalid event filter '{}' for '{}', expected state:<state> or hook:<event>",
+                        filter, endpoint.name
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// An event as forwarded to endpoints
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookEvent {
+    /// `state:<state>` or `hook:<HookEventName>`
+    pub event: String,
+    pub session_id: String,
+    pub project: Option<String>,
+    /// RFC 3339
+    pub timestamp: String,
+    pub state: Option<SessionState>,
+    pub prev_state: Option<SessionState>,
+    pub hook_event_name: Option<String>,
+    pub tool_name: Option<String>,
+    pub message: Option<String>,
+}
+
+fn state_name(state: SessionState) -> String {
+    serde_json::to_value(state)
+        .ok()
+        .and_then(|v| v.as_str().map(String::from))
+        .unwrap_or_default()
+}
+
+fn now_rfc3339() -> String {
+    chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true)
+}
+
+impl WebhookEvent {
+    pub fn from_state_change(info: &SessionStateInfo) -> Self {
+        Self {
+            event: format!("state:{}", state_name(info.state)),
+            session_id: info.session_id.clone(),
+            project: info.project.clone(),
+            timestamp: now_rfc3339(),
+            state: Some(info.state),
+            prev_state: Some(info.prev_state),
+            hook_event_name: None,
+            tool_name: None,
+            message: info.message.clone(),
+        }
+    }
+
+    pub fn from_hook(payload: &HookEventPayload, project: Option<String>) -> Self {
+        Self {
+            event: format!("hook:{}", payload.hook_event_name),
+            session_id: payload.session_id.clone(),
+            project,
+            timestamp: now_rfc3339(),
+            state: None,
+            prev_state: None,
+            hook_event_name: Some(payload.hook_event_name.clone()),
+            tool_name: payload.tool_name.clone(),
+            message: payload.message.clone(),
+        }
+    }
+}
+
+impl WebhookEndpoint {
+    pub fn accepts(&self, event: &WebhookEvent) -> bool {
+        let (kind, _) = event.event.split_once(':').unwrap_or_default();
+        let event_matches = self
+            .events
+            .iter()
+            .any(|filter| *filter == event.event || *filter == format!("{}:*", kind));
+        let project_matches = self.projects.is_empty()
+            || event.project.as_ref().is_some_and(|p| self.projects.contains(p));
+        self.enabled && event_matches && project_matches
+    }
+}
+
+fn template_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| Regex::new(r"\{\{\s*(\w+)\s*\}\}").expect("valid template regex"))
+}
+
+/// Render the request body for an event
+pub fn render_body(template: Option<&str>, event: &WebhookEvent) -> String {
+    let payload = serde_json::to_value(event).unwrap_or_default();
+    let Some(template) = template else {
+        return payload.to_string();
+    };
+    template_regex()
+        .replace_all(template, |caps: &Captures| {
+            let field = &caps[1];
+            if field == "payload" {
+                return payload.to_string();
+            }
+            match payload.get(field) {
+                Some(serde_json::Value::String(s)) => {
+                    // Escape for a JSON string, without the surrounding quotes
+                    let quoted = serde_json::Value::String(s.clone()).to_string();
+                    quoted[1..quoted.len() - 1].to_string()
+                }
+                Some(serde_json::Value::Null) | None => String::new(),
+                Some(value) => value.to_string(),
+            }
+        })
+        .into_owned()
+}
+
+/// Outcome of delivering one event to one endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeliveryRecord {
+    pub endpoint_id: String,
+    pub event: String,
+    pub session_id: String,
+    /// When delivery finished, RFC 3339
+    pub timestamp: String,
+    pub attempts: u32,
+    pub delivered: bool,
+    /// HTTP status of the last attempt, if a response was received
+    pub status: Option<u16>,
+    pub error: Option<String>,
+}
+
+enum AttemptError {
+    /// Worth trying again, e.g. a timeout or a 5xx
+    Retryable(Option<u16>, String),
+    Fatal(Option<u16>, String),
+}
+
+fn client() -> Result<reqwest::blocking::Client, String> {
+    // Same provider the updater installs; reqwest is built without its own
+    if rustls::crypto::CryptoProvider::get_default().is_none() {
+        let _ = rustls::crypto::ring::default_provider().install_default();
+    }
+    reqwest::blocking::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+fn attempt(
+    client: &reqwest::blocking::Client,
+    endpoint: &WebhookEndpoint,
+    body: &str,
+) -> Result<u16, AttemptError> {
+    let mut request = client
+        .post(&endpoint.url)
+        .header("Content-Type", "application/json")
+        .body(body.to_string());
+    for (name, value) in &endpoint.headers {
+        request = request.header(name, value);
+    }
+    let response = request.send().map_err(|e| AttemptError::Retryable(None, e.to_string()))?;
+    let status = response.status();
+    let code = status.as_u16();
+    if status.is_success() {
+        Ok(code)
+    } else if status.is_server_error() || code == 429 {
+        Err(AttemptError::Retryable(Some(code), format!("HTTP {}", status)))
+    } else {
+        Err(AttemptError::Fatal(Some(code), format!("HTTP {}", status)))
+    }
+}
+
+/// Post an event to an endpoint, retrying with exponential backoff. Blocks
+/// until delivered or out of retries.
+pub fn deliver(endpoint: &WebhookEndpoint, event: &WebhookEvent) -> DeliveryRecord {
+    let mut record = DeliveryRecord {
+        endpoint_id: endpoint.id.clone(),
+        event: event.event.clone(),
+        session_id: event.session_id.clone(),
+        timestamp: String::new(),
+        attempts: 0,
+        delivered: false,
+        status: None,
+        error: None,
+    };
+    let body = render_body(endpoint.template.as_deref(), event);
+
+    match client() {
+        Ok(client) => {
+            let mut backoff = INITIAL_BACKOFF;
+            loop {
+                record.attempts += 1;
+                match attempt(&client, endpoint, &body) {
+                    Ok(status) => {
+                        record.delivered = true;
+                        record.status = Some(status);
+                        record.error = None;
+                        break;
+                    }
+                    Err(AttemptError::Fatal(status, e)) => {
+                        record.status = status;
+                        record.error = Some(e);
+                        break;
+                    }
+                    Err(AttemptError::Retryable(status, e)) => {
+                        record.status = status;
+                        record.error = Some(e);
+                        if record.attempts > endpoint.max_retries {
+                            break;
+                        }
+                        std::thread::sleep(backoff);
+                        backoff = (backoff * 2).min(MAX_BACKOFF);
+                    }
+                }
+            }
+        }
+        Err(e) => record.error = Some(e),
+    }
+
+    record.timestamp = now_rfc3339();
+    record
+}
+
+type Delivery = (WebhookEndpoint, WebhookEvent);
+
+/// Start a thread delivering one endpoint's events in the order queued. It
+/// exits once the returned sender is dropped and the queue is drained.
+fn spawn_worker(log: Arc<Mutex<VecDeque<DeliveryRecord>>>) -> SyncSender<Delivery> {
+    let (sender, receiver) = mpsc::sync_channel::<Delivery>(QUEUE_CAPACITY);
+    std::thread::spawn(move || {
+        for (endpoint, event) in receiver {
+            let record = deliver(&endpoint, &event);
+            if !record.delivered {
+                eprintln!(
+                    "Webhook '{}' failed after {} attempt(s): {}",
+                    endpoint.name,
+                    record.attempts,
+                    record.error.as_deref().unwrap_or("unknown error")
+                );
+            }
+            push_record(&log, record);
+        }
+    });
+    sender
+}
+
+/// Sends events to matching endpoints in the background, one worker thread
+/// per endpoint, and keeps a log of recent deliveries
+pub struct WebhookForwarder {
+    settings: Mutex<WebhookSettings>,
+    /// Endpoint id -> queue of its worker
+    workers: Mutex<HashMap<String, SyncSender<Delivery>>>,
+    log: Arc<Mutex<VecDeque<DeliveryRecord>>>,
+}
+
+impl WebhookForwarder {
+    pub fn new(settings: WebhookSettings) -> Self {
+        Self {
+            settings: Mutex::new(settings),
+            workers: Mutex::new(HashMap::new()),
+            log: Arc::new(Mutex::new(VecDeque::new())),
+        }
+    }
+
+    /// Replace the endpoints; workers of removed endpoints stop after their queue
+    pub fn configure(&self, settings: WebhookSettings) {
+        self.workers
+            .lock()
+            .unwrap()
+            .retain(|id, _| settings.endpoints.iter().any(|endpoint| endpoint.id == *id));
+        *self.settings.lock().unwrap() = settings;
+    }
+
+    pub fn forward(&self, event: WebhookEvent) {
+        let endpoints: Vec<WebhookEndpoint> = self
+            .settings
+            .lock()
+            .unwrap()
+            .endpoints
+            .iter()
+            .filter(|endpoint| endpoint.accepts(&event))
+            .cloned()
+            .collect();
+        let mut workers = self.workers.lock().unwrap();
+        for endpoint in endpoints {
+            let worker = workers
+                .entry(endpoint.id.clone())
+                .or_insert_with(|| spawn_worker(self.log.clone()));
+            match worker.try_send((endpoint, event.clone())) {
+                Ok(()) => {}
+                Err(TrySendError::Full((endpoint, event))) => {
+                    eprintln!("Webhook '{}' is backed up, dropping {}", endpoint.name, event.event);
+                    let record = dropped_record(&endpoint, &event, "Delivery queue full");
+                    push_record(&self.log, record);
+                }
+                // The worker is gone, e.g. after a panic; start another and resend
+                Err(TrySendError::Disconnected(delivery)) => {
+                    let id = delivery.0.id.clone();
+                    let worker = spawn_worker(self.log.clone());
+                    // The new queue is empty, so this doesn't block
+                    if let Err(mpsc::SendError((endpoint, event))) = worker.send(delivery) {
+                        let record = dropped_record(&endpoint, &event, "Delivery worker stopped");
+                        push_record(&self.log, record);
+                    }
+                    workers.insert(id, worker);
+                }
+            }
+        }
+    }
+
+    /// Deliver a sample event to an endpoint regardless of its filters
+    pub fn test(&self, endpoint: &WebhookEndpoint) -> DeliveryRecord {
+        let event = WebhookEvent {
+            event: "state:completed".to_string(),
+            session_id: "test".to_string(),
+            project: Some("sessionly-test".to_string()),
+            timestamp: now_rfc3339(),
+            state: Some(SessionState::Completed),
+            prev_state: Some(SessionState::Working),
+            hook_event_name: None,
+            tool_name: None,
+            message: Some("Test event from Sessionly".to_string()),
+        };
+        let record = deliver(endpoint, &event);
+        push_record(&self.log, record.clone());
+        record
+    }
+
+    /// Recent deliveries, newest first
+    pub fn deliveries(&self) -> Vec<DeliveryRecord> {
+        self.log.lock().unwrap().iter().rev().cloned().collect()
+    }
+}
+
+/// Record for an event dropped before it reached its endpoint's worker
+fn dropped_record(endpoint: &WebhookEndpoint, event: &WebhookEvent, reason: &str) -> DeliveryRecord {
+    DeliveryRecord {
+        endpoint_id: endpoint.id.clone(),
+        event: event.event.clone(),
+        session_id: event.session_id.clone(),
+        timestamp: now_rfc3339(),
+        attempts: 0,
+        delivered: false,
+        status: None,
+        error: Some(reason.to_string()),
+    }
+}
+
+fn push_record(log: &Mutex<VecDeque<DeliveryRecord>>, record: DeliveryRecord) {
+    let mut log = log.lock().unwrap();
+    log.push_back(record);
+    while log.len() > LOG_CAPACITY {
+        log.pop_front();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    /// A received request: body and the value of `x-test`
+    type Received = Arc<Mutex<Vec<(String, Option<String>)>>>;
+
+    /// A local HTTP server answering with `statuses` in turn, then 200
+    fn stub(statuses: &[u16]) -> (String, Received) {
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", server.server_addr().to_ip().unwrap());
+        let received: Received = Arc::default();
+        let requests = received.clone();
+        let mut statuses: VecDeque<u16> = statuses.iter().copied().collect();
+        std::thread::spawn(move || {
+            while let Ok(Some(mut request)) = server.recv_timeout(Duration::from_secs(10)) {
+                let mut body = String::new();
+                let _ = request.as_reader().read_to_string(&mut body);
+                let header = request
+                    .headers()
+                    .iter()
+                    .find(|h| h.field.equiv("x-test"))
+                    .map(|h| h.value.to_string());
+                requests.lock().unwrap().push((body, header));
+                let status = statuses.pop_front().unwrap_or(200);
+                let _ = request.respond(tiny_http::Response::empty(status));
+            }
+        });
+        (url, received)
+    }
+
+    fn endpoint(url: &str, max_retries: u32) -> WebhookEndpoint {
+        WebhookEndpoint {
+            id: "e1".to_string(),
+            name: "test".to_string(),
+            url: url.to_string(),
+            enabled: true,
+            events: default_events(),
+            projects: Vec::new(),
+            template: None,
+            headers: BTreeMap::new(),
+            max_retries,
+        }
+    }
+
+    fn event(state: SessionState, project: &str, message: Option<&str>) -> WebhookEvent {
+        WebhookEvent::from_state_change(&SessionStateInfo {
+            session_id: "s1".to_string(),
+            prev_state: SessionState::Working,
+            state,
+            aggregate_state: state,
+            project: Some(project.to_string()),
+            message: message.map(String::from),
+        })
+    }
+
+    #[test]
+    fn renders_the_event_without_a_template() {
+        let event = event(SessionState::Completed, "app", None);
+        let body: serde_json::Value = serde_json::from_str(&render_body(None, &event)).unwrap();
+        assert_eq!(body, serde_json::to_value(&event).unwrap());
+    }
+
+    #[test]
+    fn renders_templates_with_escaped_strings() {
+        let event = event(SessionState::Completed, "app", Some("said \"hi\"\nthen left \\"));
+        let template = r#"{"text": "{{project}} is {{ state }}: {{message}}", "tool": "{{tool_name}}", "raw": {{payload}}, "x": "{{nope}}"}"#;
+        let body: serde_json::Value = serde_json::from_str(&render_body(Some(template), &event)).unwrap();
+        assert_eq!(body["text"], "app is completed: said \"hi\"\nthen left \\");
+        assert_eq!(body["tool"], "");
+        assert_eq!(body["x"], "");
+        assert_eq!(body["raw"], serde_json::to_value(&event).unwrap());
+    }
+
+    #[test]
+    fn accepts_matching_events_and_projects() {
+        let mut endpoint = endpoint("http://localhost", 0);
+        let completed = event(SessionState::Completed, "app", None);
+        let working = event(SessionState::Working, "app", None);
+        let hook = WebhookEvent {
+            event: "hook:PreToolUse".to_string(),
+            ..completed.clone()
+        };
+        assert!(endpoint.accepts(&completed));
+        assert!(!endpoint.accepts(&working));
+        assert!(!endpoint.accepts(&hook));
+
+        endpoint.events = vec!["state:*".to_string()];
+        assert!(endpoint.accepts(&working));
+        assert!(!endpoint.accepts(&hook));
+        endpoint.events = vec!["hook:*".to_string()];
+        assert!(endpoint.accepts(&hook));
+        assert!(!endpoint.accepts(&completed));
+
+        endpoint.events = vec!["state:*".to_string()];
+        endpoint.projects = vec!["other".to_string()];
+        assert!(!endpoint.accepts(&completed));
+        assert!(!endpoint.accepts(&WebhookEvent {
+            project: None,
+            ..completed.clone()
+        }));
+        endpoint.projects = vec!["app".to_string()];
+        assert!(endpoint.accepts(&completed));
+
+        endpoint.enabled = false;
+        assert!(!endpoint.accepts(&completed));
+    }
+
+    #[test]
+    fn delivers_with_headers_and_template() {
+        let (url, received) = stub(&[]);
+        let mut endpoint = endpoint(&url, 3);
+        endpoint.headers.insert("X-Test".to_string(), "secret".to_string());
+        endpoint.template = Some(r#"{"session": "{{session_id}}"}"#.to_string());
+        let record = deliver(&endpoint, &event(SessionState::Completed, "app", None));
+        assert!(record.delivered);
+        assert_eq!((record.attempts, record.status), (1, Some(200)));
+        let received = received.lock().unwrap();
+        assert_eq!(*received, [(r#"{"session": "s1"}"#.to_string(), Some("secret".to_string()))]);
+    }
+
+    #[test]
+    fn retries_server_errors_and_rate_limits() {
+        for status in [500, 503, 429] {
+            let (url, received) = stub(&[status]);
+            let record = deliver(&endpoint(&url, 1), &event(SessionState::Completed, "app", None));
+            assert!(record.delivered, "{} was not retried", status);
+            assert_eq!((record.attempts, record.status), (2, Some(200)));
+            assert_eq!(received.lock().unwrap().len(), 2);
+        }
+    }
+
+    #[test]
+    fn gives_up_after_max_retries() {
+        let (url, received) = stub(&[502, 502, 502]);
+        let record = deliver(&endpoint(&url, 1), &event(SessionState::Completed, "app", None));
+        assert!(!record.delivered);
+        assert_eq!((record.attempts, record.status), (2, Some(502)));
+        assert_eq!(record.error.as_deref(), Some("HTTP 502 Bad Gateway"));
+        assert_eq!(received.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn does_not_retry_other_client_errors() {
+        for status in [400, 401, 404] {
+            let (url, received) = stub(&[status]);
+            let record = deliver(&endpoint(&url, 3), &event(SessionState::Completed, "app", None));
+            assert!(!record.delivered);
+            assert_eq!((record.attempts, record.status), (1, Some(status)));
+            assert_eq!(received.lock().unwrap().len(), 1);
+        }
+    }
+
+    #[test]
+    fn forwards_each_endpoints_events_in_order() {
+        let (url, received) = stub(&[]);
+        let mut endpoint = endpoint(&url, 0);
+        endpoint.events = vec!["state:*".to_string()];
+        endpoint.template = Some(r#"{"n": {{message}}}"#.to_string());
+        let forwarder = WebhookForwarder::new(WebhookSettings {
+            endpoints: vec![endpoint],
+        });
+        for n in 0..20 {
+            forwarder.forward(event(SessionState::Working, "app", Some(&n.to_string())));
+        }
+        assert_eq!(forwarder.workers.lock().unwrap().len(), 1);
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while forwarder.deliveries().len() < 20 && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        let bodies: Vec<String> = received.lock().unwrap().iter().map(|(body, _)| body.clone()).collect();
+        let expected: Vec<String> = (0..20).map(|n| format!(r#"{{"n": {}}}"#, n)).collect();
+        assert_eq!(bodies, expected);
+    }
+
+    #[test]
+    fn restarts_a_stopped_worker_and_resends() {
+        let (url, received) = stub(&[]);
+        let forwarder = WebhookForwarder::new(WebhookSettings {
+            endpoints: vec![endpoint(&url, 0)],
+        });
+        // A worker whose thread has exited
+        let (stopped, _) = mpsc::sync_channel::<Delivery>(1);
+        forwarder.workers.lock().unwrap().insert("e1".to_string(), stopped);
+
+        forwarder.forward(event(SessionState::Completed, "app", None));
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while forwarder.deliveries().is_empty() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        let deliveries = forwarder.deliveries();
+        assert_eq!(deliveries.len(), 1);
+        assert!(deliveries[0].delivered, "{:?}", deliveries[0].error);
+        assert_eq!(received.lock().unwrap().len(), 1);
+    }
+}
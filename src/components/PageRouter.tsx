@@ -1,5 +1,6 @@
 import React from 'react'
 import { useNavigation } from '@/contexts/NavigationContext'
+import { useOpenSessionRequests } from '@/hooks/useOpenSessionRequests'
 import { HomePage } from '@/features/home/HomePage'
 import { SessionsPage } from '@/features/sessions/SessionsPage'
 import { AboutPage } from '@/pages/AboutPage'
@@ -15,6 +16,7 @@ const pageComponents: Record<PageId, () => React.JSX.Element> = {
 
 export function PageRouter() {
   const { currentPage } = useNavigation()
+  useOpenSessionRequests()
   const PageComponent = pageComponents[currentPage]
 
   if (!PageComponent) {
@@ -5,7 +5,7 @@ import type { NotificationPrefs } from '@/hooks/useNotifications'
 interface NotificationContextType {
   prefs: NotificationPrefs
   updatePrefs: (partial: Partial<NotificationPrefs>) => void
-  sendTest: () => Promise<void>
+  sendTest: () => Promise<string>
 }
 
 const NotificationContext = createContext<NotificationContextType | null>(null)
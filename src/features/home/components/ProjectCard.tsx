@@ -1,5 +1,5 @@
 import { Fragment } from 'react'
-import type { ProjectState, SessionEvent } from '@/types/session-monitor'
+import { ACTIVE_STATES, type ProjectState, type SessionEvent } from '@/types/session-monitor'
 import { stateToColor, stateToTextColor, stateToLabel, relativeTime } from '../utils'
 import { cn } from '@/lib/utils'
 import { FolderOpen } from 'lucide-react'
@@ -25,7 +25,7 @@ export function ProjectCard({ project }: ProjectCardProps) {
   const pastEvents = project.events.slice(1, 8)
   const collapsed = collapseEvents(pastEvents)
   const hasTransitions = collapsed.length > 0 && !(collapsed.length === 1 && collapsed[0].state === project.latestState)
-  const isWorking = project.latestState === 'working'
+  const isWorking = ACTIVE_STATES.includes(project.latestState)
 
   return (
     <div
@@ -1,7 +1,13 @@
 export function stateToColor(state: string): string {
   switch (state) {
+    case 'thinking':
     case 'working':
+    case 'subagent':
+    case 'compacting':
       return 'bg-green-500'
+    case 'awaiting_permission':
+    case 'awaiting_input':
+      return 'bg-blue-500'
     case 'completed':
       return 'bg-amber-500'
     case 'error':
@@ -13,8 +19,14 @@ export function stateToColor(state: string): string {
 
 export function stateToTextColor(state: string): string {
   switch (state) {
+    case 'thinking':
     case 'working':
+    case 'subagent':
+    case 'compacting':
       return 'text-green-600 dark:text-green-400'
+    case 'awaiting_permission':
+    case 'awaiting_input':
+      return 'text-blue-600 dark:text-blue-400'
     case 'completed':
       return 'text-amber-600 dark:text-amber-400'
     case 'error':
@@ -26,12 +38,24 @@ export function stateToTextColor(state: string): string {
 
 export function stateToLabel(state: string): string {
   switch (state) {
+    case 'thinking':
+      return 'Thinking'
     case 'working':
       return 'Working'
+    case 'subagent':
+      return 'Running subagent'
+    case 'compacting':
+      return 'Compacting'
+    case 'awaiting_permission':
+      return 'Needs permission'
+    case 'awaiting_input':
+      return 'Waiting for input'
     case 'completed':
       return 'Completed'
     case 'error':
       return 'Error'
+    case 'ended':
+      return 'Ended'
     default:
       return 'Idle'
   }
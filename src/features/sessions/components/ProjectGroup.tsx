@@ -1,16 +1,17 @@
 import { useState } from 'react'
 import { ChevronDown, ChevronRight, Folder, Eye, EyeOff } from 'lucide-react'
 import type { ProjectGroup as ProjectGroupType } from '@/types/session-types'
+import { projectKey } from '@/store/slices/sessionsSlice'
 import { SessionItem } from './SessionItem'
 
 interface ProjectGroupProps {
   group: ProjectGroupType
   selectedSessionId: string | null
-  onSelectSession: (sessionId: string, projectEncoded: string) => void
+  onSelectSession: (sessionId: string, projectEncoded: string, source?: string) => void
   defaultExpanded?: boolean
   isHidden?: boolean
-  onHide: (projectEncoded: string) => void
-  onUnhide: (projectEncoded: string) => void
+  onHide: (projectKey: string) => void
+  onUnhide: (projectKey: string) => void
   onHideSession: (sessionId: string) => void
   onUnhideSession: (sessionId: string) => void
   hiddenSessions: string[]
@@ -34,9 +35,9 @@ export function ProjectGroup({
   const handleHideClick = (e: React.MouseEvent) => {
     e.stopPropagation()
     if (isHidden) {
-      onUnhide(group.projectEncoded)
+      onUnhide(projectKey(group))
     } else {
-      onHide(group.projectEncoded)
+      onHide(projectKey(group))
     }
   }
 
@@ -62,6 +63,11 @@ export function ProjectGroup({
           <span className="text-xs font-medium text-muted-foreground truncate" title={group.project}>
             {shortName}
           </span>
+          {group.source && group.source !== 'default' && (
+            <span className="text-[10px] text-muted-foreground/50 truncate shrink-0">
+              {group.source}
+            </span>
+          )}
         </button>
         <button
           onClick={handleHideClick}
@@ -85,7 +91,7 @@ export function ProjectGroup({
               key={session.filePath}
               session={session}
               isSelected={selectedSessionId === session.id}
-              onSelect={() => onSelectSession(session.id, group.projectEncoded)}
+              onSelect={() => onSelectSession(session.id, group.projectEncoded, group.source)}
               isHidden={hiddenSessions.includes(session.id)}
               onHide={onHideSession}
               onUnhide={onUnhideSession}
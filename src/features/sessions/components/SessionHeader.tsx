@@ -41,14 +41,18 @@ export function SessionHeader({ session }: SessionHeaderProps) {
     if (isExporting) return
     setIsExporting(true)
     try {
-      const markdown = await api.sessionsExportMarkdown(session.id, session.projectEncoded)
+      const { content } = await api.sessionsExportMarkdown(
+        session.id,
+        session.projectEncoded,
+        session.source
+      )
       const filePath = await save({
         title: 'Export Session as Markdown',
         defaultPath: `session_${session.id.slice(0, 8)}.md`,
         filters: [{ name: 'Markdown', extensions: ['md'] }],
       })
       if (filePath) {
-        await writeTextFile(filePath, markdown)
+        await writeTextFile(filePath, content)
       }
     } catch (error) {
       console.error('Failed to export session:', error)
@@ -1,19 +1,20 @@
 import { RefreshCw, Loader2, Eye, EyeOff } from 'lucide-react'
 import { Button } from '@/components/ui/button'
 import type { ProjectGroup as ProjectGroupType } from '@/types/session-types'
+import { projectKey } from '@/store/slices/sessionsSlice'
 import { ProjectGroup } from './ProjectGroup'
 
 interface SessionSidebarProps {
   projectGroups: ProjectGroupType[]
   selectedSessionId: string | null
   isLoading: boolean
-  onSelectSession: (sessionId: string, projectEncoded: string) => void
+  onSelectSession: (sessionId: string, projectEncoded: string, source?: string) => void
   onRefresh: () => void
   showHidden: boolean
   hiddenCount: { projects: number; sessions: number }
   onToggleShowHidden: () => void
-  onHideProject: (projectEncoded: string) => void
-  onUnhideProject: (projectEncoded: string) => void
+  onHideProject: (projectKey: string) => void
+  onUnhideProject: (projectKey: string) => void
   onHideSession: (sessionId: string) => void
   onUnhideSession: (sessionId: string) => void
   hiddenProjects: string[]
@@ -101,12 +102,12 @@ export function SessionSidebar({
             <div className="space-y-1">
               {projectGroups.map((group) => (
                 <ProjectGroup
-                  key={group.projectEncoded}
+                  key={projectKey(group)}
                   group={group}
                   selectedSessionId={selectedSessionId}
                   onSelectSession={onSelectSession}
                   defaultExpanded={false}
-                  isHidden={hiddenProjects.includes(group.projectEncoded)}
+                  isHidden={hiddenProjects.includes(projectKey(group))}
                   onHide={onHideProject}
                   onUnhide={onUnhideProject}
                   onHideSession={onHideSession}
@@ -0,0 +1,49 @@
+import { useEffect } from 'react'
+import { listen } from '@tauri-apps/api/event'
+import { api } from '@/types/api'
+import { useAppDispatch } from '@/store/hooks'
+import {
+  sessionMessagesAppended,
+  subagentUpdated,
+  watchSession,
+} from '@/store/slices/sessionsSlice'
+import type { Session, SessionMessagesAppended, SubagentUpdated } from '@/types/session-types'
+
+/** Keep the open session up to date as Claude Code appends to it */
+export function useLiveSession(session: Session | null) {
+  const dispatch = useAppDispatch()
+  const sessionId = session?.id
+  const projectEncoded = session?.projectEncoded
+  const source = session?.source
+
+  useEffect(() => {
+    if (!sessionId || !projectEncoded || source === undefined) return
+
+    const unlistenAppended = listen<SessionMessagesAppended>('session-messages-appended', (e) => {
+      dispatch(sessionMessagesAppended(e.payload))
+    })
+    const unlistenSubagent = listen<SubagentUpdated>('session-subagent-updated', (e) => {
+      dispatch(subagentUpdated(e.payload))
+    })
+    dispatch(watchSession({ sessionId, projectEncoded, source }))
+      .unwrap()
+      .catch((error) => {
+        console.error('Failed to watch session:', error)
+      })
+
+    // Catch up on anything the file watcher missed while the window was in the background
+    const handleFocus = () => {
+      api.sessionsReadAppended(sessionId, source).catch((error) => {
+        console.error('Failed to refresh session:', error)
+      })
+    }
+    window.addEventListener('focus', handleFocus)
+
+    return () => {
+      window.removeEventListener('focus', handleFocus)
+      unlistenAppended.then((fn) => fn())
+      unlistenSubagent.then((fn) => fn())
+      api.sessionsUnwatch(sessionId, source).catch(() => {})
+    }
+  }, [dispatch, sessionId, projectEncoded, source])
+}
@@ -0,0 +1,24 @@
+import { useEffect } from 'react'
+import { listen } from '@tauri-apps/api/event'
+import { useAppDispatch } from '@/store/hooks'
+import { sessionRemoved, sessionSummaryUpdated } from '@/store/slices/sessionsSlice'
+import type { SessionRemoved, SessionSummary } from '@/types/session-types'
+
+/** Keep the session list up to date as session files change on disk */
+export function useSessionListUpdates() {
+  const dispatch = useAppDispatch()
+
+  useEffect(() => {
+    const unlistenSummary = listen<SessionSummary>('session-summary-updated', (e) => {
+      dispatch(sessionSummaryUpdated(e.payload))
+    })
+    const unlistenRemoved = listen<SessionRemoved>('session-removed', (e) => {
+      dispatch(sessionRemoved(e.payload))
+    })
+
+    return () => {
+      unlistenSummary.then((fn) => fn())
+      unlistenRemoved.then((fn) => fn())
+    }
+  }, [dispatch])
+}
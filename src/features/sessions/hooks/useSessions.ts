@@ -13,6 +13,8 @@ import {
   selectVisibleProjectGroups,
   selectHiddenCount,
 } from '@/store/slices/sessionsSlice'
+import { useLiveSession } from './useLiveSession'
+import { useSessionListUpdates } from './useSessionListUpdates'
 
 export function useSessions() {
   const dispatch = useAppDispatch()
@@ -20,6 +22,7 @@ export function useSessions() {
   const currentSession = useAppSelector((state) => state.sessions.currentSession)
   const selectedSessionId = useAppSelector((state) => state.sessions.selectedSessionId)
   const selectedProjectEncoded = useAppSelector((state) => state.sessions.selectedProjectEncoded)
+  const selectedSource = useAppSelector((state) => state.sessions.selectedSource)
   const isLoading = useAppSelector((state) => state.sessions.isLoading)
   const isLoadingSession = useAppSelector((state) => state.sessions.isLoadingSession)
   const error = useAppSelector((state) => state.sessions.error)
@@ -33,17 +36,24 @@ export function useSessions() {
     dispatch(fetchSessions())
   }, [dispatch])
 
+  useLiveSession(currentSession)
+  useSessionListUpdates()
+
   useEffect(() => {
     if (selectedSessionId && selectedProjectEncoded) {
       dispatch(
-        fetchSession({ sessionId: selectedSessionId, projectEncoded: selectedProjectEncoded })
+        fetchSession({
+          sessionId: selectedSessionId,
+          projectEncoded: selectedProjectEncoded,
+          source: selectedSource ?? undefined,
+        })
       )
     }
-  }, [dispatch, selectedSessionId, selectedProjectEncoded])
+  }, [dispatch, selectedSessionId, selectedProjectEncoded, selectedSource])
 
   const handleSelectSession = useCallback(
-    (sessionId: string, projectEncoded: string) => {
-      dispatch(selectSession({ sessionId, projectEncoded }))
+    (sessionId: string, projectEncoded: string, source?: string) => {
+      dispatch(selectSession({ sessionId, projectEncoded, source }))
     },
     [dispatch]
   )
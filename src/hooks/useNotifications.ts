@@ -1,120 +1,109 @@
 import { useEffect, useRef, useCallback, useState } from 'react'
 import { listen } from '@tauri-apps/api/event'
-import {
-  isPermissionGranted,
-  requestPermission,
-  sendNotification,
-} from '@tauri-apps/plugin-notification'
-import { invoke } from '@tauri-apps/api/core'
-
-interface SessionStateEvent {
-  session_id: string
-  prev_state: string
-  state: string
-  aggregate_state: string
-  project: string | null
-}
+import { api } from '@/types/api'
+import type { NotificationSettings } from '@/types/session-monitor'
 
 export interface NotificationPrefs {
   enabled: boolean
+  showOnPermission: boolean
+  showOnInput: boolean
   showOnComplete: boolean
   showOnError: boolean
 }
 
-const STORAGE_KEY = 'sessionly_notification_prefs'
+// Prefs used to live in localStorage; they are migrated into the backend rules once
+const LEGACY_STORAGE_KEY = 'sessionly_notification_prefs'
 
 const DEFAULT_PREFS: NotificationPrefs = {
   enabled: true,
+  showOnPermission: true,
+  showOnInput: true,
   showOnComplete: true,
   showOnError: true,
 }
 
-function loadPrefs(): NotificationPrefs {
-  try {
-    const raw = localStorage.getItem(STORAGE_KEY)
-    if (raw) return { ...DEFAULT_PREFS, ...JSON.parse(raw) }
-  } catch {
-    // ignore
-  }
-  return DEFAULT_PREFS
+function ruleEnabled(settings: NotificationSettings, ruleId: string): boolean {
+  return settings.rules.find((r) => r.id === ruleId)?.enabled ?? false
 }
 
-function savePrefs(prefs: NotificationPrefs) {
-  localStorage.setItem(STORAGE_KEY, JSON.stringify(prefs))
+function toPrefs(settings: NotificationSettings): NotificationPrefs {
+  return {
+    enabled: settings.enabled,
+    showOnPermission: ruleEnabled(settings, 'awaiting-permission'),
+    showOnInput: ruleEnabled(settings, 'awaiting-input'),
+    showOnComplete: ruleEnabled(settings, 'completed'),
+    showOnError: ruleEnabled(settings, 'error'),
+  }
 }
 
-async function ensurePermission(): Promise<boolean> {
-  let granted = await isPermissionGranted()
-  if (!granted) {
-    const result = await requestPermission()
-    granted = result === 'granted'
+function applyPrefs(
+  settings: NotificationSettings,
+  partial: Partial<NotificationPrefs>
+): NotificationSettings {
+  const toggles: Record<string, boolean | undefined> = {
+    'awaiting-permission': partial.showOnPermission,
+    'awaiting-input': partial.showOnInput,
+    completed: partial.showOnComplete,
+    error: partial.showOnError,
+  }
+  return {
+    ...settings,
+    enabled: partial.enabled ?? settings.enabled,
+    rules: settings.rules.map((r) => {
+      const enabled = toggles[r.id]
+      return enabled === undefined ? r : { ...r, enabled }
+    }),
   }
-  return granted
 }
 
-async function notify(title: string, body: string) {
-  try {
-    const granted = await ensurePermission()
-    console.log('[notification] permission granted:', granted)
-    if (granted) {
-      sendNotification({ title, body })
-      console.log('[notification] sent via plugin')
-      return
-    }
-  } catch (err) {
-    console.warn('[notification] plugin failed, using fallback:', err)
-  }
+async function loadSettings(): Promise<NotificationSettings> {
+  const settings = await api.notificationsGetSettings()
+  const raw = localStorage.getItem(LEGACY_STORAGE_KEY)
+  if (!raw) return settings
   try {
-    await invoke('send_native_notification', { title, body })
-    console.log('[notification] sent via osascript fallback')
-  } catch (e) {
-    console.error('[notification] all methods failed:', e)
+    const migrated = applyPrefs(settings, JSON.parse(raw))
+    await api.notificationsSetSettings(migrated)
+    localStorage.removeItem(LEGACY_STORAGE_KEY)
+    return migrated
+  } catch {
+    return settings
   }
 }
 
 export function useNotifications() {
-  const [prefs, setPrefs] = useState<NotificationPrefs>(loadPrefs)
-  const prefsRef = useRef(prefs)
+  const [settings, setSettings] = useState<NotificationSettings | null>(null)
+  const settingsRef = useRef(settings)
 
   useEffect(() => {
-    prefsRef.current = prefs
-    savePrefs(prefs)
-  }, [prefs])
-
-  const sendTest = useCallback(async () => {
-    await notify('Sessionly Test', 'Notifications are working!')
-  }, [])
-
-  const updatePrefs = useCallback((partial: Partial<NotificationPrefs>) => {
-    setPrefs((prev) => ({ ...prev, ...partial }))
+    loadSettings()
+      .then(setSettings)
+      .catch((e) => console.error('[notification] failed to load settings:', e))
   }, [])
 
-  // Listen for session state changes and fire notifications
-  // Cooldown per session to avoid rapid-fire duplicates (e.g. completed→working→completed)
   useEffect(() => {
-    const cooldowns = new Map<string, number>()
-    const COOLDOWN_MS = 5_000
+    settingsRef.current = settings
+  }, [settings])
 
-    const unlisten = listen<SessionStateEvent>('session-state-changed', (event) => {
-      const p = prefsRef.current
-      if (!p.enabled) return
+  /** Resolves to the backend that delivered the notification */
+  const sendTest = useCallback(
+    () => api.sendNativeNotification('Sessionly Test', 'Notifications are working!'),
+    []
+  )
 
-      const { session_id, state, project } = event.payload
-      const now = Date.now()
-      const key = `${session_id}:${state}`
-      const lastFired = cooldowns.get(key) ?? 0
-
-      if (now - lastFired < COOLDOWN_MS) return
+  const updatePrefs = useCallback((partial: Partial<NotificationPrefs>) => {
+    const current = settingsRef.current
+    if (!current) return
+    const next = applyPrefs(current, partial)
+    setSettings(next)
+    api
+      .notificationsSetSettings(next)
+      .catch((e) => console.error('[notification] failed to save settings:', e))
+  }, [])
 
-      if (state === 'completed' && p.showOnComplete) {
-        cooldowns.set(key, now)
-        const name = project || 'Claude Code'
-        notify(`${name} needs your attention`, 'Session is waiting for your input')
-      } else if (state === 'error' && p.showOnError) {
-        cooldowns.set(key, now)
-        const name = project || 'Claude Code'
-        notify(`${name} hit an error`, 'A tool failed — check the session')
-      }
+  // The backend decides what to notify about and delivers it; only failures surface here
+  useEffect(() => {
+    const unlisten = listen<string>('notification-failed', (event) => {
+      console.error('[notification] delivery failed:', event.payload)
     })
 
     return () => {
@@ -122,5 +111,6 @@ export function useNotifications() {
     }
   }, [])
 
+  const prefs = settings ? toPrefs(settings) : DEFAULT_PREFS
   return { prefs, updatePrefs, sendTest }
 }
@@ -0,0 +1,30 @@
+import { useEffect } from 'react'
+import { listen } from '@tauri-apps/api/event'
+import { useNavigation } from '@/contexts/NavigationContext'
+import { useAppDispatch } from '@/store/hooks'
+import { selectSession } from '@/store/slices/sessionsSlice'
+import type { OpenSessionRequest } from '@/types/session-monitor'
+
+/** Open sessions picked from the tray menu in the sessions page */
+export function useOpenSessionRequests() {
+  const { navigateTo } = useNavigation()
+  const dispatch = useAppDispatch()
+
+  useEffect(() => {
+    const unlisten = listen<OpenSessionRequest>('open-session', (e) => {
+      const { session_id, project_encoded, source } = e.payload
+      navigateTo('history')
+      dispatch(
+        selectSession({
+          sessionId: session_id,
+          projectEncoded: project_encoded,
+          source: source ?? undefined,
+        })
+      )
+    })
+
+    return () => {
+      unlisten.then((fn) => fn())
+    }
+  }, [navigateTo, dispatch])
+}
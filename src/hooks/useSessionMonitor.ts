@@ -1,6 +1,7 @@
 import { useEffect, useReducer, useRef } from 'react'
 import { listen } from '@tauri-apps/api/event'
-import type { SessionEvent, ProjectState } from '@/types/session-monitor'
+import { ACTIVE_STATES, type SessionEvent, type ProjectState } from '@/types/session-monitor'
+import { api } from '@/types/api'
 
 const STALE_THRESHOLD = 60 * 60 * 1000 // 1 hour
 
@@ -10,6 +11,7 @@ interface SessionStateEvent {
   state: string
   aggregate_state: string
   project: string | null
+  message: string | null
 }
 
 interface State {
@@ -62,6 +64,26 @@ export function useSessionMonitor() {
   const [state, dispatch] = useReducer(reducer, { projects: [] })
   const activeSessionsRef = useRef(new Map<string, string>())
 
+  // Restore sessions the backend was already tracking (e.g. across an app restart),
+  // oldest first so the most recent ends up on top
+  useEffect(() => {
+    api.monitorGetSessions().then((sessions) => {
+      for (const s of [...sessions].reverse()) {
+        activeSessionsRef.current.set(s.session_id, s.state)
+        dispatch({
+          type: 'event',
+          event: {
+            sessionId: s.session_id,
+            prevState: s.state,
+            state: s.state,
+            project: s.project || 'Unknown Project',
+            timestamp: s.last_update,
+          },
+        })
+      }
+    })
+  }, [])
+
   useEffect(() => {
     const unlisten = listen<SessionStateEvent>('session-state-changed', (e) => {
       const { session_id, prev_state, state, project } = e.payload
@@ -90,7 +112,7 @@ export function useSessionMonitor() {
   }, [])
 
   const aggregateActive = state.projects.filter(
-    (p) => !p.isStale && p.latestState === 'working',
+    (p) => !p.isStale && ACTIVE_STATES.includes(p.latestState),
   ).length
 
   const aggregateQuiet = aggregateActive === 0
@@ -65,8 +65,12 @@ export function SettingsPage() {
   const handleTestNotification = async () => {
     clearTimeout(testTimerRef.current)
     setTestResult(null)
-    await sendTest()
-    setTestResult('Sent! Check Notification Center if you don\u2019t see it.')
+    try {
+      const backend = await sendTest()
+      setTestResult(`Sent via ${backend}. Check your notification center if you don\u2019t see it.`)
+    } catch (e) {
+      setTestResult(`Failed to send: ${e}`)
+    }
     testTimerRef.current = setTimeout(() => setTestResult(null), 6000)
   }
 
@@ -135,6 +139,32 @@ export function SettingsPage() {
 
         {/* Per-event toggles */}
         <div className="space-y-3 pl-1">
+          <div className="flex items-center justify-between">
+            <div>
+              <span className="text-sm font-medium">Permission needed</span>
+              <p className="text-xs text-muted-foreground">
+                Claude Code is waiting for you to approve a tool call
+              </p>
+            </div>
+            <Toggle
+              checked={prefs.showOnPermission}
+              onChange={() => updatePrefs({ showOnPermission: !prefs.showOnPermission })}
+              disabled={!prefs.enabled}
+            />
+          </div>
+          <div className="flex items-center justify-between">
+            <div>
+              <span className="text-sm font-medium">Input needed</span>
+              <p className="text-xs text-muted-foreground">
+                Claude Code asked a question or has been idle waiting for you
+              </p>
+            </div>
+            <Toggle
+              checked={prefs.showOnInput}
+              onChange={() => updatePrefs({ showOnInput: !prefs.showOnInput })}
+              disabled={!prefs.enabled}
+            />
+          </div>
           <div className="flex items-center justify-between">
             <div>
               <span className="text-sm font-medium">Session completed</span>
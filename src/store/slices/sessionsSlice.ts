@@ -1,5 +1,13 @@
 import { createSlice, createAsyncThunk, createSelector, type PayloadAction } from '@reduxjs/toolkit'
-import type { Session, ProjectGroup } from '@/types/session-types'
+import type {
+  Session,
+  ProjectGroup,
+  SessionSummary,
+  SessionMessagesAppended,
+  SessionRemoved,
+  SubagentUpdated,
+  TokenUsage,
+} from '@/types/session-types'
 import type { RootState } from '@/store'
 import { api } from '@/types/api'
 
@@ -7,6 +15,33 @@ const pendingPersists: Map<string, { value: string; timeout: ReturnType<typeof s
   new Map()
 const PERSIST_DEBOUNCE_MS = 300
 
+/** Identifies a project across data roots; the default root keeps the bare name */
+export function projectKey(group: Pick<ProjectGroup, 'projectEncoded' | 'source'>): string {
+  return !group.source || group.source === 'default'
+    ? group.projectEncoded
+    : `${group.source}:${group.projectEncoded}`
+}
+
+/** Recompute a project's totals after its sessions changed */
+function updateGroupTotals(group: ProjectGroup) {
+  const usage: TokenUsage = {
+    inputTokens: 0,
+    outputTokens: 0,
+    cacheCreationInputTokens: 0,
+    cacheReadInputTokens: 0,
+  }
+  let costUsd: number | null = null
+  for (const session of group.sessions) {
+    usage.inputTokens += session.usage.inputTokens
+    usage.outputTokens += session.usage.outputTokens
+    usage.cacheCreationInputTokens += session.usage.cacheCreationInputTokens
+    usage.cacheReadInputTokens += session.usage.cacheReadInputTokens
+    if (session.costUsd !== null) costUsd = (costUsd ?? 0) + session.costUsd
+  }
+  group.usage = usage
+  group.costUsd = costUsd
+}
+
 function debouncedPersist(key: string, value: unknown) {
   const serialized = JSON.stringify(value)
   const pending = pendingPersists.get(key)
@@ -29,6 +64,7 @@ interface SessionsState {
   currentSession: Session | null
   selectedSessionId: string | null
   selectedProjectEncoded: string | null
+  selectedSource: string | null
   isLoading: boolean
   isLoadingSession: boolean
   error: string | null
@@ -60,6 +96,7 @@ const initialState: SessionsState = {
   currentSession: null,
   selectedSessionId: null,
   selectedProjectEncoded: null,
+  selectedSource: null,
   isLoading: false,
   isLoadingSession: false,
   error: null,
@@ -82,17 +119,40 @@ export const fetchSessions = createAsyncThunk(
 export const fetchSession = createAsyncThunk(
   'sessions/fetchOne',
   async (
-    { sessionId, projectEncoded }: { sessionId: string; projectEncoded: string },
+    {
+      sessionId,
+      projectEncoded,
+      source,
+    }: { sessionId: string; projectEncoded: string; source?: string },
     { rejectWithValue }
   ) => {
     try {
-      return await api.sessionsGet(sessionId, projectEncoded)
+      return await api.sessionsGet(sessionId, projectEncoded, source)
     } catch (error) {
       return rejectWithValue(error instanceof Error ? error.message : 'Failed to fetch session')
     }
   }
 )
 
+/** Start tailing the open session, catching up on anything appended since it was loaded */
+export const watchSession = createAsyncThunk(
+  'sessions/watch',
+  async (
+    {
+      sessionId,
+      projectEncoded,
+      source,
+    }: { sessionId: string; projectEncoded: string; source: string },
+    { getState, dispatch }
+  ) => {
+    const session = (getState() as RootState).sessions.currentSession
+    const knownMessages =
+      session?.id === sessionId && session.source === source ? session.messages.length : 0
+    const update = await api.sessionsWatch(sessionId, projectEncoded, source, knownMessages)
+    dispatch(sessionMessagesAppended({ ...update, sessionId, projectEncoded, source }))
+  }
+)
+
 export const refreshSessions = createAsyncThunk(
   'sessions/refresh',
   async (_, { dispatch, rejectWithValue }) => {
@@ -111,17 +171,76 @@ const sessionsSlice = createSlice({
   reducers: {
     selectSession: (
       state,
-      action: PayloadAction<{ sessionId: string; projectEncoded: string } | null>
+      action: PayloadAction<{ sessionId: string; projectEncoded: string; source?: string } | null>
     ) => {
       if (action.payload) {
         state.selectedSessionId = action.payload.sessionId
         state.selectedProjectEncoded = action.payload.projectEncoded
+        state.selectedSource = action.payload.source ?? null
       } else {
         state.selectedSessionId = null
         state.selectedProjectEncoded = null
+        state.selectedSource = null
         state.currentSession = null
       }
     },
+    sessionMessagesAppended: (state, action: PayloadAction<SessionMessagesAppended>) => {
+      const session = state.currentSession
+      const update = action.payload
+      if (!session || session.id !== update.sessionId || session.source !== update.source) return
+      if (update.reset) {
+        session.messages = update.messages
+      } else {
+        session.messages.push(...update.messages)
+      }
+      for (const [toolUseId, result] of Object.entries(update.toolResults)) {
+        const owner = session.messages.find((m) =>
+          m.toolUseBlocks.some((block) => block.id === toolUseId)
+        )
+        if (owner) owner.toolResults[toolUseId] = result
+      }
+    },
+    subagentUpdated: (state, action: PayloadAction<SubagentUpdated>) => {
+      const session = state.currentSession
+      const { sessionId, source, subagent } = action.payload
+      if (!session || session.id !== sessionId || session.source !== source) return
+      session.subagents[subagent.agentId] = subagent
+    },
+    sessionSummaryUpdated: (state, action: PayloadAction<SessionSummary>) => {
+      const summary = action.payload
+      let group = state.projectGroups.find(
+        (g) => g.projectEncoded === summary.projectEncoded && g.source === summary.source
+      )
+      if (!group) {
+        group = {
+          project: summary.project,
+          projectEncoded: summary.projectEncoded,
+          source: summary.source,
+          sessions: [],
+          usage: summary.usage,
+          costUsd: null,
+        }
+        state.projectGroups.unshift(group)
+      }
+      const sessions = group.sessions.filter((s) => s.id !== summary.id)
+      sessions.push(summary)
+      sessions.sort((a, b) => (b.startTime ?? 0) - (a.startTime ?? 0))
+      group.sessions = sessions
+      updateGroupTotals(group)
+    },
+    sessionRemoved: (state, action: PayloadAction<SessionRemoved>) => {
+      const { sessionId, projectEncoded, source } = action.payload
+      const group = state.projectGroups.find(
+        (g) => g.projectEncoded === projectEncoded && g.source === source
+      )
+      if (!group) return
+      group.sessions = group.sessions.filter((s) => s.id !== sessionId)
+      if (group.sessions.length === 0) {
+        state.projectGroups = state.projectGroups.filter((g) => g !== group)
+      } else {
+        updateGroupTotals(group)
+      }
+    },
     clearCurrentSession: (state) => {
       state.currentSession = null
     },
@@ -192,6 +311,10 @@ const sessionsSlice = createSlice({
 
 export const {
   selectSession,
+  sessionMessagesAppended,
+  subagentUpdated,
+  sessionSummaryUpdated,
+  sessionRemoved,
   clearCurrentSession,
   clearError,
   hideProject,
@@ -212,7 +335,7 @@ export const selectVisibleProjectGroups = createSelector(
   (projectGroups, hiddenProjects, hiddenSessions, showHidden): ProjectGroup[] => {
     if (showHidden) return projectGroups
     return projectGroups
-      .filter((group) => !hiddenProjects.includes(group.projectEncoded))
+      .filter((group) => !hiddenProjects.includes(projectKey(group)))
       .map((group) => ({
         ...group,
         sessions: group.sessions.filter((s) => !hiddenSessions.includes(s.id)),
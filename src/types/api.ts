@@ -2,7 +2,36 @@
  * Tauri API bridge - replaces window.electron with Tauri invoke calls
  */
 import { invoke } from '@tauri-apps/api/core'
-import type { ProjectGroup, Session, HookStatus } from './session-types'
+import type {
+  ProjectGroup,
+  Session,
+  HookStatus,
+  HookServerSettings,
+  SearchQuery,
+  SearchHit,
+  PriceTable,
+  UsagePeriod,
+  UsageBucket,
+  StatsWindow,
+  Statistics,
+  RedactionConfig,
+  RedactionReport,
+  SessionExport,
+  DataRoot,
+  TailUpdate,
+} from './session-types'
+import type {
+  TrackedSessionInfo,
+  ActivityEntry,
+  ActivitySettings,
+  LatencyStats,
+  StuckCall,
+  ToolTimingSettings,
+  NotificationSettings,
+  WebhookEndpoint,
+  WebhookSettings,
+  DeliveryRecord,
+} from './session-monitor'
 
 export const api = {
   // App
@@ -13,16 +42,76 @@ export const api = {
 
   // Sessions
   sessionsGetAll: () => invoke<ProjectGroup[]>('get_projects'),
-  sessionsGet: (sessionId: string, projectEncoded: string) =>
-    invoke<Session>('get_session', { sessionId, projectEncoded }),
+  sessionsGet: (sessionId: string, projectEncoded: string, source?: string) =>
+    invoke<Session>('get_session', { sessionId, projectEncoded, source }),
   sessionsRefresh: () => Promise.resolve(), // Just re-fetch
-  sessionsExportMarkdown: (sessionId: string, projectEncoded: string) =>
-    invoke<string>('export_session_markdown', { sessionId, projectEncoded }),
+  sessionsRebuildIndex: () => invoke<ProjectGroup[]>('rebuild_session_index'),
+  dataRootsGet: () => invoke<DataRoot[]>('get_data_roots'),
+  dataRootsSet: (roots: DataRoot[]) => invoke<void>('set_data_roots', { roots }),
+  sessionsSearch: (query: SearchQuery) => invoke<SearchHit[]>('search_sessions', { query }),
+  sessionsWatch: (
+    sessionId: string,
+    projectEncoded: string,
+    source: string | undefined,
+    knownMessages: number
+  ) => invoke<TailUpdate>('watch_session', { sessionId, projectEncoded, source, knownMessages }),
+  sessionsUnwatch: (sessionId: string, source: string) =>
+    invoke<void>('unwatch_session', { sessionId, source }),
+  sessionsReadAppended: (sessionId: string, source: string) =>
+    invoke<void>('refresh_session', { sessionId, source }),
+  sessionsExportMarkdown: (sessionId: string, projectEncoded: string, source?: string) =>
+    invoke<SessionExport>('export_session_markdown', { sessionId, projectEncoded, source }),
+  sessionsExportHtml: (sessionId: string, projectEncoded: string, source?: string) =>
+    invoke<SessionExport>('export_session_html', { sessionId, projectEncoded, source }),
+  sessionsExportJson: (sessionId: string, projectEncoded: string, source?: string) =>
+    invoke<SessionExport>('export_session_json', { sessionId, projectEncoded, source }),
+  sessionsExportJsonl: (sessionId: string, projectEncoded: string, source?: string) =>
+    invoke<SessionExport>('export_session_jsonl', { sessionId, projectEncoded, source }),
+
+  // Redaction
+  redactionPreview: (sessionId: string, projectEncoded: string, source?: string) =>
+    invoke<RedactionReport>('preview_redaction', { sessionId, projectEncoded, source }),
+  redactionGetConfig: () => invoke<RedactionConfig>('get_redaction_config'),
+  redactionSetConfig: (config: RedactionConfig) => invoke<void>('set_redaction_config', { config }),
+  redactionGetDetectors: () => invoke<string[]>('get_redaction_detectors'),
+
+  // Usage & cost
+  usageGetReport: (period: UsagePeriod) => invoke<UsageBucket[]>('get_usage_report', { period }),
+  statsGet: (window: StatsWindow) => invoke<Statistics>('get_statistics', { window }),
+  pricingGet: () => invoke<PriceTable>('get_price_table'),
+  pricingSet: (table: PriceTable) => invoke<void>('set_price_table', { table }),
 
   // Hooks
+  monitorGetSessions: () => invoke<TrackedSessionInfo[]>('get_monitored_sessions'),
+  monitorGetActivity: (sessionId: string) =>
+    invoke<ActivityEntry[]>('get_session_activity', { sessionId }),
+  monitorGetActivitySettings: () => invoke<ActivitySettings>('get_activity_settings'),
+  monitorSetActivitySettings: (settings: ActivitySettings) =>
+    invoke<void>('set_activity_settings', { settings }),
+  /** `project` is a project's full working directory */
+  monitorGetToolLatency: (project?: string) =>
+    invoke<Record<string, LatencyStats>>('get_tool_latency', { project }),
+  monitorGetStuckToolCalls: () => invoke<StuckCall[]>('get_stuck_tool_calls'),
+  monitorGetToolTimingSettings: () => invoke<ToolTimingSettings>('get_tool_timing_settings'),
+  monitorSetToolTimingSettings: (settings: ToolTimingSettings) =>
+    invoke<void>('set_tool_timing_settings', { settings }),
+  notificationsGetSettings: () => invoke<NotificationSettings>('get_notification_settings'),
+  notificationsSetSettings: (settings: NotificationSettings) =>
+    invoke<void>('set_notification_settings', { settings }),
+  sendNativeNotification: (title: string, body: string) =>
+    invoke<string>('send_native_notification', { title, body }),
+  webhooksGetSettings: () => invoke<WebhookSettings>('get_webhook_settings'),
+  webhooksSetSettings: (settings: WebhookSettings) =>
+    invoke<void>('set_webhook_settings', { settings }),
+  webhooksGetDeliveries: () => invoke<DeliveryRecord[]>('get_webhook_deliveries'),
+  webhooksTest: (endpoint: WebhookEndpoint) => invoke<DeliveryRecord>('test_webhook', { endpoint }),
   hooksGetStatus: () => invoke<HookStatus>('hooks_get_status'),
   hooksInstall: () => invoke<void>('hooks_install'),
   hooksUninstall: () => invoke<void>('hooks_uninstall'),
   hooksIsInstalled: () => invoke<boolean>('hooks_is_installed'),
+  hooksGetServerSettings: () => invoke<HookServerSettings>('get_hook_server_settings'),
+  hooksSetServerSettings: (settings: HookServerSettings) =>
+    invoke<HookStatus>('set_hook_server_settings', { settings }),
+  hooksRotateToken: () => invoke<void>('rotate_hook_token'),
 
 }
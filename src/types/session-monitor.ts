@@ -1,3 +1,6 @@
+/** Monitor states in which a session is mid-turn */
+export const ACTIVE_STATES = ['thinking', 'working', 'subagent', 'compacting']
+
 export interface SessionEvent {
   sessionId: string
   prevState: string
@@ -13,3 +16,143 @@ export interface ProjectState {
   lastActivity: number
   isStale: boolean
 }
+
+/** A session tracked by the backend monitor, as returned by get_monitored_sessions */
+export interface TrackedSessionInfo {
+  session_id: string
+  state: string
+  project: string | null
+  /** Directory under the projects dir holding the transcript, if known */
+  project_encoded: string | null
+  /** Label of the data root holding the transcript, if known */
+  source: string | null
+  last_update: number
+  /** When the session entered its current state */
+  state_since: number
+}
+
+/** Emitted when a session is picked from the tray menu */
+export interface OpenSessionRequest {
+  session_id: string
+  project_encoded: string
+  source: string | null
+}
+
+/** One hook event in a session's activity timeline */
+export interface ActivityEntry {
+  timestamp: number
+  event: string
+  tool_name: string | null
+  tool_use_id: string | null
+  summary: string | null
+  state: string | null
+  duration_ms: number | null
+}
+
+/** Payload of the `session-activity` event */
+export interface ActivityEvent {
+  session_id: string
+  entry: ActivityEntry
+}
+
+export interface ActivitySettings {
+  persist: boolean
+  capacity: number
+  /** Delete saved timelines not written to for this many days; 0 keeps them */
+  retention_days: number
+}
+
+export interface LatencyStats {
+  count: number
+  failures: number
+  total_ms: number
+  min_ms: number | null
+  max_ms: number | null
+  avg_ms: number | null
+}
+
+/** A tool call running past the stuck threshold; also the `tool-call-stuck` event payload */
+export interface StuckCall {
+  session_id: string
+  tool_use_id: string
+  tool_name: string
+  /** Project name, the last component of `cwd` */
+  project: string | null
+  cwd: string | null
+  summary: string | null
+  started_at: number
+  elapsed_ms: number
+}
+
+export interface ToolTimingSettings {
+  stuck_threshold_secs: number
+}
+
+export interface NotificationRule {
+  id: string
+  name: string
+  enabled: boolean
+  /** Project name to match; any project if null */
+  project: string | null
+  /** Previous states to match; any state if empty */
+  from: string[]
+  to: string[]
+  min_session_secs: number | null
+  /** Templates with `{project}`, `{state}` and `{message}` placeholders */
+  title: string | null
+  body: string | null
+}
+
+export interface QuietHours {
+  enabled: boolean
+  /** "HH:MM", local time */
+  start: string
+  end: string
+}
+
+export interface NotificationSettings {
+  enabled: boolean
+  rules: NotificationRule[]
+  quiet_hours: QuietHours
+  muted_projects: string[]
+  dedupe_secs: number
+}
+
+/** Payload of the `session-notification` event */
+export interface SessionNotification {
+  rule_id: string
+  session_id: string
+  project: string | null
+  title: string
+  body: string
+}
+
+export interface WebhookEndpoint {
+  id: string
+  name: string
+  url: string
+  enabled: boolean
+  /** `state:<state>` or `hook:<HookEventName>`; `*` matches any state or hook event */
+  events: string[]
+  /** Project names to forward events for; all projects if empty */
+  projects: string[]
+  /** JSON body template with `{{field}}` placeholders; the event as JSON if null */
+  template: string | null
+  headers: Record<string, string>
+  max_retries: number
+}
+
+export interface WebhookSettings {
+  endpoints: WebhookEndpoint[]
+}
+
+export interface DeliveryRecord {
+  endpoint_id: string
+  event: string
+  session_id: string
+  timestamp: string
+  attempts: number
+  delivered: boolean
+  status: number | null
+  error: string | null
+}
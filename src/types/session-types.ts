@@ -29,6 +29,13 @@ export interface ToolResultBlock {
 
 export type ContentBlock = { type: 'text'; text: string } | ThinkingBlock | ToolUseBlock | ToolResultBlock
 
+export interface TokenUsage {
+  inputTokens: number
+  outputTokens: number
+  cacheCreationInputTokens: number
+  cacheReadInputTokens: number
+}
+
 export interface ProcessedMessage {
   uuid: string
   parentUuid: string | null
@@ -39,6 +46,9 @@ export interface ProcessedMessage {
   toolUseBlocks: ToolUseBlock[]
   toolResults: Record<string, ToolResultBlock>
   model?: string
+  /** API message id; shared by the entries an assistant reply is split into */
+  messageId?: string
+  usage?: TokenUsage
 }
 
 export interface SubagentSession {
@@ -52,6 +62,8 @@ export interface SessionSummary {
   id: string
   project: string
   projectEncoded: string
+  /** Label of the data root the session was read from */
+  source: string
   firstMessage: string
   messageCount: number
   startTime: number | null
@@ -59,12 +71,25 @@ export interface SessionSummary {
   gitBranch: string | null
   model: string | null
   filePath: string
+  usage: TokenUsage
+  modelUsage: Record<string, TokenUsage>
+  /** Usage per model in 15-minute windows, including subagents */
+  usageSlices: UsageSlice[]
+  costUsd: number | null
+}
+
+export interface UsageSlice {
+  /** Start of the window, ms since epoch */
+  start: number
+  model: string
+  usage: TokenUsage
 }
 
 export interface Session {
   id: string
   project: string
   projectEncoded: string
+  source: string
   gitBranch: string | null
   cwd: string
   version: string
@@ -78,7 +103,94 @@ export interface Session {
 export interface ProjectGroup {
   project: string
   projectEncoded: string
+  source: string
   sessions: SessionSummary[]
+  usage: TokenUsage
+  costUsd: number | null
+}
+
+/** A directory Claude Code keeps sessions in, besides the Claude config directory */
+export interface DataRoot {
+  label: string
+  /** Directory containing `projects/` */
+  path: string
+  enabled: boolean
+}
+
+export interface ModelPrice {
+  input: number
+  output: number
+  cacheWrite: number
+  cacheRead: number
+}
+
+export interface PriceTable {
+  models: Record<string, ModelPrice>
+}
+
+export type UsagePeriod = 'day' | 'week' | 'month'
+
+export interface UsageBucket {
+  period: string
+  sessionCount: number
+  usage: TokenUsage
+  costUsd: number | null
+}
+
+export interface TailUpdate {
+  reset: boolean
+  messages: ProcessedMessage[]
+  toolResults: Record<string, ToolResultBlock>
+  agentLinks: Record<string, string>
+}
+
+/** Payload of `session-messages-appended` for a watched session */
+export interface SessionMessagesAppended extends TailUpdate {
+  sessionId: string
+  projectEncoded: string
+  source: string
+}
+
+/** Payload of `session-removed` when a session file is deleted */
+export interface SessionRemoved {
+  sessionId: string
+  projectEncoded: string
+  source: string
+}
+
+/** Payload of `session-subagent-updated` for a watched session */
+export interface SubagentUpdated {
+  sessionId: string
+  projectEncoded: string
+  source: string
+  subagent: SubagentSession
+}
+
+export interface SearchQuery {
+  query: string
+  project?: string
+  since?: number
+  until?: number
+  role?: 'user' | 'assistant'
+  toolName?: string
+  limit?: number
+}
+
+export interface SearchHit {
+  sessionId: string
+  project: string
+  projectEncoded: string
+  source: string
+  messageUuid: string
+  agentId: string | null
+  role: 'user' | 'assistant'
+  timestamp: string
+  field: 'text' | 'thinking' | 'toolInput' | 'toolResult'
+  toolName: string | null
+  snippet: string
+  /** [start, end) ranges in `snippet`, as UTF-16 offsets for `slice` */
+  highlights: [number, number][]
+  score: number
 }
 
 export interface HookStatus {
@@ -86,3 +198,60 @@ export interface HookStatus {
   port: number
   hooksInstalled: boolean
 }
+
+export interface HookServerSettings {
+  port: number
+  bindAddress: string
+}
+
+export interface StatsWindow {
+  since?: number
+  until?: number
+}
+
+export interface ToolStats {
+  calls: number
+  errors: number
+  errorRate: number
+}
+
+export interface UsageStats {
+  sessionCount: number
+  messageCount: number
+  avgMessagesPerSession: number
+  avgDurationMs: number | null
+  sessionsPerDay: Record<string, number>
+  toolCalls: Record<string, ToolStats>
+  models: Record<string, number>
+}
+
+export interface Statistics {
+  overall: UsageStats
+  /** Keyed by `projectKey` */
+  byProject: Record<string, UsageStats>
+  byBranch: Record<string, UsageStats>
+}
+
+export interface CustomPattern {
+  name: string
+  pattern: string
+}
+
+export interface RedactionConfig {
+  enabled: boolean
+  disabledDetectors: string[]
+  customPatterns: CustomPattern[]
+  anonymizePaths: boolean
+}
+
+export interface RedactionReport {
+  total: number
+  byDetector: Record<string, number>
+  pathsAnonymized: number
+}
+
+/** A rendered session export and what was redacted from it */
+export interface SessionExport {
+  content: string
+  report: RedactionReport
+}